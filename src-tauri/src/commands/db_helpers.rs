@@ -1,35 +1,57 @@
 //! 数据库操作辅助函数和宏
 //! 消除命令文件中重复的数据库连接和 row 映射模式
 
+use crate::error::AppError;
 use crate::types::*;
-use rusqlite::{Row, Result as SqliteResult};
+use rusqlite::{params, Connection, Row, Result as SqliteResult};
 
-/// 获取数据库连接的简写模式，返回错误信息字符串
-/// 使用示例: with_db!(conn, { conn.prepare(...) })
+/// 获取工作区数据库连接的简写模式，获取失败时返回 AppError
+/// 使用示例: with_db!(workspace, conn, { conn.prepare(...) })
 #[macro_export]
 macro_rules! with_db {
-    ($conn:ident, $body:block) => {{
-        let db_guard =
-            $crate::db::get_db().map_err(|e| format!("获取数据库失败: {}", e))?;
-        let $conn = db_guard.as_ref().ok_or("数据库未初始化")?;
-        let __result = { $body };
-        drop(db_guard);
-        __result
-    }};
+    ($workspace:expr, $conn:ident, $body:block) => {{
+            let db_guard = $crate::db::get_db($workspace.path()).map_err(|e| {
+                $crate::error::AppError::Db(format!("获取数据库失败: {}", e))
+            })?;
+            let $conn = db_guard.as_ref().ok_or_else(|| {
+                $crate::error::AppError::WorkspaceNotOpen("数据库未初始化".to_string())
+            })?;
+            let __result = { $body };
+            drop(db_guard);
+            __result
+        }};
 }
 
 /// 获取可变数据库连接的简写模式，用于需要事务的操作
-/// 使用示例: with_db_mut!(conn, { let tx = conn.transaction()?; ... })
+/// 使用示例: with_db_mut!(workspace, conn, { let tx = conn.transaction()?; ... })
 #[macro_export]
 macro_rules! with_db_mut {
-    ($conn:ident, $body:block) => {{
-        let mut db_guard =
-            $crate::db::get_db().map_err(|e| format!("获取数据库失败: {}", e))?;
-        let $conn = (&mut *db_guard).as_mut().ok_or("数据库未初始化")?;
-        let __result = { $body };
-        drop(db_guard);
-        __result
-    }};
+    ($workspace:expr, $conn:ident, $body:block) => {{
+            let mut db_guard = $crate::db::get_db($workspace.path()).map_err(|e| {
+                $crate::error::AppError::Db(format!("获取数据库失败: {}", e))
+            })?;
+            let $conn = db_guard.as_mut().ok_or_else(|| {
+                $crate::error::AppError::WorkspaceNotOpen("数据库未初始化".to_string())
+            })?;
+            let __result = { $body };
+            drop(db_guard);
+            __result
+        }};
+}
+
+/// 按仓库 ID 查询本地路径，统一"仓库不存在"的错误
+pub fn repo_path(conn: &Connection, repo_id: &str) -> Result<String, AppError> {
+    conn.query_row(
+        "SELECT path FROM git_repositories WHERE id = ?1",
+        params![repo_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => {
+            AppError::NotFound(format!("仓库不存在: {}", repo_id))
+        }
+        e => AppError::Db(format!("查询仓库失败: {}", e)),
+    })
 }
 
 /// 辅助函数：从 Row 中解析 ide_override_json 字段
@@ -53,7 +75,8 @@ pub fn parse_optional_json<T: serde::de::DeserializeOwned>(
 
 /// 从 git_repositories 行映射为 GitRepository
 /// cols: id, project_id, name, path, folder, remote_url, branch, description,
-///       last_sync_at, last_status_checked_at, ide_override_json(idx=10), sort_order
+///       last_sync_at, last_status_checked_at, ide_override_json(idx=10), sort_order, custom_name,
+///       last_commit_sha, last_commit_summary, last_commit_at
 pub fn map_git_repository_row(row: &Row) -> SqliteResult<GitRepository> {
     Ok(GitRepository {
         id: row.get(0)?,
@@ -68,11 +91,15 @@ pub fn map_git_repository_row(row: &Row) -> SqliteResult<GitRepository> {
         last_status_checked_at: row.get(9)?,
         ide_override: parse_ide_override(row, 10),
         sort_order: row.get(11)?,
+        custom_name: row.get(12)?,
+        last_commit_sha: row.get(13)?,
+        last_commit_summary: row.get(14)?,
+        last_commit_at: row.get(15)?,
     })
 }
 
 /// 从 projects 行映射为 Project
-/// cols: id, name, description, project_path, display_json(idx=4), ide_override_json(idx=5), visible, updated_at
+/// cols: id, name, description, project_path, display_json(idx=4), ide_override_json(idx=5), visible, updated_at, archived
 pub fn map_project_row(row: &Row) -> SqliteResult<Project> {
     Ok(Project {
         id: row.get(0)?,
@@ -83,6 +110,10 @@ pub fn map_project_row(row: &Row) -> SqliteResult<Project> {
         ide_override: parse_optional_json(row, 5),
         visible: row.get(6)?,
         updated_at: row.get(7)?,
+        archived: row.get(8)?,
+        sort_order: row.get(9)?,
+        created_at: row.get(10)?,
+        tags: Vec::new(),
     })
 }
 
@@ -159,3 +190,27 @@ pub fn map_task_column_row(row: &Row) -> SqliteResult<TaskColumn> {
         updated_at: row.get(8)?,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SCHEMA;
+
+    #[test]
+    fn test_repo_path_reports_missing_repo() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute(
+            "INSERT INTO git_repositories (id, project_id, name, path, created_at, updated_at)
+             VALUES ('r1', 'p1', 'api', '/tmp/api', '2024-01-01', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(repo_path(&conn, "r1").unwrap(), "/tmp/api");
+        assert_eq!(
+            repo_path(&conn, "missing"),
+            Err(AppError::NotFound("仓库不存在: missing".to_string()))
+        );
+    }
+}
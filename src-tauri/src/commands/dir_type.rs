@@ -1,21 +1,32 @@
+use crate::commands::db_helpers::repo_path;
+use crate::commands::filesystem::{base64_encode, move_to_trash, resolve_in_project};
 use crate::commands::project::project_get;
-use crate::commands::workspace::load_global_settings;
+use crate::commands::workspace::{load_global_settings, Workspace};
+use crate::error::AppError;
 use crate::types::*;
-use crate::with_db;
+use crate::{with_db, with_db_mut};
 use chrono::Utc;
 use rusqlite::params;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
+use tauri::Window;
 use which::which;
 
 /// 列出所有目录类型
 #[tauri::command]
-pub fn dir_types_list() -> Result<Vec<DirectoryType>, String> {
-    with_db!(conn, {
+pub fn dir_types_list(workspace: Workspace) -> Result<Vec<DirectoryType>, AppError> {
+    with_db!(workspace, conn, {
     let mut stmt = conn
-        .prepare("SELECT id, kind, name, category, sort_order, created_at, updated_at FROM directory_types ORDER BY sort_order")
-        .map_err(|e| format!("查询失败: {}", e))?;
+        .prepare(
+            "SELECT t.id, t.kind, t.name, t.category, t.sort_order, t.created_at, t.updated_at,
+                    COUNT(DISTINCT d.project_id)
+             FROM directory_types t
+             LEFT JOIN project_directories d ON d.dir_type_id = t.id
+             GROUP BY t.id
+             ORDER BY t.sort_order",
+        )
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
 
     let types = stmt
         .query_map([], |row| {
@@ -36,11 +47,12 @@ pub fn dir_types_list() -> Result<Vec<DirectoryType>, String> {
                 sort_order: row.get(4)?,
                 created_at: row.get(5)?,
                 updated_at: row.get(6)?,
+                usage_count: row.get(7)?,
             })
         })
-        .map_err(|e| format!("查询失败: {}", e))?
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("读取数据失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
 
     Ok(types)
 })
@@ -48,11 +60,14 @@ pub fn dir_types_list() -> Result<Vec<DirectoryType>, String> {
 
 /// 创建自定义目录类型
 #[tauri::command]
-pub fn dir_type_create_custom(input: serde_json::Value) -> Result<DirectoryType, String> {
+pub fn dir_type_create_custom(
+    workspace: Workspace,
+    input: serde_json::Value,
+) -> Result<DirectoryType, AppError> {
     let name = input
         .get("name")
         .and_then(|v| v.as_str())
-        .ok_or("缺少名称")?
+        .ok_or_else(|| AppError::Validation("缺少名称".to_string()))?
         .to_string();
 
     let category = input
@@ -68,13 +83,13 @@ pub fn dir_type_create_custom(input: serde_json::Value) -> Result<DirectoryType,
     let id = uuid::Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute(
             "INSERT INTO directory_types (id, kind, name, category, sort_order, created_at, updated_at)
              VALUES (?1, 'custom', ?2, ?3, ?4, ?5, ?6)",
             params![id, name, category, sort_order, now, now],
         )
-        .map_err(|e| format!("创建目录类型失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("创建目录类型失败: {}", e)))?;
 
         Ok(DirectoryType {
             id,
@@ -84,22 +99,40 @@ pub fn dir_type_create_custom(input: serde_json::Value) -> Result<DirectoryType,
             sort_order,
             created_at: now.clone(),
             updated_at: now,
+            usage_count: 0,
         })
     })
 }
 
 /// 更新目录类型
 #[tauri::command]
-pub fn dir_type_update(id: String, patch: serde_json::Value) -> Result<DirectoryType, String> {
-    with_db!(conn, {
+pub fn dir_type_update(
+    workspace: Workspace,
+    id: String,
+    patch: serde_json::Value,
+) -> Result<DirectoryType, AppError> {
+    with_db!(workspace, conn, { update_dir_type_with_conn(conn, id, &patch) })
+}
+
+fn update_dir_type_with_conn(
+    conn: &rusqlite::Connection,
+    id: String,
+    patch: &serde_json::Value,
+) -> Result<DirectoryType, AppError> {
     // 获取当前类型
-    let (old_name, old_category, old_sort_order): (String, Option<String>, i32) = conn
+    let (kind_str, old_name, old_category, old_sort_order, created_at): (
+        String,
+        String,
+        Option<String>,
+        i32,
+        String,
+    ) = conn
         .query_row(
-            "SELECT name, category, sort_order FROM directory_types WHERE id = ?1",
+            "SELECT kind, name, category, sort_order, created_at FROM directory_types WHERE id = ?1",
             params![id],
-            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
         )
-        .map_err(|e| format!("目录类型不存在: {}", e))?;
+        .map_err(|e| AppError::lookup(&e, format!("目录类型不存在: {}", e)))?;
 
     let name = patch
         .get("name")
@@ -125,16 +158,15 @@ pub fn dir_type_update(id: String, patch: serde_json::Value) -> Result<Directory
         "UPDATE directory_types SET name = ?1, category = ?2, sort_order = ?3, updated_at = ?4 WHERE id = ?5",
         params![name, category, sort_order, now, id],
     )
-    .map_err(|e| format!("更新目录类型失败: {}", e))?;
+    .map_err(|e| AppError::Db(format!("更新目录类型失败: {}", e)))?;
 
-    // 获取 kind
-    let kind_str: String = conn
+    let usage_count: i64 = conn
         .query_row(
-            "SELECT kind FROM directory_types WHERE id = ?1",
+            "SELECT COUNT(DISTINCT project_id) FROM project_directories WHERE dir_type_id = ?1",
             params![id],
             |row| row.get(0),
         )
-        .map_err(|e| format!("获取 kind 失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
 
     let kind = match kind_str.as_str() {
         "code" => DirectoryTypeKind::Code,
@@ -150,22 +182,126 @@ pub fn dir_type_update(id: String, patch: serde_json::Value) -> Result<Directory
         name,
         category,
         sort_order,
-        created_at: now.clone(), // 不返回创建时间
+        created_at,
         updated_at: now,
+        usage_count,
     })
-})
+}
+
+/// 按给定顺序重排目录类型（在同一事务中写入连续的 sort_order）
+#[tauri::command]
+pub fn dir_types_reorder(
+    workspace: Workspace,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<DirectoryType>, AppError> {
+    with_db_mut!(workspace, conn, { reorder_dir_types_with_conn(conn, &ordered_ids) })?;
+
+    dir_types_list(workspace)
+}
+
+fn reorder_dir_types_with_conn(
+    conn: &mut rusqlite::Connection,
+    ordered_ids: &[String],
+) -> Result<(), AppError> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+
+    // 写入前先确认所有 id 都存在
+    for id in ordered_ids {
+        let exists: bool = tx
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM directory_types WHERE id = ?1)",
+                params![id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+        if !exists {
+            return Err(AppError::NotFound(format!("目录类型不存在: {}", id)));
+        }
+    }
+
+    let now = Utc::now().to_rfc3339();
+    for (index, id) in ordered_ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE directory_types SET sort_order = ?1, updated_at = ?2 WHERE id = ?3",
+            params![index as i32, now, id],
+        )
+        .map_err(|e| AppError::Db(format!("更新排序失败: {}", e)))?;
+    }
+    tx.commit()
+        .map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))
+}
+
+/// 删除目录类型：内置类型不可删除；仍被项目目录引用时需要 `force` 才会一并删除这些引用
+#[tauri::command]
+pub fn dir_type_delete(
+    workspace: Workspace,
+    id: String,
+    force: Option<bool>,
+) -> Result<serde_json::Value, AppError> {
+    with_db_mut!(workspace, conn, {
+        delete_dir_type_with_conn(conn, &id, force.unwrap_or(false))
+    })?;
+
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+fn delete_dir_type_with_conn(
+    conn: &mut rusqlite::Connection,
+    id: &str,
+    force: bool,
+) -> Result<(), AppError> {
+    let kind: String = conn
+        .query_row(
+            "SELECT kind FROM directory_types WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::lookup(&e, format!("目录类型不存在: {}", e)))?;
+    if kind != "custom" {
+        return Err(AppError::Validation("内置目录类型不能删除".to_string()));
+    }
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+    let usage: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM project_directories WHERE dir_type_id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+    if usage > 0 {
+        if !force {
+            return Err(AppError::Validation(format!("该目录类型仍被 {} 个项目目录使用", usage)));
+        }
+        tx.execute(
+            "DELETE FROM project_directories WHERE dir_type_id = ?1",
+            params![id],
+        )
+        .map_err(|e| AppError::Db(format!("删除项目目录失败: {}", e)))?;
+    }
+    tx.execute("DELETE FROM directory_types WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Db(format!("删除目录类型失败: {}", e)))?;
+    tx.commit()
+        .map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))
 }
 
 /// 列出项目的所有目录
 #[tauri::command]
-pub fn project_dirs_list(project_id: String) -> Result<Vec<ProjectDirectory>, String> {
-    with_db!(conn, {
+pub fn project_dirs_list(
+    workspace: Workspace,
+    project_id: String,
+) -> Result<Vec<ProjectDirectory>, AppError> {
+    with_db!(workspace, conn, {
     let mut stmt = conn
         .prepare(
             "SELECT id, project_id, dir_type_id, relative_path, created_at, updated_at
              FROM project_directories WHERE project_id = ?1",
         )
-        .map_err(|e| format!("查询失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
 
     let dirs = stmt
         .query_map(params![project_id], |row| {
@@ -178,9 +314,9 @@ pub fn project_dirs_list(project_id: String) -> Result<Vec<ProjectDirectory>, St
                 updated_at: row.get(5)?,
             })
         })
-        .map_err(|e| format!("查询失败: {}", e))?
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("读取数据失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
 
     Ok(dirs)
 })
@@ -189,93 +325,394 @@ pub fn project_dirs_list(project_id: String) -> Result<Vec<ProjectDirectory>, St
 /// 创建或更新项目目录
 #[tauri::command]
 pub fn project_dir_create_or_update(
+    workspace: Workspace,
     project_id: String,
     input: serde_json::Value,
-) -> Result<ProjectDirectory, String> {
+) -> Result<ProjectDirectory, AppError> {
     let dir_type_id = input
         .get("dirTypeId")
         .or(input.get("dir_type_id"))
         .and_then(|v| v.as_str())
-        .ok_or("缺少目录类型 ID")?
+        .ok_or_else(|| AppError::Validation("缺少目录类型 ID".to_string()))?
         .to_string();
 
     let relative_path = input
         .get("relativePath")
         .or(input.get("relative_path"))
         .and_then(|v| v.as_str())
-        .ok_or("缺少目录路径")?
+        .ok_or_else(|| AppError::Validation("缺少目录路径".to_string()))?
         .to_string();
 
-    // 获取项目信息，创建物理目录
-    let project = project_get(project_id.clone())?;
-    let full_path = Path::new(&project.project_path).join(&relative_path);
+    // 解析映射目录（拒绝越出项目目录的路径）
+    let project = project_get(workspace.clone(), project_id.clone())?;
+    let full_path = resolve_in_project(Path::new(&project.project_path), &relative_path)?;
 
-    // 创建物理目录（如果不存在）
-    fs::create_dir_all(&full_path)
-        .map_err(|e| format!("创建物理目录失败: {} - {}", full_path.display(), e))?;
+    with_db_mut!(workspace, conn, {
+        upsert_project_dir_with_conn(conn, project_id, dir_type_id, relative_path, &full_path)
+    })
+}
 
+/// 在事务中写入目录映射并创建物理目录（已存在时保留），创建失败时回滚映射
+fn upsert_project_dir_with_conn(
+    conn: &mut rusqlite::Connection,
+    project_id: String,
+    dir_type_id: String,
+    relative_path: String,
+    full_path: &Path,
+) -> Result<ProjectDirectory, AppError> {
     let now = Utc::now().to_rfc3339();
+    let tx = conn
+        .transaction()
+        .map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
 
-    with_db!(conn, {
-        // 检查是否已存在
-        let existing_id: Option<String> = conn
-            .query_row(
-                "SELECT id FROM project_directories WHERE project_id = ?1 AND dir_type_id = ?2",
-                params![project_id, dir_type_id],
-                |row| row.get(0),
-            )
-            .ok();
+    let existing: Option<(String, String)> = tx
+        .query_row(
+            "SELECT id, created_at FROM project_directories WHERE project_id = ?1 AND dir_type_id = ?2",
+            params![project_id, dir_type_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
 
-        if let Some(id) = existing_id {
-            conn.execute(
+    let (id, created_at) = match existing {
+        Some((id, created_at)) => {
+            tx.execute(
                 "UPDATE project_directories SET relative_path = ?1, updated_at = ?2 WHERE id = ?3",
                 params![relative_path, now, id],
             )
-            .map_err(|e| format!("更新目录失败: {}", e))?;
-
-            Ok(ProjectDirectory {
-                id,
-                project_id,
-                dir_type_id,
-                relative_path,
-                created_at: now.clone(),
-                updated_at: now,
-            })
-        } else {
+            .map_err(|e| AppError::Db(format!("更新目录失败: {}", e)))?;
+            (id, created_at)
+        }
+        None => {
             let id = uuid::Uuid::new_v4().to_string();
-
-            conn.execute(
+            tx.execute(
                 "INSERT INTO project_directories (id, project_id, dir_type_id, relative_path, created_at, updated_at)
                  VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
                 params![id, project_id, dir_type_id, relative_path, now, now],
             )
-            .map_err(|e| format!("创建目录失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("创建目录失败: {}", e)))?;
+            (id, now.clone())
+        }
+    };
 
-            Ok(ProjectDirectory {
-                id,
-                project_id,
-                dir_type_id,
-                relative_path,
-                created_at: now.clone(),
-                updated_at: now,
-            })
+    // 未提交的事务在返回错误时随 tx 一起回滚
+    fs::create_dir_all(full_path)
+        .map_err(|e| AppError::io(&e, format!("创建物理目录失败: {} - {}", full_path.display(), e)))?;
+
+    tx.commit()
+        .map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))?;
+
+    Ok(ProjectDirectory {
+        id,
+        project_id,
+        dir_type_id,
+        relative_path,
+        created_at,
+        updated_at: now,
+    })
+}
+
+/// 删除项目的目录映射；映射不存在时直接返回成功
+/// `delete_files` 为 true 时一并将映射的物理目录移入回收站（仅限项目目录内部，不会删除项目根目录）；
+/// 目录校验或移除失败时映射保持不变
+#[tauri::command]
+pub fn project_dir_delete(
+    workspace: Workspace,
+    project_id: String,
+    dir_type_id: String,
+    delete_files: Option<bool>,
+) -> Result<serde_json::Value, AppError> {
+    with_db_mut!(workspace, conn, {
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+
+        let removed = delete_project_dir_with_conn(&tx, &project_id, &dir_type_id)?;
+        if let (Some(relative_path), true) = (removed, delete_files.unwrap_or(false)) {
+            let project_path: String = tx
+                .query_row(
+                    "SELECT project_path FROM projects WHERE id = ?1",
+                    params![project_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| AppError::lookup(&e, format!("项目不存在: {}", e)))?;
+            remove_project_subdir(Path::new(&project_path), &relative_path)?;
         }
+
+        // 目录已移入回收站后才提交，失败时事务随 tx 丢弃而回滚
+        tx.commit()
+            .map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))?;
+        Ok(serde_json::json!({ "ok": true }))
     })
 }
 
-/// 检测文件预览类型
+/// 将项目内的子目录移入回收站；目录不存在时忽略，不在项目目录内部时拒绝
+fn remove_project_subdir(root: &Path, relative_path: &str) -> Result<(), AppError> {
+    let full_path = root.join(relative_path);
+    if !full_path.exists() {
+        return Ok(());
+    }
+    let inside_project = match (root.canonicalize(), full_path.canonicalize()) {
+        (Ok(root), Ok(full)) => full.starts_with(&root) && full != root,
+        _ => false,
+    };
+    if !inside_project {
+        return Err(AppError::Validation(format!(
+            "只能删除项目目录内的子目录: {}",
+            relative_path
+        )));
+    }
+    move_to_trash(&full_path)
+}
+
+/// 删除目录映射记录，返回被删除映射的相对路径（不存在时为 None）
+fn delete_project_dir_with_conn(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+    dir_type_id: &str,
+) -> Result<Option<String>, AppError> {
+    let relative_path: Option<String> = conn
+        .query_row(
+            "SELECT relative_path FROM project_directories WHERE project_id = ?1 AND dir_type_id = ?2",
+            params![project_id, dir_type_id],
+            |row| row.get(0),
+        )
+        .ok();
+    if relative_path.is_some() {
+        conn.execute(
+            "DELETE FROM project_directories WHERE project_id = ?1 AND dir_type_id = ?2",
+            params![project_id, dir_type_id],
+        )
+        .map_err(|e| AppError::Db(format!("删除目录映射失败: {}", e)))?;
+    }
+    Ok(relative_path)
+}
+
+/// 内容嗅探读取的字节数
+const PREVIEW_SNIFF_BYTES: u64 = 4096;
+
+/// 检测文件预览类型：优先根据文件头的魔数判断，内容无法确定时再按扩展名判断
 #[tauri::command]
-pub fn preview_detect(path: String) -> Result<PreviewDetectResult, String> {
+pub fn preview_detect(path: String) -> Result<PreviewDetectResult, AppError> {
+    use std::io::Read;
+
     let path = Path::new(&path);
     let extension = path
         .extension()
         .map(|e| e.to_string_lossy().to_lowercase())
         .unwrap_or_default();
+    let by_extension = preview_kind_from_extension(&extension);
+
+    let mut head = Vec::new();
+    if let Ok(file) = std::fs::File::open(path) {
+        let _ = file.take(PREVIEW_SNIFF_BYTES).read_to_end(&mut head);
+    }
+
+    let kind = match sniff_preview_kind(&head) {
+        // 带 BOM 的文本：Markdown 和 SVG 仍按扩展名处理
+        Some(PreviewKind::Text)
+            if by_extension == PreviewKind::Markdown || extension == "svg" =>
+        {
+            by_extension
+        }
+        Some(kind) => kind,
+        None => by_extension,
+    };
+
+    let language = if kind == PreviewKind::Text {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        language_from_file_name(&file_name, &extension).map(str::to_string)
+    } else {
+        None
+    };
+
+    Ok(PreviewDetectResult { kind, language })
+}
+
+/// 生成图片缩略图：等比缩放到 `max_dim` 以内（不放大），返回 PNG 的 base64 data URL
+/// 当前窗口打开了工作区时按路径、修改时间和尺寸缓存到工作区数据目录（默认 .app）下的 thumbnails
+#[tauri::command]
+pub fn preview_thumbnail(window: Window, path: String, max_dim: u32) -> Result<String, AppError> {
+    if max_dim == 0 {
+        return Err(AppError::Validation("缩略图尺寸必须大于 0".into()));
+    }
+    let metadata =
+        fs::metadata(&path).map_err(|e| AppError::io(&e, format!("读取文件信息失败: {}", e)))?;
+    if preview_detect(path.clone())?.kind != PreviewKind::Image {
+        return Err(AppError::Validation("不是图片文件".into()));
+    }
+
+    let cache_file = Workspace::of_window(window.label())
+        .and_then(|workspace| crate::db::data_dir(workspace.path()))
+        .map(|data_dir| {
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            data_dir
+                .join("thumbnails")
+                .join(thumbnail_cache_key(&path, modified, max_dim))
+        });
+
+    let cached = cache_file.as_ref().and_then(|file| fs::read(file).ok());
+    let png = match cached {
+        Some(png) => png,
+        None => {
+            let png = render_thumbnail(Path::new(&path), max_dim)?;
+            // 缓存写入失败不影响返回结果
+            if let Some(file) = &cache_file {
+                if let Some(dir) = file.parent() {
+                    let _ = fs::create_dir_all(dir);
+                }
+                let _ = fs::write(file, &png);
+            }
+            png
+        }
+    };
+
+    Ok(format!("data:image/png;base64,{}", base64_encode(&png)))
+}
+
+/// 缩略图缓存文件名（路径、修改时间、尺寸任一变化都会生成新的缓存）
+fn thumbnail_cache_key(path: &str, modified_nanos: u128, max_dim: u32) -> String {
+    use std::hash::{Hash, Hasher};
 
-    let kind = match extension.as_str() {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path.hash(&mut hasher);
+    modified_nanos.hash(&mut hasher);
+    max_dim.hash(&mut hasher);
+    format!("{:016x}.png", hasher.finish())
+}
+
+/// 解码图片并缩放到 `max_dim` 以内，编码为 PNG
+fn render_thumbnail(path: &Path, max_dim: u32) -> Result<Vec<u8>, AppError> {
+    let image = image::ImageReader::open(path)
+        .map_err(|e| AppError::io(&e, format!("读取图片失败: {}", e)))?
+        .with_guessed_format()
+        .map_err(|e| AppError::io(&e, format!("读取图片失败: {}", e)))?
+        .decode()
+        .map_err(|e| AppError::Validation(format!("解码图片失败: {}", e)))?;
+
+    let image = if image.width() > max_dim || image.height() > max_dim {
+        image.thumbnail(max_dim, max_dim)
+    } else {
+        image
+    };
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| AppError::Internal(format!("编码缩略图失败: {}", e)))?;
+    Ok(png)
+}
+
+/// 根据文件名和扩展名推断代码语言（名称与常见高亮库的语言标识一致）
+fn language_from_file_name(file_name: &str, extension: &str) -> Option<&'static str> {
+    // 无扩展名的常见文件
+    let by_name = match file_name {
+        "Dockerfile" | "Containerfile" => Some("dockerfile"),
+        "Makefile" | "GNUmakefile" | "makefile" => Some("makefile"),
+        "CMakeLists.txt" => Some("cmake"),
+        "Gemfile" | "Rakefile" => Some("ruby"),
+        ".gitignore" | ".dockerignore" | ".env" => Some("ini"),
+        _ => None,
+    };
+    if by_name.is_some() {
+        return by_name;
+    }
+
+    let language = match extension {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "py" | "pyw" | "pyi" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "scala" => "scala",
+        "groovy" | "gradle" => "groovy",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => "cpp",
+        "cs" => "csharp",
+        "fs" | "fsx" => "fsharp",
+        "swift" => "swift",
+        "m" | "mm" => "objective-c",
+        "rb" => "ruby",
+        "php" => "php",
+        "pl" | "pm" => "perl",
+        "lua" => "lua",
+        "r" => "r",
+        "dart" => "dart",
+        "ex" | "exs" => "elixir",
+        "erl" | "hrl" => "erlang",
+        "hs" => "haskell",
+        "clj" | "cljs" | "edn" => "clojure",
+        "zig" => "zig",
+        "sh" | "bash" | "zsh" => "bash",
+        "fish" => "fish",
+        "ps1" | "psm1" => "powershell",
+        "bat" | "cmd" => "batch",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "sass" => "sass",
+        "less" => "less",
+        "json" | "jsonc" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "xml" | "xsd" | "xsl" | "plist" => "xml",
+        "ini" | "cfg" | "conf" => "ini",
+        "proto" => "protobuf",
+        "graphql" | "gql" => "graphql",
+        "tf" | "hcl" => "hcl",
+        "diff" | "patch" => "diff",
+        "tex" => "latex",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// 根据文件头的魔数判断预览类型，无法确定时返回 None
+fn sniff_preview_kind(head: &[u8]) -> Option<PreviewKind> {
+    const UTF_BOMS: &[&[u8]] = &[
+        b"\xEF\xBB\xBF", // UTF-8
+        b"\xFF\xFE",     // UTF-16 LE
+        b"\xFE\xFF",     // UTF-16 BE
+    ];
+
+    if head.starts_with(b"\x89PNG\r\n\x1a\n")
+        || head.starts_with(b"\xFF\xD8\xFF")
+        || head.starts_with(b"GIF87a")
+        || head.starts_with(b"GIF89a")
+        || (head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP")
+    {
+        return Some(PreviewKind::Image);
+    }
+    if head.starts_with(b"%PDF-") {
+        return Some(PreviewKind::Pdf);
+    }
+    if UTF_BOMS.iter().any(|bom| head.starts_with(bom)) {
+        return Some(PreviewKind::Text);
+    }
+    None
+}
+
+/// 根据扩展名判断预览类型（默认为纯文本）
+fn preview_kind_from_extension(extension: &str) -> PreviewKind {
+    match extension {
         // 图片格式
-        "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "bmp" | "ico" | "tiff" | "tif"
-        | " BMP" => PreviewKind::Image,
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "bmp" | "ico" | "tiff" | "tif" => {
+            PreviewKind::Image
+        }
         // Markdown
         "md" | "markdown" | "mdown" | "mkd" => PreviewKind::Markdown,
         // PDF
@@ -284,21 +721,31 @@ pub fn preview_detect(path: String) -> Result<PreviewDetectResult, String> {
         "doc" | "docx" | "rtf" | "odt" => PreviewKind::Word,
         // Excel 表格
         "xls" | "xlsx" | "xlsm" | "xlsb" | "ods" => PreviewKind::Excel,
+        // 视频
+        "mp4" | "m4v" | "webm" | "mov" | "mkv" | "avi" | "ogv" => PreviewKind::Video,
+        // 音频
+        "mp3" | "wav" | "flac" | "ogg" | "oga" | "m4a" | "aac" | "opus" => PreviewKind::Audio,
         // 纯文本（默认）
         _ => PreviewKind::Text,
-    };
-
-    Ok(PreviewDetectResult { kind })
+    }
 }
 
 /// 列出所有支持的 IDE（包括未安装的），并标记可用状态
 #[tauri::command]
-pub fn ide_list_supported() -> Result<Vec<IdeConfig>, String> {
+pub fn ide_list_supported() -> Result<Vec<IdeConfig>, AppError> {
     let mut ides = Vec::new();
 
     // 定义所有支持的 IDE 列表
     let all_supported_ides: Vec<(&str, SupportedIdeKind, &str, Option<Vec<&str>>)> = vec![
         ("code", SupportedIdeKind::Vscode, "VS Code", Some(vec!["--reuse-window"])),
+        (
+            "code-insiders",
+            SupportedIdeKind::Vscode,
+            "VS Code Insiders",
+            Some(vec!["--reuse-window"]),
+        ),
+        ("codium", SupportedIdeKind::Vscode, "VSCodium", Some(vec!["--reuse-window"])),
+        ("cursor", SupportedIdeKind::Vscode, "Cursor", Some(vec!["--reuse-window"])),
         ("idea", SupportedIdeKind::Idea, "IntelliJ IDEA", None),
         ("webstorm", SupportedIdeKind::Webstorm, "WebStorm", None),
         ("trae", SupportedIdeKind::Trae, "Trae", None),
@@ -317,9 +764,333 @@ pub fn ide_list_supported() -> Result<Vec<IdeConfig>, String> {
         });
     }
 
+    // 补充各平台按安装位置检测到的 IDE（未加入 PATH 的安装也能被识别）
+    for detected in detect_installed_ides() {
+        merge_detected_ide(&mut ides, detected);
+    }
+
     Ok(ides)
 }
 
+/// 合并检测到的 IDE：同名且 CLI 不可用的条目改用检测到的启动方式，否则追加为新条目
+fn merge_detected_ide(ides: &mut Vec<IdeConfig>, detected: IdeConfig) {
+    match ides.iter_mut().find(|ide| ide.name == detected.name) {
+        Some(existing) if existing.available != Some(true) => *existing = detected,
+        Some(_) => {}
+        None => ides.push(detected),
+    }
+}
+
+/// 构造一个已检测到（可用）的 IDE 配置
+#[cfg_attr(
+    not(any(windows, target_os = "macos", target_os = "linux")),
+    allow(dead_code)
+)]
+fn detected_ide(
+    kind: SupportedIdeKind,
+    name: &str,
+    command: String,
+    args: Option<Vec<String>>,
+) -> IdeConfig {
+    IdeConfig {
+        kind,
+        name: name.to_string(),
+        command,
+        args,
+        available: Some(true),
+    }
+}
+
+/// macOS：扫描 /Applications 与 ~/Applications 下的应用包
+#[cfg(target_os = "macos")]
+fn detect_installed_ides() -> Vec<IdeConfig> {
+    const MAC_APPS: &[(&str, SupportedIdeKind, &str)] = &[
+        ("Visual Studio Code.app", SupportedIdeKind::Vscode, "VS Code"),
+        (
+            "Visual Studio Code - Insiders.app",
+            SupportedIdeKind::Vscode,
+            "VS Code Insiders",
+        ),
+        ("VSCodium.app", SupportedIdeKind::Vscode, "VSCodium"),
+        ("Cursor.app", SupportedIdeKind::Vscode, "Cursor"),
+        ("IntelliJ IDEA.app", SupportedIdeKind::Idea, "IntelliJ IDEA"),
+        ("IntelliJ IDEA CE.app", SupportedIdeKind::Idea, "IntelliJ IDEA"),
+        ("WebStorm.app", SupportedIdeKind::Webstorm, "WebStorm"),
+        ("Trae.app", SupportedIdeKind::Trae, "Trae"),
+        ("Zed.app", SupportedIdeKind::Zed, "Zed"),
+        ("PyCharm.app", SupportedIdeKind::Other, "PyCharm"),
+        ("PyCharm CE.app", SupportedIdeKind::Other, "PyCharm"),
+        ("GoLand.app", SupportedIdeKind::Other, "GoLand"),
+        ("CLion.app", SupportedIdeKind::Other, "CLion"),
+        ("Rider.app", SupportedIdeKind::Other, "Rider"),
+        ("RustRover.app", SupportedIdeKind::Other, "RustRover"),
+    ];
+
+    let mut roots = vec![std::path::PathBuf::from("/Applications")];
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join("Applications"));
+    }
+
+    let mut found: Vec<IdeConfig> = Vec::new();
+    for (bundle, kind, name) in MAC_APPS {
+        if found.iter().any(|ide| ide.name == *name) {
+            continue;
+        }
+        if let Some(app_path) = roots.iter().map(|root| root.join(bundle)).find(|p| p.is_dir()) {
+            found.push(detected_ide(
+                kind.clone(),
+                name,
+                app_path.to_string_lossy().to_string(),
+                None,
+            ));
+        }
+    }
+    found
+}
+
+/// Linux：检查 PATH 中的常见启动器以及 .desktop 启动项
+#[cfg(target_os = "linux")]
+fn detect_installed_ides() -> Vec<IdeConfig> {
+    const LINUX_COMMANDS: &[(&str, SupportedIdeKind, &str)] = &[
+        ("pycharm", SupportedIdeKind::Other, "PyCharm"),
+        ("pycharm-community", SupportedIdeKind::Other, "PyCharm"),
+        ("subl", SupportedIdeKind::Other, "Sublime Text"),
+    ];
+    const DESKTOP_ENTRIES: &[(&str, SupportedIdeKind, &str)] = &[
+        ("code.desktop", SupportedIdeKind::Vscode, "VS Code"),
+        ("code_code.desktop", SupportedIdeKind::Vscode, "VS Code"),
+        ("com.visualstudio.code.desktop", SupportedIdeKind::Vscode, "VS Code"),
+        ("code-insiders.desktop", SupportedIdeKind::Vscode, "VS Code Insiders"),
+        ("code-insiders_code-insiders.desktop", SupportedIdeKind::Vscode, "VS Code Insiders"),
+        ("codium.desktop", SupportedIdeKind::Vscode, "VSCodium"),
+        ("codium_codium.desktop", SupportedIdeKind::Vscode, "VSCodium"),
+        ("com.vscodium.codium.desktop", SupportedIdeKind::Vscode, "VSCodium"),
+        ("cursor.desktop", SupportedIdeKind::Vscode, "Cursor"),
+        ("jetbrains-idea.desktop", SupportedIdeKind::Idea, "IntelliJ IDEA"),
+        ("jetbrains-idea-ce.desktop", SupportedIdeKind::Idea, "IntelliJ IDEA"),
+        (
+            "intellij-idea-community_intellij-idea-community.desktop",
+            SupportedIdeKind::Idea,
+            "IntelliJ IDEA",
+        ),
+        ("jetbrains-webstorm.desktop", SupportedIdeKind::Webstorm, "WebStorm"),
+        ("webstorm_webstorm.desktop", SupportedIdeKind::Webstorm, "WebStorm"),
+        ("dev.zed.Zed.desktop", SupportedIdeKind::Zed, "Zed"),
+        ("jetbrains-pycharm.desktop", SupportedIdeKind::Other, "PyCharm"),
+        ("jetbrains-pycharm-ce.desktop", SupportedIdeKind::Other, "PyCharm"),
+        ("sublime_text.desktop", SupportedIdeKind::Other, "Sublime Text"),
+    ];
+
+    let mut found: Vec<IdeConfig> = Vec::new();
+
+    for (command, kind, name) in LINUX_COMMANDS {
+        if !found.iter().any(|ide| ide.name == *name) && is_command_available(command) {
+            found.push(detected_ide(kind.clone(), name, command.to_string(), None));
+        }
+    }
+
+    let mut desktop_dirs = vec![
+        std::path::PathBuf::from("/usr/share/applications"),
+        std::path::PathBuf::from("/usr/local/share/applications"),
+        std::path::PathBuf::from("/var/lib/snapd/desktop/applications"),
+        std::path::PathBuf::from("/var/lib/flatpak/exports/share/applications"),
+    ];
+    if let Some(data_dir) = dirs::data_dir() {
+        desktop_dirs.push(data_dir.join("applications"));
+        desktop_dirs.push(data_dir.join("flatpak/exports/share/applications"));
+    }
+
+    for (file_name, kind, name) in DESKTOP_ENTRIES {
+        if found.iter().any(|ide| ide.name == *name) {
+            continue;
+        }
+        let exec = desktop_dirs
+            .iter()
+            .filter_map(|dir| fs::read_to_string(dir.join(file_name)).ok())
+            .find_map(|content| desktop_exec_command(&content));
+        if let Some((command, args)) = exec {
+            let args = if args.is_empty() { None } else { Some(args) };
+            found.push(detected_ide(kind.clone(), name, command, args));
+        }
+    }
+
+    found
+}
+
+/// Windows：检查 VS Code 及其分支的默认安装位置（用户安装与系统安装）
+#[cfg(windows)]
+fn detect_installed_ides() -> Vec<IdeConfig> {
+    const WINDOWS_INSTALLS: &[(&str, SupportedIdeKind, &str)] = &[
+        (r"Microsoft VS Code\Code.exe", SupportedIdeKind::Vscode, "VS Code"),
+        (
+            r"Microsoft VS Code Insiders\Code - Insiders.exe",
+            SupportedIdeKind::Vscode,
+            "VS Code Insiders",
+        ),
+        (r"VSCodium\VSCodium.exe", SupportedIdeKind::Vscode, "VSCodium"),
+        (r"cursor\Cursor.exe", SupportedIdeKind::Vscode, "Cursor"),
+    ];
+
+    let mut roots: Vec<std::path::PathBuf> = Vec::new();
+    if let Ok(local) = std::env::var("LOCALAPPDATA") {
+        roots.push(Path::new(&local).join("Programs"));
+    }
+    for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+        if let Ok(dir) = std::env::var(var) {
+            roots.push(std::path::PathBuf::from(dir));
+        }
+    }
+
+    let mut found: Vec<IdeConfig> = Vec::new();
+    for (relative, kind, name) in WINDOWS_INSTALLS {
+        if let Some(exe) = roots.iter().map(|root| root.join(relative)).find(|p| p.is_file()) {
+            found.push(detected_ide(
+                kind.clone(),
+                name,
+                exe.to_string_lossy().to_string(),
+                Some(vec!["--reuse-window".to_string()]),
+            ));
+        }
+    }
+
+    // JetBrains Toolbox 安装的 IDE：%LOCALAPPDATA%\JetBrains\Toolbox\apps\<产品>\ch-*\<版本>
+    if let Ok(local) = std::env::var("LOCALAPPDATA") {
+        let apps_dir = Path::new(&local).join(r"JetBrains\Toolbox\apps");
+        for detected in detect_toolbox_ides(&apps_dir) {
+            if !found.iter().any(|ide| ide.name == detected.name) {
+                found.push(detected);
+            }
+        }
+    }
+    found
+}
+
+/// JetBrains Toolbox 产品目录：(apps 下的目录名, 类型, 显示名称, 启动器文件名前缀)
+#[cfg_attr(not(windows), allow(dead_code))]
+const TOOLBOX_PRODUCTS: &[(&str, SupportedIdeKind, &str, &str)] = &[
+    ("IDEA-U", SupportedIdeKind::Idea, "IntelliJ IDEA", "idea"),
+    ("IDEA-C", SupportedIdeKind::Idea, "IntelliJ IDEA", "idea"),
+    ("WebStorm", SupportedIdeKind::Webstorm, "WebStorm", "webstorm"),
+    ("PyCharm-P", SupportedIdeKind::Other, "PyCharm", "pycharm"),
+    ("PyCharm-C", SupportedIdeKind::Other, "PyCharm", "pycharm"),
+    ("GoLand", SupportedIdeKind::Other, "GoLand", "goland"),
+    ("CLion", SupportedIdeKind::Other, "CLion", "clion"),
+    ("Rider", SupportedIdeKind::Other, "Rider", "rider"),
+    ("PhpStorm", SupportedIdeKind::Other, "PhpStorm", "phpstorm"),
+    ("RubyMine", SupportedIdeKind::Other, "RubyMine", "rubymine"),
+    ("RustRover", SupportedIdeKind::Other, "RustRover", "rustrover"),
+    ("DataGrip", SupportedIdeKind::Other, "DataGrip", "datagrip"),
+];
+
+/// 扫描 Toolbox 的 apps 目录，每个产品取最新版本的启动器（同名产品只保留先找到的版本）
+#[cfg_attr(not(windows), allow(dead_code))]
+fn detect_toolbox_ides(apps_dir: &Path) -> Vec<IdeConfig> {
+    let mut found: Vec<IdeConfig> = Vec::new();
+    for (dir_name, kind, name, launcher) in TOOLBOX_PRODUCTS {
+        if found.iter().any(|ide| ide.name == *name) {
+            continue;
+        }
+        let Some(build_dir) = latest_toolbox_build(&apps_dir.join(dir_name)) else {
+            continue;
+        };
+        if let Some(exe) = toolbox_launcher(&build_dir.join("bin"), launcher) {
+            found.push(detected_ide(
+                kind.clone(),
+                name,
+                exe.to_string_lossy().to_string(),
+                None,
+            ));
+        }
+    }
+    found
+}
+
+/// 在产品目录的各渠道（ch-0、ch-1 等）中找出版本号最大的安装目录
+#[cfg_attr(not(windows), allow(dead_code))]
+fn latest_toolbox_build(product_dir: &Path) -> Option<std::path::PathBuf> {
+    let channels = fs::read_dir(product_dir).ok()?;
+    channels
+        .flatten()
+        .filter(|channel| channel.file_name().to_string_lossy().starts_with("ch-"))
+        .filter_map(|channel| fs::read_dir(channel.path()).ok())
+        .flat_map(|builds| builds.flatten())
+        .filter(|build| build.path().is_dir())
+        .filter_map(|build| {
+            // 跳过 Toolbox 生成的 <版本>.plugins 等附属目录
+            let version = parse_build_version(&build.file_name().to_string_lossy())?;
+            Some((version, build.path()))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, path)| path)
+}
+
+/// 解析形如 233.11799.241 的构建号，含非数字段时返回 None
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_build_version(name: &str) -> Option<Vec<u64>> {
+    name.split('.').map(|part| part.parse::<u64>().ok()).collect()
+}
+
+/// 选择产品的启动器：每个产品只返回一个（优先 idea.exe、idea.cmd 这类启动器，最后 .bat），
+/// 不使用 idea64.exe 等直接启动 JVM 的可执行文件
+#[cfg_attr(not(windows), allow(dead_code))]
+fn toolbox_launcher(bin_dir: &Path, launcher: &str) -> Option<std::path::PathBuf> {
+    [
+        format!("{}.exe", launcher),
+        format!("{}.cmd", launcher),
+        format!("{}.bat", launcher),
+    ]
+    .iter()
+    .map(|file| bin_dir.join(file))
+    .find(|path| path.is_file())
+}
+
+/// 其他平台暂无按安装位置检测
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+fn detect_installed_ides() -> Vec<IdeConfig> {
+    Vec::new()
+}
+
+/// 解析 .desktop 文件 [Desktop Entry] 中的 Exec，返回 (命令, 参数)，去掉 %U 等字段代码
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn desktop_exec_command(content: &str) -> Option<(String, Vec<String>)> {
+    let mut in_main_section = false;
+    let exec = content.lines().map(str::trim).find_map(|line| {
+        if line.starts_with('[') {
+            in_main_section = line == "[Desktop Entry]";
+            return None;
+        }
+        if in_main_section {
+            line.strip_prefix("Exec=")
+        } else {
+            None
+        }
+    })?;
+
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in exec.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    let mut tokens = tokens
+        .into_iter()
+        .filter(|t| !(t.len() == 2 && t.starts_with('%')));
+    let command = tokens.next()?;
+    Some((command, tokens.collect()))
+}
+
 /// 检测命令是否在 PATH 中可用
 fn is_command_available(command: &str) -> bool {
     // 验证命令名格式：只允许字母、数字、连字符、下划线和点
@@ -357,15 +1128,41 @@ fn is_valid_command_name(command: &str) -> bool {
         .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
 }
 
-/// 获取有效的 IDE 配置（按优先级：仓库 > 工作区 > 全局）
+/// 获取有效的 IDE 配置（按优先级：仓库 > 项目 > 工作区 > 全局）
 fn get_effective_ide(
     conn: &rusqlite::Connection,
     repo_id: &str,
     provided_ide: Option<IdeConfig>,
 ) -> Option<IdeConfig> {
+    resolve_effective_ide(conn, repo_id, provided_ide).map(|(ide, _)| ide)
+}
+
+/// 生效 IDE 配置的来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IdeSource {
+    Provided,
+    Repository,
+    Project,
+    Workspace,
+    Global,
+}
+
+impl IdeSource {
+    /// 是否来自设置中的默认 IDE（工作区或全局）
+    fn is_default(self) -> bool {
+        matches!(self, IdeSource::Workspace | IdeSource::Global)
+    }
+}
+
+/// 获取有效的 IDE 配置及其来源
+fn resolve_effective_ide(
+    conn: &rusqlite::Connection,
+    repo_id: &str,
+    provided_ide: Option<IdeConfig>,
+) -> Option<(IdeConfig, IdeSource)> {
     // 1. 如果调用时提供了 IDE，直接使用
     if let Some(ide) = provided_ide {
-        return Some(ide);
+        return Some((ide, IdeSource::Provided));
     }
 
     // 2. 尝试获取仓库级别的 IDE 设置
@@ -378,11 +1175,52 @@ fn get_effective_ide(
         .ok();
     if let Some(json) = repo_ide {
         if let Ok(ide) = serde_json::from_str::<IdeConfig>(&json) {
-            return Some(ide);
+            return Some((ide, IdeSource::Repository));
         }
     }
 
-    // 3. 尝试获取工作区设置
+    // 3. 回退到仓库所属项目的 IDE 设置，再到默认 IDE
+    let project_id: Option<String> = conn
+        .query_row(
+            "SELECT project_id FROM git_repositories WHERE id = ?1",
+            params![repo_id],
+            |row| row.get(0),
+        )
+        .ok();
+    match project_id {
+        Some(project_id) => resolve_project_ide(conn, &project_id, None),
+        None => default_ide(conn),
+    }
+}
+
+/// 获取项目的有效 IDE 配置（按优先级：调用参数 > 项目 > 工作区 > 全局）
+fn resolve_project_ide(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+    provided_ide: Option<IdeConfig>,
+) -> Option<(IdeConfig, IdeSource)> {
+    if let Some(ide) = provided_ide {
+        return Some((ide, IdeSource::Provided));
+    }
+
+    let project_ide: Option<String> = conn
+        .query_row(
+            "SELECT ide_override_json FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )
+        .ok()
+        .flatten();
+    if let Some(ide) = project_ide.and_then(|json| serde_json::from_str::<IdeConfig>(&json).ok()) {
+        return Some((ide, IdeSource::Project));
+    }
+
+    default_ide(conn)
+}
+
+/// 设置中的默认 IDE：工作区设置优先，其次全局设置
+fn default_ide(conn: &rusqlite::Connection) -> Option<(IdeConfig, IdeSource)> {
+    // 1. 尝试获取工作区设置
     let workspace_ide: Option<String> = conn
         .query_row(
             "SELECT value FROM workspace_meta WHERE key = 'settings'",
@@ -396,23 +1234,26 @@ fn get_effective_ide(
                 .get("defaultIde")
                 .and_then(|i| serde_json::from_value::<IdeConfig>(i.clone()).ok())
             {
-                return Some(ide);
+                return Some((ide, IdeSource::Workspace));
             }
         }
     }
 
-    // 4. 使用全局设置
+    // 2. 使用全局设置
     let global_settings = load_global_settings();
-    global_settings.default_ide
+    global_settings
+        .default_ide
+        .map(|ide| (ide, IdeSource::Global))
 }
 
 /// 预览 IDE 配置（不实际打开，仅返回会使用什么 IDE）
 #[tauri::command]
 pub fn ide_preview(
+    workspace: Workspace,
     repo_id: String,
     provided_ide: Option<IdeConfig>,
-) -> Result<Option<IdeConfig>, String> {
-    with_db!(conn, {
+) -> Result<Option<IdeConfig>, AppError> {
+    with_db!(workspace, conn, {
         Ok(get_effective_ide(conn, &repo_id, provided_ide))
     })
 }
@@ -420,79 +1261,303 @@ pub fn ide_preview(
 /// 用 IDE 打开仓库
 #[tauri::command]
 pub fn ide_open_repo(
+    workspace: Workspace,
     repo_id: String,
     provided_ide: Option<IdeConfig>,
-) -> Result<serde_json::Value, String> {
-    with_db!(conn, {
-        let path: String = conn
-            .query_row(
-                "SELECT path FROM git_repositories WHERE id = ?1",
-                params![repo_id],
-                |row| row.get(0),
-            )
-            .map_err(|e| format!("仓库不存在: {}", e))?;
+) -> Result<serde_json::Value, AppError> {
+    let (path, ide_config, source) = with_db!(workspace, conn, {
+        let path = repo_path(conn, &repo_id)?;
 
-        // 获取有效的 IDE 配置（优先级：仓库 > 工作区 > 全局）
-        let ide_config = get_effective_ide(conn, &repo_id, provided_ide)
-            .ok_or_else(|| "未配置 IDE，请先在设置中配置默认 IDE".to_string())?;
+        // 获取有效的 IDE 配置（优先级：仓库 > 项目 > 工作区 > 全局）
+        let (ide_config, source) = resolve_effective_ide(conn, &repo_id, provided_ide)
+            .ok_or_else(|| AppError::Validation("未配置 IDE，请先在设置中配置默认 IDE".to_string()))?;
+        Ok::<_, AppError>((path, ide_config, source))
+    })?;
 
-        // 验证 IDE 可执行文件存在（支持完整路径或 PATH 中的命令）
-        let ide_path = if std::path::Path::new(&ide_config.command).exists() {
-            // 如果是完整路径且文件存在，直接使用
-            std::path::PathBuf::from(&ide_config.command)
-        } else {
-            // 否则尝试在 PATH 中查找
-            which(&ide_config.command).map_err(|_| {
-                format!(
-                    "IDE 可执行文件不存在: {}，请确保已安装并在 PATH 中",
-                    ide_config.command
-                )
-            })?
-        };
+    open_dir_in_ide(&path, &ide_config, source)
+}
 
-        // 启动 IDE（在新窗口中打开，不复用已有窗口）
-        #[cfg(windows)]
-        {
-            let mut cmd = Command::new(&ide_path);
-            // 移除 --reuse-window 参数，让 IDE 在新窗口中打开
-            if let Some(args) = ide_config.args {
-                // 过滤掉 --reuse-window 参数
-                let filtered_args: Vec<String> = args
-                    .into_iter()
-                    .filter(|arg| arg != "--reuse-window")
-                    .collect();
-                cmd.args(&filtered_args);
-            }
-            cmd.arg(&path);
+/// 用 IDE 打开整个项目目录
+#[tauri::command]
+pub fn ide_open_project(
+    workspace: Workspace,
+    project_id: String,
+    ide: Option<IdeConfig>,
+) -> Result<serde_json::Value, AppError> {
+    let project = project_get(workspace.clone(), project_id.clone())?;
+    let (ide_config, source) = with_db!(workspace, conn, {
+        // 获取有效的 IDE 配置（优先级：项目 > 工作区 > 全局）
+        resolve_project_ide(conn, &project_id, ide)
+            .ok_or_else(|| AppError::Validation("未配置 IDE，请先在设置中配置默认 IDE".to_string()))
+    })?;
+
+    open_dir_in_ide(&project.project_path, &ide_config, source)
+}
 
-            match cmd.spawn() {
-                Ok(_) => Ok(
-                    serde_json::json!({ "ok": true, "message": format!("已使用 {} 打开", ide_config.name) }),
-                ),
-                Err(e) => {
-                    Ok(serde_json::json!({ "ok": false, "message": format!("启动 IDE 失败: {}", e) }))
-                }
+/// 在新窗口中打开目录（仓库或项目）
+fn open_dir_in_ide(
+    path: &str,
+    ide_config: &IdeConfig,
+    source: IdeSource,
+) -> Result<serde_json::Value, AppError> {
+    // 设置中的默认 IDE 已失效时返回单独的标记，便于前端提示重新选择
+    if source.is_default() && validate_ide_executable(ide_config).is_err() {
+        return Ok(serde_json::json!({
+            "ok": false,
+            "defaultIdeMissing": true,
+            "message": format!("默认 IDE（{}）的可执行文件不存在，请重新选择默认 IDE", ide_config.name),
+        }));
+    }
+
+    launch_ide(
+        ide_config,
+        &IdeTarget {
+            path,
+            line: None,
+            new_window: true,
+        },
+    )
+}
+
+/// 用 IDE 打开仓库中的文件，可选跳转到指定行（IDE 不支持行号时只打开文件）
+#[tauri::command]
+pub fn ide_open_file(
+    workspace: Workspace,
+    repo_id: String,
+    relative_path: String,
+    line: Option<u32>,
+    ide: Option<IdeConfig>,
+) -> Result<serde_json::Value, AppError> {
+    let (repo_path, ide_config) = with_db!(workspace, conn, {
+        let repo_path = repo_path(conn, &repo_id)?;
+
+        let ide_config = get_effective_ide(conn, &repo_id, ide)
+            .ok_or_else(|| AppError::Validation("未配置 IDE，请先在设置中配置默认 IDE".to_string()))?;
+        Ok::<_, AppError>((repo_path, ide_config))
+    })?;
+
+    let file_path = resolve_in_project(Path::new(&repo_path), &relative_path)?;
+    if !file_path.exists() {
+        return Err(AppError::NotFound(format!("文件不存在: {}", relative_path)));
+    }
+
+    launch_ide(
+        &ide_config,
+        &IdeTarget {
+            path: &file_path.to_string_lossy(),
+            line,
+            new_window: false,
+        },
+    )
+}
+
+/// IDE 要打开的目标
+struct IdeTarget<'a> {
+    path: &'a str,
+    /// 跳转的行号（仅打开文件时使用）
+    line: Option<u32>,
+    /// 是否强制在新窗口中打开（打开仓库时为 true，打开文件时复用已有窗口）
+    new_window: bool,
+}
+
+/// 启动 IDE 打开指定目标，返回 `{ ok, message }`
+fn launch_ide(ide_config: &IdeConfig, target: &IdeTarget) -> Result<serde_json::Value, AppError> {
+    validate_ide_executable(ide_config)?;
+    let mut cmd = build_ide_command(ide_config, target)?;
+    match cmd.spawn() {
+        Ok(_) => Ok(
+            serde_json::json!({ "ok": true, "message": format!("已使用 {} 打开", ide_config.name) }),
+        ),
+        Err(e) => {
+            Ok(serde_json::json!({ "ok": false, "message": format!("启动 IDE 失败: {}", e) }))
+        }
+    }
+}
+
+/// 构建启动 IDE 的命令
+fn build_ide_command(ide_config: &IdeConfig, target: &IdeTarget) -> Result<Command, AppError> {
+    // 打开仓库时移除 --reuse-window 参数，让 IDE 在新窗口中打开
+    let args: Vec<String> = ide_config
+        .args
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|arg| !target.new_window || arg != "--reuse-window")
+        .collect();
+    // 参数中包含 {path} 占位符时就地替换，否则按原方式把路径追加在最后
+    let (args, has_placeholder) = substitute_path_placeholder(args, target.path);
+
+    // macOS 上的 .app 应用包、应用名或 Bundle ID 通过 `open` 启动（无法指定行号）
+    #[cfg(target_os = "macos")]
+    {
+        let in_path = which(&ide_config.command).is_ok();
+        if let Some(app) = classify_mac_app(&ide_config.command, in_path) {
+            let mut cmd = Command::new("open");
+            match app {
+                MacApp::Bundle(bundle) => cmd.arg("-a").arg(bundle),
+                MacApp::BundleId(id) => cmd.arg("-b").arg(id),
+            };
+            if !has_placeholder {
+                cmd.arg(target.path);
+            }
+            if !args.is_empty() {
+                cmd.arg("--args").args(&args);
             }
+            return Ok(cmd);
         }
+    }
 
-        #[cfg(not(windows))]
-        {
-            Ok(serde_json::json!({ "ok": false, "message": "不支持的平台" }))
+    let ide_path = resolve_ide_executable(ide_config)?;
+    let mut cmd = Command::new(&ide_path);
+    cmd.args(&args);
+    if !has_placeholder {
+        cmd.args(ide_target_args(&ide_config.kind, target.path, target.line));
+    }
+
+    // Linux 下 IDE 作为独立进程运行，不继承应用的标准输入输出
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        use std::process::Stdio;
+        cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    }
+
+    Ok(cmd)
+}
+
+/// IDE 参数中的路径占位符
+const IDE_PATH_PLACEHOLDER: &str = "{path}";
+
+/// 将参数中的 `{path}` 替换为目标路径，返回替换后的参数以及是否存在占位符
+fn substitute_path_placeholder(args: Vec<String>, path: &str) -> (Vec<String>, bool) {
+    let has_placeholder = args.iter().any(|arg| arg.contains(IDE_PATH_PLACEHOLDER));
+    let args = args
+        .into_iter()
+        .map(|arg| arg.replace(IDE_PATH_PLACEHOLDER, path))
+        .collect();
+    (args, has_placeholder)
+}
+
+/// 打开目标的命令行参数：支持行号的 IDE 使用各自的跳转写法，其余只传路径
+fn ide_target_args(kind: &SupportedIdeKind, path: &str, line: Option<u32>) -> Vec<String> {
+    match (kind, line) {
+        (SupportedIdeKind::Vscode | SupportedIdeKind::Trae, Some(line)) => {
+            vec!["-g".to_string(), format!("{}:{}", path, line)]
         }
-    })
+        (SupportedIdeKind::Idea | SupportedIdeKind::Webstorm, Some(line)) => {
+            vec!["--line".to_string(), line.to_string(), path.to_string()]
+        }
+        (SupportedIdeKind::Zed, Some(line)) => vec![format!("{}:{}", path, line)],
+        _ => vec![path.to_string()],
+    }
+}
+
+/// IDE 可执行文件不存在时的提示
+const IDE_MISSING_MESSAGE: &str = "IDE 可执行文件不存在，请重新配置";
+
+/// 启动前检查 IDE 可执行文件是否存在（macOS 上按应用名或 Bundle ID 启动的应用交由 `open` 解析）
+fn validate_ide_executable(ide_config: &IdeConfig) -> Result<(), AppError> {
+    #[cfg(target_os = "macos")]
+    {
+        let in_path = which(&ide_config.command).is_ok();
+        match classify_mac_app(&ide_config.command, in_path) {
+            Some(MacApp::Bundle(bundle)) if bundle.contains('/') => {
+                return if Path::new(&bundle).exists() {
+                    Ok(())
+                } else {
+                    Err(AppError::NotFound(format!("{}: {}", IDE_MISSING_MESSAGE, bundle)))
+                };
+            }
+            Some(_) => return Ok(()),
+            None => {}
+        }
+    }
+    resolve_ide_executable(ide_config).map(|_| ())
+}
+
+/// 解析 IDE 可执行文件：完整路径、PATH 中的命令，最后尝试该类 IDE 常见的启动器名称
+fn resolve_ide_executable(ide_config: &IdeConfig) -> Result<std::path::PathBuf, AppError> {
+    let command = &ide_config.command;
+    if Path::new(command).exists() {
+        // 如果是完整路径且文件存在，直接使用
+        return Ok(std::path::PathBuf::from(command));
+    }
+    // 配置的是完整路径但文件已不存在（如 IDE 已卸载或升级后路径变化）
+    if command.contains('/') || command.contains('\\') {
+        return Err(AppError::NotFound(format!("{}: {}", IDE_MISSING_MESSAGE, command)));
+    }
+    // 否则尝试在 PATH 中查找
+    if let Ok(path) = which(command) {
+        return Ok(path);
+    }
+
+    ide_launcher_aliases(&ide_config.kind)
+        .iter()
+        .find_map(|alias| which(alias).ok())
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "IDE 可执行文件不存在: {}，请确保已安装并在 PATH 中",
+                command
+            ))
+        })
+}
+
+/// 各类 IDE 在 PATH 中常见的启动器名称
+fn ide_launcher_aliases(kind: &SupportedIdeKind) -> &'static [&'static str] {
+    match kind {
+        SupportedIdeKind::Vscode => &["code", "code-insiders", "codium"],
+        SupportedIdeKind::Idea => &[
+            "idea",
+            "idea.sh",
+            "intellij-idea-ultimate",
+            "intellij-idea-community",
+        ],
+        SupportedIdeKind::Webstorm => &["webstorm", "webstorm.sh"],
+        SupportedIdeKind::Trae => &["trae"],
+        SupportedIdeKind::Zed => &["zed", "zeditor"],
+        SupportedIdeKind::Other => &[],
+    }
+}
+
+/// macOS 应用的启动目标
+#[derive(Debug, PartialEq)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+enum MacApp {
+    /// 应用包路径或应用名（`open -a`）
+    Bundle(String),
+    /// Bundle ID，如 com.microsoft.VSCode（`open -b`）
+    BundleId(String),
+}
+
+/// 判断 IDE 命令是否应作为 macOS 应用启动；CLI 启动器（如 code、idea）返回 None 直接执行
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn classify_mac_app(command: &str, in_path: bool) -> Option<MacApp> {
+    let trimmed = command.trim_end_matches('/');
+    if trimmed.ends_with(".app") {
+        return Some(MacApp::Bundle(trimmed.to_string()));
+    }
+    // 带路径的命令（包括 .app/Contents/MacOS 下的可执行文件）直接执行
+    if trimmed.contains('/') || in_path {
+        return None;
+    }
+    // 形如 com.vendor.App 的反向域名视为 Bundle ID，其余视为应用名
+    let is_bundle_id = trimmed.split('.').count() >= 3
+        && !trimmed.contains(' ')
+        && trimmed.split('.').all(|part| !part.is_empty());
+    if is_bundle_id {
+        Some(MacApp::BundleId(trimmed.to_string()))
+    } else {
+        Some(MacApp::Bundle(trimmed.to_string()))
+    }
 }
 
 /// 用终端打开仓库目录
 #[tauri::command]
-pub fn open_in_terminal(repo_id: String) -> Result<serde_json::Value, String> {
-    with_db!(conn, {
-        let path: String = conn
-            .query_row(
-                "SELECT path FROM git_repositories WHERE id = ?1",
-                params![repo_id],
-                |row| row.get(0),
-            )
-            .map_err(|e| format!("仓库不存在: {}", e))?;
+pub fn open_in_terminal(
+    workspace: Workspace,
+    repo_id: String,
+) -> Result<serde_json::Value, AppError> {
+    with_db!(workspace, conn, {
+        let path = repo_path(conn, &repo_id)?;
 
         // 检测并打开终端
         #[cfg(windows)]
@@ -599,30 +1664,19 @@ pub fn open_in_terminal(repo_id: String) -> Result<serde_json::Value, String> {
 /// 自动扫描并同步项目目录到数据库
 /// 当进入代码仓库页时，如果文件目录中有数据库中不存在的目录，自动导入到数据库中
 #[tauri::command]
-pub fn project_dirs_sync_auto(project_id: String) -> Result<serde_json::Value, String> {
-    with_db!(conn, {
+pub fn project_dirs_sync_auto(
+    workspace: Workspace,
+    project_id: String,
+) -> Result<serde_json::Value, AppError> {
+    with_db!(workspace, conn, {
     // 先获取项目信息，避免多次获取锁
     let project: crate::types::Project = conn
         .query_row(
-            "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at FROM projects WHERE id = ?1",
+            "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at, archived, sort_order, created_at FROM projects WHERE id = ?1",
             params![project_id],
-            |row| {
-                let display_json: Option<String> = row.get(4)?;
-                let ide_override_json: Option<String> = row.get(5)?;
-
-                Ok(crate::types::Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    project_path: row.get(3)?,
-                    display: display_json.and_then(|j| serde_json::from_str(&j).ok()),
-                    ide_override: ide_override_json.and_then(|j| serde_json::from_str(&j).ok()),
-                    visible: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            },
+            crate::commands::db_helpers::map_project_row,
         )
-        .map_err(|e| format!("项目不存在：{}", e))?;
+        .map_err(|e| AppError::lookup(&e, format!("项目不存在：{}", e)))?;
 
     let project_path = Path::new(&project.project_path);
 
@@ -647,22 +1701,22 @@ pub fn project_dirs_sync_auto(project_id: String) -> Result<serde_json::Value, S
     // 获取所有已有的目录类型
     let mut stmt = conn
         .prepare("SELECT id, kind FROM directory_types ORDER BY sort_order")
-        .map_err(|e| format!("查询目录类型失败：{}", e))?;
+        .map_err(|e| AppError::Db(format!("查询目录类型失败：{}", e)))?;
 
     let dir_types: Vec<(String, String)> = stmt
         .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
-        .map_err(|e| format!("读取目录类型失败：{}", e))?
+        .map_err(|e| AppError::Db(format!("读取目录类型失败：{}", e)))?
         .filter_map(|r| r.ok())
         .collect();
 
     // 获取项目中已绑定的目录
     let mut stmt = conn
         .prepare("SELECT dir_type_id, relative_path FROM project_directories WHERE project_id = ?1")
-        .map_err(|e| format!("查询项目目录失败：{}", e))?;
+        .map_err(|e| AppError::Db(format!("查询项目目录失败：{}", e)))?;
 
     let existing_dirs: std::collections::HashMap<String, String> = stmt
         .query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?)))
-        .map_err(|e| format!("读取项目目录失败：{}", e))?
+        .map_err(|e| AppError::Db(format!("读取项目目录失败：{}", e)))?
         .filter_map(|r| r.ok())
         .collect();
 
@@ -701,7 +1755,7 @@ pub fn project_dirs_sync_auto(project_id: String) -> Result<serde_json::Value, S
                  VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
                 params![id, project_id, dir_type_id, default_dir_name, now, now],
             )
-            .map_err(|e| format!("创建目录绑定失败：{}", e))?;
+            .map_err(|e| AppError::Db(format!("创建目录绑定失败：{}", e)))?;
 
             synced.push(default_dir_name.to_string());
         }
@@ -714,3 +1768,490 @@ pub fn project_dirs_sync_auto(project_id: String) -> Result<serde_json::Value, S
     }))
 })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SCHEMA;
+    use rusqlite::Connection;
+
+    fn setup_dir_types() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute_batch(
+            "INSERT INTO directory_types (id, kind, name, sort_order, created_at, updated_at)
+             VALUES ('t-code', 'code', '代码', 1, '2024-01-01', '2024-01-01'),
+                    ('t-custom', 'custom', '素材', 10, '2024-01-01', '2024-01-01');
+             INSERT INTO project_directories (id, project_id, dir_type_id, relative_path, created_at, updated_at)
+             VALUES ('d1', 'p1', 't-custom', 'assets', '2024-01-01', '2024-01-01');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_upsert_project_dir_rolls_back_when_dir_creation_fails() {
+        let mut conn = setup_dir_types();
+        let root = tempfile::tempdir().unwrap();
+
+        let dir = upsert_project_dir_with_conn(
+            &mut conn,
+            "p1".to_string(),
+            "t-code".to_string(),
+            "src".to_string(),
+            &root.path().join("src"),
+        )
+        .unwrap();
+        assert_eq!(dir.relative_path, "src");
+        assert!(root.path().join("src").is_dir());
+
+        // 目标位置已是文件，目录创建失败，映射保持原值
+        fs::write(root.path().join("blocked"), "").unwrap();
+        let result = upsert_project_dir_with_conn(
+            &mut conn,
+            "p1".to_string(),
+            "t-code".to_string(),
+            "blocked".to_string(),
+            &root.path().join("blocked"),
+        );
+        assert!(result.is_err());
+        let relative_path: String = conn
+            .query_row(
+                "SELECT relative_path FROM project_directories WHERE project_id = 'p1' AND dir_type_id = 't-code'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(relative_path, "src");
+    }
+
+    #[test]
+    fn test_delete_project_dir_is_idempotent() {
+        let conn = setup_dir_types();
+
+        let removed = delete_project_dir_with_conn(&conn, "p1", "t-custom").unwrap();
+        assert_eq!(removed.as_deref(), Some("assets"));
+        let again = delete_project_dir_with_conn(&conn, "p1", "t-custom").unwrap();
+        assert!(again.is_none());
+    }
+
+    #[test]
+    fn test_remove_project_subdir_rejects_paths_outside_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(dir.path().join("outside")).unwrap();
+
+        assert!(matches!(
+            remove_project_subdir(&root, "../outside"),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            remove_project_subdir(&root, "."),
+            Err(AppError::Validation(_))
+        ));
+        assert!(dir.path().join("outside").exists());
+        assert!(remove_project_subdir(&root, "missing").is_ok());
+    }
+
+    #[test]
+    fn test_update_dir_type_keeps_created_at() {
+        let conn = setup_dir_types();
+
+        let first = update_dir_type_with_conn(
+            &conn,
+            "t-custom".to_string(),
+            &serde_json::json!({ "name": "素材库" }),
+        )
+        .unwrap();
+        assert_eq!(first.created_at, "2024-01-01");
+        assert_eq!(first.name, "素材库");
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = update_dir_type_with_conn(
+            &conn,
+            "t-custom".to_string(),
+            &serde_json::json!({ "sortOrder": 3 }),
+        )
+        .unwrap();
+        assert_eq!(second.created_at, "2024-01-01");
+        assert_eq!(second.name, "素材库");
+        assert_eq!(second.usage_count, 1);
+        assert!(second.updated_at > first.updated_at);
+    }
+
+    #[test]
+    fn test_reorder_dir_types() {
+        let mut conn = setup_dir_types();
+
+        let ids = vec!["t-custom".to_string(), "missing".to_string()];
+        assert!(reorder_dir_types_with_conn(&mut conn, &ids).is_err());
+        let order: i32 = conn
+            .query_row(
+                "SELECT sort_order FROM directory_types WHERE id = 't-custom'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(order, 10);
+
+        let ids = vec!["t-custom".to_string(), "t-code".to_string()];
+        reorder_dir_types_with_conn(&mut conn, &ids).unwrap();
+        let ordered: Vec<String> = conn
+            .prepare("SELECT id FROM directory_types ORDER BY sort_order")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ordered, ids);
+    }
+
+    #[test]
+    fn test_delete_dir_type() {
+        let mut conn = setup_dir_types();
+
+        assert!(delete_dir_type_with_conn(&mut conn, "t-code", true).is_err());
+        assert!(delete_dir_type_with_conn(&mut conn, "t-custom", false).is_err());
+        delete_dir_type_with_conn(&mut conn, "t-custom", true).unwrap();
+
+        let remaining: i64 = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM directory_types) + (SELECT COUNT(*) FROM project_directories)",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(remaining, 1);
+    }
+
+    #[test]
+    fn test_render_thumbnail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wide.png");
+        image::RgbImage::new(64, 32).save(&path).unwrap();
+
+        let png = render_thumbnail(&path, 16).unwrap();
+        let thumb = image::load_from_memory(&png).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (16, 8));
+
+        // 小于目标尺寸的图片不放大
+        let png = render_thumbnail(&path, 128).unwrap();
+        let thumb = image::load_from_memory(&png).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (64, 32));
+
+        let text = dir.path().join("notes.txt");
+        fs::write(&text, "hello").unwrap();
+        assert!(render_thumbnail(&text, 16).is_err());
+    }
+
+    #[test]
+    fn test_classify_mac_app() {
+        assert_eq!(
+            classify_mac_app("/Applications/Visual Studio Code.app/", false),
+            Some(MacApp::Bundle("/Applications/Visual Studio Code.app".to_string()))
+        );
+        assert_eq!(
+            classify_mac_app("Visual Studio Code", false),
+            Some(MacApp::Bundle("Visual Studio Code".to_string()))
+        );
+        assert_eq!(
+            classify_mac_app("com.microsoft.VSCode", false),
+            Some(MacApp::BundleId("com.microsoft.VSCode".to_string()))
+        );
+        assert_eq!(classify_mac_app("code", true), None);
+        assert_eq!(classify_mac_app("/usr/local/bin/idea", false), None);
+    }
+
+    #[test]
+    fn test_desktop_exec_command() {
+        let content = "[Desktop Entry]\nName=Visual Studio Code\nExec=/usr/share/code/code --unity-launch %F\n\n[Desktop Action new-empty-window]\nExec=/usr/share/code/code --new-window %F\n";
+        assert_eq!(
+            desktop_exec_command(content),
+            Some((
+                "/usr/share/code/code".to_string(),
+                vec!["--unity-launch".to_string()]
+            ))
+        );
+
+        let quoted = "[Desktop Entry]\nExec=\"/opt/JetBrains Toolbox/idea.sh\" %u\n";
+        assert_eq!(
+            desktop_exec_command(quoted),
+            Some(("/opt/JetBrains Toolbox/idea.sh".to_string(), vec![]))
+        );
+        assert_eq!(desktop_exec_command("[Desktop Entry]\nName=Foo\n"), None);
+    }
+
+    #[test]
+    fn test_ide_list_includes_vscode_variants() {
+        let ides = ide_list_supported().unwrap();
+        for name in ["VS Code", "VS Code Insiders", "VSCodium", "Cursor"] {
+            assert!(
+                ides.iter()
+                    .any(|ide| ide.name == name && ide.kind == SupportedIdeKind::Vscode),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_project_ide_prefers_project_override() {
+        let conn = setup_dir_types();
+        let ide = |name: &str| IdeConfig {
+            kind: SupportedIdeKind::Other,
+            name: name.to_string(),
+            command: "code".to_string(),
+            args: None,
+            available: None,
+        };
+        let settings = serde_json::json!({ "defaultIde": ide("Workspace") }).to_string();
+        conn.execute(
+            "INSERT INTO workspace_meta (key, value, updated_at) VALUES ('settings', ?1, '2024-01-01')",
+            params![settings],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO projects (id, name, project_path, ide_override_json, created_at, updated_at)
+             VALUES ('p1', 'Alpha', '/tmp/alpha', ?1, '2024-01-01', '2024-01-01'),
+                    ('p2', 'Beta', '/tmp/beta', NULL, '2024-01-01', '2024-01-01')",
+            params![serde_json::to_string(&ide("Project")).unwrap()],
+        )
+        .unwrap();
+
+        let (config, source) = resolve_project_ide(&conn, "p1", None).unwrap();
+        assert_eq!((config.name.as_str(), source), ("Project", IdeSource::Project));
+        let (config, source) = resolve_project_ide(&conn, "p2", None).unwrap();
+        assert_eq!((config.name.as_str(), source), ("Workspace", IdeSource::Workspace));
+        let (_, source) = resolve_project_ide(&conn, "p1", Some(ide("Provided"))).unwrap();
+        assert_eq!(source, IdeSource::Provided);
+    }
+
+    #[test]
+    fn test_resolve_repo_ide_falls_back_to_project_override() {
+        let conn = setup_dir_types();
+        let ide = |name: &str| IdeConfig {
+            kind: SupportedIdeKind::Other,
+            name: name.to_string(),
+            command: "code".to_string(),
+            args: None,
+            available: None,
+        };
+        conn.execute(
+            "INSERT INTO projects (id, name, project_path, ide_override_json, created_at, updated_at)
+             VALUES ('p1', 'Alpha', '/tmp/alpha', ?1, '2024-01-01', '2024-01-01')",
+            params![serde_json::to_string(&ide("Project")).unwrap()],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO git_repositories (id, project_id, name, path, ide_override_json, created_at, updated_at)
+             VALUES ('r1', 'p1', 'api', '/tmp/alpha/api', NULL, '2024-01-01', '2024-01-01'),
+                    ('r2', 'p1', 'web', '/tmp/alpha/web', ?1, '2024-01-01', '2024-01-01')",
+            params![serde_json::to_string(&ide("Repo")).unwrap()],
+        )
+        .unwrap();
+
+        let (config, source) = resolve_effective_ide(&conn, "r1", None).unwrap();
+        assert_eq!((config.name.as_str(), source), ("Project", IdeSource::Project));
+        let (config, source) = resolve_effective_ide(&conn, "r2", None).unwrap();
+        assert_eq!((config.name.as_str(), source), ("Repo", IdeSource::Repository));
+    }
+
+    #[test]
+    fn test_ide_target_args() {
+        assert_eq!(
+            ide_target_args(&SupportedIdeKind::Vscode, "/r/src/main.rs", Some(12)),
+            vec!["-g", "/r/src/main.rs:12"]
+        );
+        assert_eq!(
+            ide_target_args(&SupportedIdeKind::Idea, "/r/src/Main.java", Some(3)),
+            vec!["--line", "3", "/r/src/Main.java"]
+        );
+        assert_eq!(
+            ide_target_args(&SupportedIdeKind::Other, "/r/notes.txt", Some(5)),
+            vec!["/r/notes.txt"]
+        );
+        assert_eq!(
+            ide_target_args(&SupportedIdeKind::Vscode, "/r", None),
+            vec!["/r"]
+        );
+    }
+
+    #[test]
+    fn test_substitute_path_placeholder() {
+        let args = vec!["--folder".to_string(), "{path}".to_string(), "--new".to_string()];
+        let (args, has_placeholder) = substitute_path_placeholder(args, "/r/app");
+        assert!(has_placeholder);
+        assert_eq!(args, vec!["--folder", "/r/app", "--new"]);
+
+        let (args, has_placeholder) =
+            substitute_path_placeholder(vec!["--uri=file://{path}".to_string()], "/r/app");
+        assert!(has_placeholder);
+        assert_eq!(args, vec!["--uri=file:///r/app"]);
+
+        let (args, has_placeholder) = substitute_path_placeholder(vec!["-n".to_string()], "/r/app");
+        assert!(!has_placeholder);
+        assert_eq!(args, vec!["-n"]);
+    }
+
+    #[test]
+    fn test_validate_ide_executable_missing_path() {
+        let ide = IdeConfig {
+            kind: SupportedIdeKind::Other,
+            name: "Stale".to_string(),
+            command: "/nonexistent/bin/stale-ide".to_string(),
+            args: None,
+            available: None,
+        };
+        let err = validate_ide_executable(&ide).unwrap_err();
+        assert!(err.message().starts_with(IDE_MISSING_MESSAGE));
+    }
+
+    #[test]
+    fn test_detect_toolbox_ides() {
+        let dir = tempfile::tempdir().unwrap();
+        let apps = dir.path();
+        for build in ["ch-0/232.9921.47", "ch-0/233.11799.241", "ch-1/233.9102.97"] {
+            let bin = apps.join("IDEA-U").join(build).join("bin");
+            fs::create_dir_all(&bin).unwrap();
+            fs::write(bin.join("idea64.exe"), "").unwrap();
+            fs::write(bin.join("idea.exe"), "").unwrap();
+            fs::write(bin.join("idea.bat"), "").unwrap();
+        }
+        fs::create_dir_all(apps.join("IDEA-U/ch-0/233.11799.241.plugins")).unwrap();
+        let bin = apps.join("WebStorm/ch-0/241.14494.229/bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(bin.join("webstorm64.exe"), "").unwrap();
+        fs::write(bin.join("webstorm.cmd"), "").unwrap();
+        fs::write(bin.join("webstorm.bat"), "").unwrap();
+
+        let ides = detect_toolbox_ides(apps);
+        assert_eq!(ides.len(), 2);
+        assert_eq!(ides[0].name, "IntelliJ IDEA");
+        assert!(Path::new(&ides[0].command)
+            .ends_with(Path::new("IDEA-U/ch-0/233.11799.241/bin/idea.exe")));
+        assert_eq!(ides[1].name, "WebStorm");
+        assert!(ides[1].command.ends_with("webstorm.cmd"));
+    }
+
+    #[test]
+    fn test_merge_detected_ide() {
+        let cli = |available| IdeConfig {
+            kind: SupportedIdeKind::Vscode,
+            name: "VS Code".to_string(),
+            command: "code".to_string(),
+            args: None,
+            available: Some(available),
+        };
+        let app = detected_ide(
+            SupportedIdeKind::Vscode,
+            "VS Code",
+            "/Applications/Visual Studio Code.app".to_string(),
+            None,
+        );
+
+        let mut ides = vec![cli(false)];
+        merge_detected_ide(&mut ides, app.clone());
+        assert_eq!(ides, vec![app.clone()]);
+
+        let mut ides = vec![cli(true)];
+        merge_detected_ide(&mut ides, app.clone());
+        assert_eq!(ides[0].command, "code");
+
+        let pycharm = detected_ide(SupportedIdeKind::Other, "PyCharm", "pycharm".to_string(), None);
+        merge_detected_ide(&mut ides, pycharm);
+        assert_eq!(ides.len(), 2);
+    }
+
+    #[test]
+    fn test_sniff_preview_kind() {
+        assert_eq!(
+            sniff_preview_kind(b"\x89PNG\r\n\x1a\n\0\0"),
+            Some(PreviewKind::Image)
+        );
+        assert_eq!(sniff_preview_kind(b"\xFF\xD8\xFF\xE0"), Some(PreviewKind::Image));
+        assert_eq!(sniff_preview_kind(b"GIF89a..."), Some(PreviewKind::Image));
+        assert_eq!(
+            sniff_preview_kind(b"RIFF\x24\0\0\0WEBPVP8 "),
+            Some(PreviewKind::Image)
+        );
+        assert_eq!(sniff_preview_kind(b"%PDF-1.7"), Some(PreviewKind::Pdf));
+        assert_eq!(sniff_preview_kind(b"\xEF\xBB\xBF# title"), Some(PreviewKind::Text));
+        assert_eq!(sniff_preview_kind(b"PK\x03\x04"), None);
+        assert_eq!(sniff_preview_kind(b""), None);
+    }
+
+    #[test]
+    fn test_preview_detect_prefers_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let extensionless = dir.path().join("screenshot");
+        let mislabeled = dir.path().join("report.txt");
+        let markdown = dir.path().join("README.md");
+        std::fs::write(&extensionless, b"\x89PNG\r\n\x1a\n").unwrap();
+        std::fs::write(&mislabeled, b"%PDF-1.4").unwrap();
+        std::fs::write(&markdown, b"\xEF\xBB\xBF# Title").unwrap();
+
+        let detect = |p: &Path| preview_detect(p.to_string_lossy().to_string()).unwrap().kind;
+        assert_eq!(detect(&extensionless), PreviewKind::Image);
+        assert_eq!(detect(&mislabeled), PreviewKind::Pdf);
+        assert_eq!(detect(&markdown), PreviewKind::Markdown);
+        assert_eq!(detect(&dir.path().join("missing.xlsx")), PreviewKind::Excel);
+    }
+
+    #[test]
+    fn test_language_from_file_name() {
+        let cases = [
+            ("main.rs", "rs", "rust"),
+            ("index.ts", "ts", "typescript"),
+            ("App.tsx", "tsx", "tsx"),
+            ("app.js", "js", "javascript"),
+            ("App.vue", "vue", "vue"),
+            ("script.py", "py", "python"),
+            ("main.go", "go", "go"),
+            ("Main.java", "java", "java"),
+            ("lib.cpp", "cpp", "cpp"),
+            ("Program.cs", "cs", "csharp"),
+            ("build.sh", "sh", "bash"),
+            ("schema.sql", "sql", "sql"),
+            ("config.yml", "yml", "yaml"),
+            ("Cargo.toml", "toml", "toml"),
+            ("package.json", "json", "json"),
+            ("Dockerfile", "", "dockerfile"),
+        ];
+        for (file_name, extension, expected) in cases {
+            assert_eq!(
+                language_from_file_name(file_name, extension),
+                Some(expected),
+                "{}",
+                file_name
+            );
+        }
+        assert_eq!(language_from_file_name("notes.txt", "txt"), None);
+    }
+
+    #[test]
+    fn test_preview_detect_language_only_for_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("main.rs");
+        let image = dir.path().join("logo.png");
+        std::fs::write(&source, "fn main() {}").unwrap();
+        std::fs::write(&image, b"\x89PNG\r\n\x1a\n").unwrap();
+
+        let result = preview_detect(source.to_string_lossy().to_string()).unwrap();
+        assert_eq!(result.language.as_deref(), Some("rust"));
+        let result = preview_detect(image.to_string_lossy().to_string()).unwrap();
+        assert_eq!(result.language, None);
+    }
+
+    #[test]
+    fn test_preview_kind_from_extension_media() {
+        assert_eq!(preview_kind_from_extension("pdf"), PreviewKind::Pdf);
+        for ext in ["mp4", "webm", "mov"] {
+            assert_eq!(preview_kind_from_extension(ext), PreviewKind::Video);
+        }
+        for ext in ["mp3", "wav", "flac"] {
+            assert_eq!(preview_kind_from_extension(ext), PreviewKind::Audio);
+        }
+        assert_eq!(preview_kind_from_extension("unknown"), PreviewKind::Text);
+    }
+}
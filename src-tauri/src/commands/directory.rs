@@ -1,5 +1,7 @@
 use crate::commands::module::module_get;
 use crate::commands::project::project_get;
+use crate::commands::workspace::Workspace;
+use crate::error::AppError;
 use crate::types::*;
 use crate::with_db;
 use chrono::Utc;
@@ -9,15 +11,18 @@ use std::path::Path;
 
 /// 列出项目的所有目录
 #[tauri::command]
-pub fn directory_list(project_id: String) -> Result<Vec<Directory>, String> {
-    with_db!(conn, {
+pub fn directory_list(
+    workspace: Workspace,
+    project_id: String,
+) -> Result<Vec<Directory>, AppError> {
+    with_db!(workspace, conn, {
         let mut stmt = conn
             .prepare(
                 "SELECT id, project_id, name, relative_path, module_id, module_config_json,
                         sort_order, created_at, updated_at
                  FROM directories WHERE project_id = ?1 ORDER BY sort_order, name",
             )
-            .map_err(|e| format!("查询失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
 
         let dirs = stmt
             .query_map(params![project_id], |row| {
@@ -37,9 +42,9 @@ pub fn directory_list(project_id: String) -> Result<Vec<Directory>, String> {
                     updated_at: row.get(8)?,
                 })
             })
-            .map_err(|e| format!("查询失败: {}", e))?
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
             .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("读取数据失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
 
         Ok(dirs)
     })
@@ -47,8 +52,8 @@ pub fn directory_list(project_id: String) -> Result<Vec<Directory>, String> {
 
 /// 获取目录
 #[tauri::command]
-pub fn directory_get(id: String) -> Result<Directory, String> {
-    with_db!(conn, {
+pub fn directory_get(workspace: Workspace, id: String) -> Result<Directory, AppError> {
+    with_db!(workspace, conn, {
         conn.query_row(
             "SELECT id, project_id, name, relative_path, module_id, module_config_json,
                     sort_order, created_at, updated_at
@@ -72,23 +77,27 @@ pub fn directory_get(id: String) -> Result<Directory, String> {
                 })
             },
         )
-        .map_err(|e| format!("目录不存在: {}", e))
+        .map_err(|e| AppError::lookup(&e, format!("目录不存在: {}", e)))
     })
 }
 
 /// 创建目录
 #[tauri::command]
-pub fn directory_create(project_id: String, input: serde_json::Value) -> Result<Directory, String> {
+pub fn directory_create(
+    workspace: Workspace,
+    project_id: String,
+    input: serde_json::Value,
+) -> Result<Directory, AppError> {
     let name = input
         .get("name")
         .and_then(|v| v.as_str())
-        .ok_or("缺少目录名称")?
+        .ok_or_else(|| AppError::Validation("缺少目录名称".to_string()))?
         .to_string();
 
     let relative_path = input
         .get("relativePath")
         .and_then(|v| v.as_str())
-        .ok_or("缺少目录路径")?
+        .ok_or_else(|| AppError::Validation("缺少目录路径".to_string()))?
         .to_string();
 
     let module_id = input
@@ -101,12 +110,12 @@ pub fn directory_create(project_id: String, input: serde_json::Value) -> Result<
     let sort_order = input.get("sortOrder").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
 
     // 获取项目信息，创建物理目录
-    let project = project_get(project_id.clone())?;
+    let project = project_get(workspace.clone(), project_id.clone())?;
     let full_path = Path::new(&project.project_path).join(&relative_path);
 
     // 创建物理目录（如果不存在）
     fs::create_dir_all(&full_path)
-        .map_err(|e| format!("创建物理目录失败: {} - {}", full_path.display(), e))?;
+        .map_err(|e| AppError::io(&e, format!("创建物理目录失败: {} - {}", full_path.display(), e)))?;
 
     let id = uuid::Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
@@ -116,18 +125,18 @@ pub fn directory_create(project_id: String, input: serde_json::Value) -> Result<
         .map(|c| serde_json::to_string(c).ok())
         .flatten();
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute(
             "INSERT INTO directories (id, project_id, name, relative_path, module_id, module_config_json, sort_order, created_at, updated_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![id, project_id, name, relative_path, module_id, module_config_json, sort_order, now, now],
         )
-        .map_err(|e| format!("创建目录失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("创建目录失败: {}", e)))?;
     });
 
     // 如果创建的是 task 模块目录，初始化默认列
     if module_id.as_deref() == Some("builtin:task") {
-        crate::commands::task::task_column_init_defaults(id.clone())?;
+        crate::commands::task::task_column_init_defaults(workspace.clone(), id.clone())?;
     }
 
     Ok(Directory {
@@ -145,9 +154,13 @@ pub fn directory_create(project_id: String, input: serde_json::Value) -> Result<
 
 /// 更新目录
 #[tauri::command]
-pub fn directory_update(id: String, patch: serde_json::Value) -> Result<Directory, String> {
+pub fn directory_update(
+    workspace: Workspace,
+    id: String,
+    patch: serde_json::Value,
+) -> Result<Directory, AppError> {
     // 获取当前目录
-    let dir = directory_get(id.clone())?;
+    let dir = directory_get(workspace.clone(), id.clone())?;
 
     // 保存原始值用于后续比较
     let original_relative_path = dir.relative_path.clone();
@@ -191,17 +204,17 @@ pub fn directory_update(id: String, patch: serde_json::Value) -> Result<Director
 
     // 如果相对路径变了，也更新物理目录
     if relative_path != original_relative_path {
-        let project = project_get(original_project_id)?;
+        let project = project_get(workspace.clone(), original_project_id)?;
         let old_full_path = Path::new(&project.project_path).join(&original_relative_path);
         let new_full_path = Path::new(&project.project_path).join(&relative_path);
 
         if old_full_path.exists() && old_full_path.is_dir() {
             fs::rename(&old_full_path, &new_full_path)
-                .map_err(|e| format!("移动目录失败: {} - {}", old_full_path.display(), e))?;
+                .map_err(|e| AppError::io(&e, format!("移动目录失败: {} - {}", old_full_path.display(), e)))?;
         }
     }
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute(
             "UPDATE directories SET name = ?1, relative_path = ?2, module_id = ?3,
                                module_config_json = ?4, sort_order = ?5, updated_at = ?6
@@ -216,7 +229,7 @@ pub fn directory_update(id: String, patch: serde_json::Value) -> Result<Director
                 id
             ],
         )
-        .map_err(|e| format!("更新目录失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("更新目录失败: {}", e)))?;
     });
 
     Ok(Directory {
@@ -234,13 +247,13 @@ pub fn directory_update(id: String, patch: serde_json::Value) -> Result<Director
 
 /// 删除目录
 #[tauri::command]
-pub fn directory_delete(id: String) -> Result<(), String> {
+pub fn directory_delete(workspace: Workspace, id: String) -> Result<(), AppError> {
     // 获取目录信息用于删除物理目录
-    let _dir = directory_get(id.clone())?;
+    let _dir = directory_get(workspace.clone(), id.clone())?;
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute("DELETE FROM directories WHERE id = ?1", params![id])
-            .map_err(|e| format!("删除目录失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("删除目录失败: {}", e)))?;
     });
 
     Ok(())
@@ -249,17 +262,21 @@ pub fn directory_delete(id: String) -> Result<(), String> {
 /// 在目录上启用模块
 #[tauri::command]
 pub fn directory_enable_module(
+    workspace: Workspace,
     id: String,
     module_id: String,
     config: Option<serde_json::Value>,
-) -> Result<Directory, String> {
+) -> Result<Directory, AppError> {
     // 验证模块存在
-    let _module = module_get(module_id.clone())?;
+    let _module = module_get(workspace.clone(), module_id.clone())?;
 
     // 验证配置
     if let Some(cfg) = &config {
-        let validation =
-            crate::commands::module::module_validate_config(module_id.clone(), cfg.clone())?;
+        let validation = crate::commands::module::module_validate_config(
+            workspace.clone(),
+            module_id.clone(),
+            cfg.clone(),
+        )?;
         if !validation
             .get("valid")
             .and_then(|v| v.as_bool())
@@ -275,7 +292,7 @@ pub fn directory_enable_module(
                         .join(", ")
                 })
                 .unwrap_or_default();
-            return Err(format!("配置验证失败: {}", errors));
+            return Err(AppError::Validation(format!("配置验证失败: {}", errors)));
         }
     }
 
@@ -286,7 +303,7 @@ pub fn directory_enable_module(
 
     let now = Utc::now().to_rfc3339();
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         // 检查目录是否已有模块
         let current_module_id: Option<String> = conn
             .query_row(
@@ -298,53 +315,59 @@ pub fn directory_enable_module(
             .flatten();
 
         if current_module_id.is_some() {
-            return Err("目录已启用模块，请先禁用现有模块".to_string());
+            return Err(AppError::Validation("目录已启用模块，请先禁用现有模块".into()));
         }
 
         conn.execute(
             "UPDATE directories SET module_id = ?1, module_config_json = ?2, updated_at = ?3 WHERE id = ?4",
             params![module_id, module_config_json, now, id],
         )
-        .map_err(|e| format!("启用模块失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("启用模块失败: {}", e)))?;
     });
 
     // 如果启用的是 task 模块，初始化默认列
     if module_id == "builtin:task" {
-        crate::commands::task::task_column_init_defaults(id.clone())?;
+        crate::commands::task::task_column_init_defaults(workspace.clone(), id.clone())?;
     }
 
-    directory_get(id)
+    directory_get(workspace, id)
 }
 
 /// 禁用目录上的模块
 #[tauri::command]
-pub fn directory_disable_module(id: String) -> Result<Directory, String> {
+pub fn directory_disable_module(workspace: Workspace, id: String) -> Result<Directory, AppError> {
     let now = Utc::now().to_rfc3339();
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute(
             "UPDATE directories SET module_id = NULL, module_config_json = NULL, updated_at = ?1 WHERE id = ?2",
             params![now, id],
         )
-        .map_err(|e| format!("禁用模块失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("禁用模块失败: {}", e)))?;
     });
 
-    directory_get(id)
+    directory_get(workspace, id)
 }
 
 /// 更新目录的模块配置
 #[tauri::command]
 pub fn directory_update_module_config(
+    workspace: Workspace,
     id: String,
     config: serde_json::Value,
-) -> Result<Directory, String> {
+) -> Result<Directory, AppError> {
     // 获取当前模块
-    let dir = directory_get(id.clone())?;
-    let module_id = dir.module_id.ok_or("目录未启用模块")?;
+    let dir = directory_get(workspace.clone(), id.clone())?;
+    let module_id = dir
+        .module_id
+        .ok_or_else(|| AppError::Validation("目录未启用模块".to_string()))?;
 
     // 验证配置
-    let validation =
-        crate::commands::module::module_validate_config(module_id.clone(), config.clone())?;
+    let validation = crate::commands::module::module_validate_config(
+        workspace.clone(),
+        module_id.clone(),
+        config.clone(),
+    )?;
     if !validation
         .get("valid")
         .and_then(|v| v.as_bool())
@@ -360,36 +383,40 @@ pub fn directory_update_module_config(
                     .join(", ")
             })
             .unwrap_or_default();
-        return Err(format!("配置验证失败: {}", errors));
+        return Err(AppError::Validation(format!("配置验证失败: {}", errors)));
     }
 
     let module_config_json = serde_json::to_string(&config).ok();
 
     let now = Utc::now().to_rfc3339();
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute(
             "UPDATE directories SET module_config_json = ?1, updated_at = ?2 WHERE id = ?3",
             params![module_config_json, now, id],
         )
-        .map_err(|e| format!("更新模块配置失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("更新模块配置失败: {}", e)))?;
     });
 
-    directory_get(id)
+    directory_get(workspace, id)
 }
 
 /// 重新排序目录
 #[tauri::command]
-pub fn directory_reorder(project_id: String, ordered_ids: Vec<String>) -> Result<(), String> {
+pub fn directory_reorder(
+    workspace: Workspace,
+    project_id: String,
+    ordered_ids: Vec<String>,
+) -> Result<(), AppError> {
     let now = Utc::now().to_rfc3339();
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         for (index, id) in ordered_ids.iter().enumerate() {
             conn.execute(
                 "UPDATE directories SET sort_order = ?1, updated_at = ?2 WHERE id = ?3 AND project_id = ?4",
                 params![index as i32, now, id, project_id],
             )
-            .map_err(|e| format!("更新排序失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("更新排序失败: {}", e)))?;
         }
     });
 
@@ -0,0 +1,159 @@
+//! 文件树的忽略规则：内置默认列表、项目根目录的 .gitignore 以及调用方传入的排除模式
+//! .gitignore 只解析根目录下的文件，支持 `!` 取反、末尾 `/` 仅匹配目录和带 `/` 的锚定模式。
+
+use glob::{MatchOptions, Pattern};
+use std::fs;
+use std::path::Path;
+
+/// 始终跳过的条目
+const ALWAYS_IGNORED: &[&str] = &[".git"];
+
+/// 默认忽略的依赖与构建产物
+const DEFAULT_IGNORES: &[&str] = &[
+    "node_modules/",
+    "target/",
+    "dist/",
+    "build/",
+    "__pycache__/",
+    ".venv/",
+    ".DS_Store",
+    "Thumbs.db",
+];
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+struct Rule {
+    pattern: Pattern,
+    negate: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Rule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negate, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.trim_start_matches('/');
+        if line.is_empty() {
+            return None;
+        }
+
+        Pattern::new(line).ok().map(|pattern| Rule {
+            pattern,
+            negate,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative: &str, name: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            self.pattern.matches_with(relative, MATCH_OPTIONS)
+        } else {
+            self.pattern.matches_with(name, MATCH_OPTIONS)
+        }
+    }
+}
+
+/// 一组忽略规则（后出现的规则优先，与 git 一致）
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+    excludes: Vec<Pattern>,
+}
+
+impl IgnoreRules {
+    /// 构建规则：`respect_gitignore` 为 true 时启用默认列表和根目录 .gitignore
+    pub fn load(project_root: &Path, respect_gitignore: bool, exclude: &[String]) -> IgnoreRules {
+        let mut rules = Vec::new();
+        if respect_gitignore {
+            rules.extend(DEFAULT_IGNORES.iter().filter_map(|line| Rule::parse(line)));
+            if let Ok(content) = fs::read_to_string(project_root.join(".gitignore")) {
+                rules.extend(content.lines().filter_map(Rule::parse));
+            }
+        }
+
+        let excludes = exclude
+            .iter()
+            .filter_map(|p| Pattern::new(p.trim().trim_start_matches('/')).ok())
+            .collect();
+
+        IgnoreRules { rules, excludes }
+    }
+
+    /// 判断条目是否被忽略；`relative` 为相对项目根目录、以 `/` 分隔的路径
+    pub fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        let relative = relative.trim_start_matches('/');
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+
+        if ALWAYS_IGNORED.contains(&name) {
+            return true;
+        }
+        if self.excludes.iter().any(|p| {
+            p.matches_with(relative, MATCH_OPTIONS) || p.matches_with(name, MATCH_OPTIONS)
+        }) {
+            return true;
+        }
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(relative, name, is_dir))
+            .map(|rule| !rule.negate)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules_from(gitignore: &str, exclude: &[&str]) -> IgnoreRules {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".gitignore"), gitignore).unwrap();
+        let exclude: Vec<String> = exclude.iter().map(|s| s.to_string()).collect();
+        IgnoreRules::load(dir.path(), true, &exclude)
+    }
+
+    #[test]
+    fn test_git_dir_always_ignored() {
+        let rules = IgnoreRules::load(Path::new("/nonexistent"), false, &[]);
+        assert!(rules.is_ignored(".git", true));
+        assert!(rules.is_ignored("vendor/lib/.git", true));
+        assert!(!rules.is_ignored("node_modules", true));
+    }
+
+    #[test]
+    fn test_gitignore_patterns() {
+        let rules = rules_from("*.log\n/secret/\n!keep.log\ndocs/*.tmp\n", &["*.bak"]);
+
+        assert!(rules.is_ignored("node_modules", true));
+        assert!(rules.is_ignored("logs/app.log", false));
+        assert!(!rules.is_ignored("keep.log", false));
+        assert!(rules.is_ignored("secret", true));
+        assert!(!rules.is_ignored("secret", false));
+        assert!(!rules.is_ignored("src/secret", true));
+        assert!(rules.is_ignored("docs/draft.tmp", false));
+        assert!(!rules.is_ignored("docs/nested/draft.tmp", false));
+        assert!(rules.is_ignored("/notes.bak", false));
+        assert!(!rules.is_ignored("src/main.rs", false));
+    }
+}
@@ -0,0 +1,1192 @@
+mod ignore;
+mod watch;
+
+pub use watch::*;
+
+use crate::commands::project::project_get;
+use crate::commands::workspace::Workspace;
+use crate::error::AppError;
+use crate::types::*;
+use crate::with_db;
+use std::fs;
+use ignore::IgnoreRules;
+use std::path::{Component, Path, PathBuf};
+
+/// Normalize a path: on Windows, convert forward slashes to backslashes
+fn normalize_path(path: &str) -> String {
+    if cfg!(windows) {
+        path.replace('/', "\\")
+    } else {
+        path.to_string()
+    }
+}
+
+/// 将项目内的相对路径解析为绝对路径，拒绝绝对路径和 `..` 等越出项目根目录的写法
+pub(crate) fn resolve_in_project(project_root: &Path, relative: &str) -> Result<PathBuf, AppError> {
+    let relative = normalize_path(relative);
+    let mut resolved = project_root.to_path_buf();
+    for component in Path::new(&relative).components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            _ => return Err(AppError::Validation(format!("路径超出项目目录: {}", relative))),
+        }
+    }
+    if resolved == project_root {
+        return Err(AppError::Validation("路径不能为项目根目录".to_string()));
+    }
+    Ok(resolved)
+}
+
+/// 移除已存在的目标（用于允许覆盖的移动/复制）
+fn remove_existing(target: &Path) -> Result<(), AppError> {
+    if target.is_dir() {
+        fs::remove_dir_all(target).map_err(|e| AppError::io(&e, format!("删除目标目录失败: {}", e)))
+    } else {
+        fs::remove_file(target).map_err(|e| AppError::io(&e, format!("删除目标文件失败: {}", e)))
+    }
+}
+
+/// 获取项目的文件系统树
+/// `respectGitignore` 默认开启，跳过默认忽略列表和 .gitignore 中的条目；`.git` 始终跳过。
+/// 子项按目录优先、名称不区分大小写排序；`offset`/`limit` 仅对 `relativeRoot` 这一层分页
+/// 传入 `dirTypeId` 时以该目录类型映射的目录为根，`relativeRoot` 相对于映射目录解析
+#[tauri::command]
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub fn project_fs_tree(
+    workspace: Workspace,
+    projectId: String,
+    relativeRoot: String,
+    respectGitignore: Option<bool>,
+    exclude: Option<Vec<String>>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    dirTypeId: Option<String>,
+) -> Result<FileNode, AppError> {
+    let relativeRoot = match dirTypeId {
+        Some(dir_type_id) => {
+            let mapped = with_db!(workspace, conn, {
+                mapped_dir_relative_path_with_conn(conn, &projectId, &dir_type_id)
+            })?;
+            join_relative(&mapped, &relativeRoot)
+        }
+        None => relativeRoot,
+    };
+    let project = project_get(workspace.clone(), projectId)?;
+
+    let root_path = Path::new(&project.project_path);
+    let target_path = if relativeRoot.is_empty() {
+        root_path.to_path_buf()
+    } else {
+        root_path.join(&relativeRoot)
+    };
+
+    if !target_path.exists() {
+        return Err(AppError::NotFound("目录不存在".into()));
+    }
+
+    let rules = IgnoreRules::load(
+        root_path,
+        respectGitignore.unwrap_or(true),
+        &exclude.unwrap_or_default(),
+    );
+    let page = TreePage {
+        offset: offset.unwrap_or(0),
+        limit: limit.unwrap_or(usize::MAX),
+    };
+
+    build_tree(&target_path, &relativeRoot, &rules, Some(page))
+        .ok_or_else(|| AppError::Io("读取目录信息失败".into()))
+}
+
+/// 项目中某个目录类型映射的相对路径
+fn mapped_dir_relative_path_with_conn(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+    dir_type_id: &str,
+) -> Result<String, AppError> {
+    conn.query_row(
+        "SELECT relative_path FROM project_directories WHERE project_id = ?1 AND dir_type_id = ?2",
+        rusqlite::params![project_id, dir_type_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| AppError::lookup(&e, format!("目录类型映射不存在: {}", dir_type_id)))
+}
+
+/// 拼接两段相对路径，忽略空段和多余的分隔符
+fn join_relative(base: &str, rest: &str) -> String {
+    [base, rest]
+        .iter()
+        .map(|part| part.trim_matches(|c| c == '/' || c == '\\'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 单层目录的分页参数
+#[derive(Clone, Copy)]
+struct TreePage {
+    offset: usize,
+    limit: usize,
+}
+
+/// 目录条目：(名称, 是否目录, 路径)
+type TreeEntry = (String, bool, PathBuf);
+
+/// 目录优先，其次按名称（不区分大小写）排序
+fn sort_tree_entries(entries: &mut [TreeEntry]) {
+    entries.sort_by(|a, b| {
+        b.1.cmp(&a.1)
+            .then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase()))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+}
+
+/// 构建文件树；单个条目读取元数据失败时跳过该条目，而不是让整棵树失败
+fn build_tree(
+    path: &Path,
+    relative_path: &str,
+    rules: &IgnoreRules,
+    page: Option<TreePage>,
+) -> Option<FileNode> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "root".to_string());
+
+    let metadata = fs::metadata(path).ok()?;
+    let modified_at = metadata
+        .modified()
+        .ok()
+        .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339());
+
+    if metadata.is_dir() {
+        let mut entries: Vec<TreeEntry> = fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| {
+                        let is_dir = e.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                        (
+                            e.file_name().to_string_lossy().to_string(),
+                            is_dir,
+                            e.path(),
+                        )
+                    })
+                    .filter(|(name, is_dir, _)| {
+                        !rules.is_ignored(&format!("{}/{}", relative_path, name), *is_dir)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        sort_tree_entries(&mut entries);
+
+        let total_children = entries.len();
+        let page = page.unwrap_or(TreePage {
+            offset: 0,
+            limit: usize::MAX,
+        });
+        let children: Vec<FileNode> = entries
+            .into_iter()
+            .skip(page.offset)
+            .take(page.limit)
+            .filter_map(|(name, _, child_path)| {
+                build_tree(
+                    &child_path,
+                    &format!("{}/{}", relative_path, name),
+                    rules,
+                    None,
+                )
+            })
+            .collect();
+
+        Some(FileNode {
+            path: relative_path.to_string(),
+            name,
+            kind: "dir".to_string(),
+            children: Some(children),
+            size: None,
+            modified_at,
+            total_children: Some(total_children),
+        })
+    } else {
+        Some(FileNode {
+            path: relative_path.to_string(),
+            name,
+            kind: "file".to_string(),
+            children: None,
+            size: Some(metadata.len()),
+            modified_at,
+            total_children: None,
+        })
+    }
+}
+
+/// 内容搜索的匹配条数上限
+const SEARCH_MAX_MATCHES: usize = 500;
+/// 内容搜索的命中文件数上限
+const SEARCH_MAX_FILES: usize = 200;
+/// 超过该大小的文件不参与内容搜索
+const SEARCH_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+/// 行预览的最大字符数
+const SEARCH_PREVIEW_CHARS: usize = 200;
+
+/// 在项目文件中搜索文本（遵循忽略规则，跳过二进制文件）
+#[tauri::command]
+pub fn fs_search(
+    workspace: Workspace,
+    project_id: String,
+    query: String,
+    case_sensitive: bool,
+) -> Result<FsSearchResult, AppError> {
+    if query.is_empty() {
+        return Err(AppError::Validation("搜索内容不能为空".into()));
+    }
+    let project = project_get(workspace.clone(), project_id)?;
+    Ok(search_in_dir(
+        Path::new(&project.project_path),
+        &query,
+        case_sensitive,
+    ))
+}
+
+fn search_in_dir(root: &Path, query: &str, case_sensitive: bool) -> FsSearchResult {
+    let rules = IgnoreRules::load(root, true, &[]);
+    let needle = if case_sensitive {
+        query.to_string()
+    } else {
+        query.to_lowercase()
+    };
+
+    let mut matches = Vec::new();
+    let mut matched_files = 0;
+    let mut truncated = false;
+
+    let walker = walkdir::WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !rules.is_ignored(
+                    &relative_slash_path(root, entry.path()),
+                    entry.file_type().is_dir(),
+                )
+        });
+
+    'files: for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file()
+            || entry
+                .metadata()
+                .map(|m| m.len() > SEARCH_MAX_FILE_SIZE)
+                .unwrap_or(true)
+        {
+            continue;
+        }
+        let bytes = match fs::read(entry.path()) {
+            Ok(bytes) => bytes,
+            Err(_) => continue,
+        };
+        if is_binary(&bytes) {
+            continue;
+        }
+
+        let relative = relative_slash_path(root, entry.path());
+        let content = String::from_utf8_lossy(&bytes);
+        let mut file_matched = false;
+
+        for (index, line) in content.lines().enumerate() {
+            let hit = if case_sensitive {
+                line.contains(&needle)
+            } else {
+                line.to_lowercase().contains(&needle)
+            };
+            if !hit {
+                continue;
+            }
+
+            if !file_matched {
+                if matched_files == SEARCH_MAX_FILES {
+                    truncated = true;
+                    break 'files;
+                }
+                matched_files += 1;
+                file_matched = true;
+            }
+            if matches.len() == SEARCH_MAX_MATCHES {
+                truncated = true;
+                break 'files;
+            }
+            matches.push(FsSearchMatch {
+                path: relative.clone(),
+                line_number: index + 1,
+                line_preview: line.trim().chars().take(SEARCH_PREVIEW_CHARS).collect(),
+            });
+        }
+    }
+
+    FsSearchResult { matches, truncated }
+}
+
+/// 通过检查前 8KB 是否包含 NUL 字节判断是否为二进制内容
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8192).any(|b| *b == 0)
+}
+
+/// 相对根目录、以 `/` 分隔的路径
+fn relative_slash_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// 文本读取的大小上限，超出部分截断
+const READ_TEXT_MAX_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 读取文本文件内容
+/// 超过 5MB 的文件只返回前 5MB 并标记 `truncated`；二进制文件返回空内容和 `binary: true`；
+/// 非 UTF-8 文本按有损方式解码并标记 `lossy`
+#[tauri::command]
+pub fn fs_read_text(path: String) -> Result<serde_json::Value, AppError> {
+    use std::io::Read;
+
+    let normalized = normalize_path(&path);
+    let file = fs::File::open(&normalized)
+        .map_err(|e| AppError::io(&e, format!("读取文件失败: {}", e)))?;
+    let size = file
+        .metadata()
+        .map_err(|e| AppError::io(&e, format!("读取文件失败: {}", e)))?
+        .len();
+
+    let mut bytes = Vec::new();
+    file.take(READ_TEXT_MAX_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| AppError::io(&e, format!("读取文件失败: {}", e)))?;
+    let truncated = size > READ_TEXT_MAX_BYTES;
+
+    if is_binary(&bytes) {
+        return Ok(serde_json::json!({ "content": "", "binary": true, "size": size }));
+    }
+
+    let (content, lossy) = decode_text(bytes, truncated);
+    Ok(serde_json::json!({
+        "content": content,
+        "binary": false,
+        "truncated": truncated,
+        "lossy": lossy,
+        "size": size
+    }))
+}
+
+/// 解码文本；截断时末尾被切断的多字节字符直接丢弃，不算作有损
+fn decode_text(mut bytes: Vec<u8>, truncated: bool) -> (String, bool) {
+    if truncated {
+        if let Err(e) = std::str::from_utf8(&bytes) {
+            if e.error_len().is_none() {
+                bytes.truncate(e.valid_up_to());
+            }
+        }
+    }
+    match String::from_utf8(bytes) {
+        Ok(content) => (content, false),
+        Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), true),
+    }
+}
+
+/// 写入文本文件（先写同目录临时文件再重命名覆盖，避免写入中断导致文件被截断）
+#[tauri::command]
+pub fn fs_write_text(path: String, content: String) -> Result<serde_json::Value, AppError> {
+    use std::io::Write;
+
+    let normalized = normalize_path(&path);
+    let target = Path::new(&normalized);
+    let parent = target
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let file_name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| AppError::Validation("无效的文件路径".to_string()))?;
+
+    fs::create_dir_all(parent).map_err(|e| AppError::io(&e, format!("创建父目录失败: {}", e)))?;
+
+    let temp_path = parent.join(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+    let write_temp = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    };
+
+    if let Err(e) = write_temp().and_then(|_| fs::rename(&temp_path, target)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(AppError::Io(format!("写入文件失败: {}", e)));
+    }
+
+    Ok(serde_json::json!({ "ok": true, "bytes": content.len() }))
+}
+
+/// 创建目录
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn fs_create_dir(
+    workspace: Workspace,
+    projectId: String,
+    relativePath: String,
+) -> Result<serde_json::Value, AppError> {
+    let project = project_get(workspace.clone(), projectId)?;
+    let target_path = Path::new(&project.project_path).join(&relativePath);
+
+    fs::create_dir_all(&target_path)
+        .map_err(|e| AppError::io(&e, format!("创建目录失败: {}", e)))?;
+
+    Ok(serde_json::json!({ "ok": true, "path": target_path.to_string_lossy().to_string() }))
+}
+
+/// 将文件或目录移入系统回收站
+pub(crate) fn move_to_trash(path: &Path) -> Result<(), AppError> {
+    trash::delete(path).map_err(|e| AppError::Io(format!("移入回收站失败: {}", e)))
+}
+
+/// 删除文件或目录
+/// `to_trash` 默认开启：移入系统回收站，失败时返回错误而不会改为永久删除；
+/// 仅在显式传入 `to_trash: false` 时永久删除。结果中的 `mode` 标明实际方式
+#[tauri::command]
+pub fn fs_delete(path: String, to_trash: Option<bool>) -> Result<serde_json::Value, AppError> {
+    let normalized = normalize_path(&path);
+    let target_path = Path::new(&normalized);
+
+    if !target_path.exists() {
+        return Err(AppError::NotFound("文件不存在".into()));
+    }
+
+    if to_trash.unwrap_or(true) {
+        move_to_trash(target_path)?;
+        return Ok(serde_json::json!({ "ok": true, "mode": "trash" }));
+    }
+
+    if target_path.is_dir() {
+        fs::remove_dir_all(&target_path).map_err(|e| AppError::io(&e, format!("删除目录失败: {}", e)))?;
+    } else {
+        fs::remove_file(&target_path).map_err(|e| AppError::io(&e, format!("删除文件失败: {}", e)))?;
+    }
+
+    Ok(serde_json::json!({ "ok": true, "mode": "permanent" }))
+}
+
+/// 重命名文件或目录
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn fs_rename(oldPath: String, newName: String) -> Result<serde_json::Value, AppError> {
+    let normalized_old = normalize_path(&oldPath);
+    let old = Path::new(&normalized_old);
+    let new = old
+        .parent()
+        .map(|p| p.join(&newName))
+        .ok_or_else(|| AppError::Validation("无法确定新路径".to_string()))?;
+
+    fs::rename(&old, &new).map_err(|e| AppError::io(&e, format!("重命名失败: {}", e)))?;
+
+    Ok(serde_json::json!({ "ok": true, "newPath": new.to_string_lossy().to_string() }))
+}
+
+/// 在项目内移动文件或目录（可跨目录，自动创建中间目录）
+#[tauri::command]
+pub fn fs_move(
+    workspace: Workspace,
+    project_id: String,
+    from_relative: String,
+    to_relative: String,
+    overwrite: Option<bool>,
+) -> Result<serde_json::Value, AppError> {
+    let project = project_get(workspace.clone(), project_id)?;
+    let root = Path::new(&project.project_path);
+    let from = resolve_in_project(root, &from_relative)?;
+    let to = resolve_in_project(root, &to_relative)?;
+    move_path(&from, &to, overwrite.unwrap_or(false))?;
+
+    Ok(serde_json::json!({ "ok": true, "path": to.to_string_lossy().to_string() }))
+}
+
+fn move_path(from: &Path, to: &Path, overwrite: bool) -> Result<(), AppError> {
+    if !from.exists() {
+        return Err(AppError::NotFound("源路径不存在".to_string()));
+    }
+    if from == to {
+        return Ok(());
+    }
+    if to.starts_with(from) {
+        return Err(AppError::Validation("不能将目录移动到其自身的子目录中".to_string()));
+    }
+    // 目标是源的上级目录时，覆盖前的删除会连同源一起删掉
+    if from.starts_with(to) {
+        return Err(AppError::Validation("不能将路径移动到其上级目录".to_string()));
+    }
+    if to.exists() {
+        if !overwrite {
+            return Err(AppError::Validation("目标已存在".to_string()));
+        }
+        remove_existing(to)?;
+    }
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(&e, format!("创建父目录失败: {}", e)))?;
+    }
+    fs::rename(from, to).map_err(|e| AppError::io(&e, format!("移动失败: {}", e)))
+}
+
+/// 在项目内复制文件或目录（目录递归复制），返回目标的相对路径
+#[tauri::command]
+pub fn fs_copy(
+    workspace: Workspace,
+    project_id: String,
+    from_relative: String,
+    to_relative: String,
+    overwrite: Option<bool>,
+) -> Result<serde_json::Value, AppError> {
+    let project = project_get(workspace.clone(), project_id)?;
+    let root = Path::new(&project.project_path);
+    let from = resolve_in_project(root, &from_relative)?;
+    let to = resolve_in_project(root, &to_relative)?;
+    copy_path(&from, &to, overwrite.unwrap_or(false))?;
+
+    let relative_path = to
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or(to_relative);
+    Ok(serde_json::json!({ "ok": true, "relativePath": relative_path }))
+}
+
+fn copy_path(from: &Path, to: &Path, overwrite: bool) -> Result<(), AppError> {
+    if !from.exists() {
+        return Err(AppError::NotFound("源路径不存在".into()));
+    }
+    if from.is_dir() && to.starts_with(from) {
+        return Err(AppError::Validation("不能将目录复制到其自身的子目录中".into()));
+    }
+    // 目标是源本身或其上级目录时，覆盖前的删除会连同源一起删掉
+    if from.starts_with(to) {
+        return Err(AppError::Validation("不能用源路径覆盖其自身或上级目录".into()));
+    }
+    if to.exists() {
+        if !overwrite {
+            return Err(AppError::Validation("目标已存在".into()));
+        }
+        remove_existing(to)?;
+    }
+
+    copy_recursive(from, to)
+}
+
+/// 递归复制文件或目录（不保留权限等元数据）
+fn copy_recursive(from: &Path, to: &Path) -> Result<(), AppError> {
+    if from.is_dir() {
+        fs::create_dir_all(to).map_err(|e| AppError::io(&e, format!("创建目录失败: {}", e)))?;
+        for entry in fs::read_dir(from).map_err(|e| AppError::io(&e, format!("读取目录失败: {}", e)))? {
+            let entry = entry.map_err(|e| AppError::io(&e, format!("读取目录失败: {}", e)))?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::io(&e, format!("创建父目录失败: {}", e)))?;
+        }
+        fs::copy(from, to).map_err(|e| AppError::io(&e, format!("复制文件失败: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// 创建文件
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn fs_create_file(
+    workspace: Workspace,
+    projectId: String,
+    relativePath: String,
+) -> Result<serde_json::Value, AppError> {
+    let project = project_get(workspace.clone(), projectId)?;
+    let target_path = resolve_in_project(Path::new(&project.project_path), &relativePath)?;
+
+    // 确保父目录存在
+    if let Some(parent) = target_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(&e, format!("创建父目录失败: {}", e)))?;
+    }
+
+    // 创建空文件（已存在时报错，避免覆盖已有内容）
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&target_path)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => AppError::Validation("文件已存在".to_string()),
+            _ => AppError::io(&e, format!("创建文件失败: {}", e)),
+        })?;
+
+    Ok(serde_json::json!({ "ok": true, "path": target_path.to_string_lossy().to_string() }))
+}
+
+/// 使用系统默认程序打开文件或文件夹
+#[tauri::command]
+pub fn fs_open_external(path: String) -> Result<serde_json::Value, AppError> {
+    let normalized = normalize_path(&path);
+
+    #[cfg(windows)]
+    {
+        use std::ffi::OsStr;
+        use std::os::windows::ffi::OsStrExt;
+        use std::ptr;
+
+        // Check if the path exists first
+        let path_check = Path::new(&normalized);
+        if !path_check.exists() {
+            return Err(AppError::NotFound(format!("文件不存在: {}", normalized)));
+        }
+
+        // Convert UTF-8 path to UTF-16 wide string
+        let wide_path: Vec<u16> = OsStr::new(&normalized)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+
+        // ShellExecuteW returns HINSTANCE as isize, > 32 means success
+        const SW_SHOWNORMAL: i32 = 1;
+
+        extern "system" {
+            fn ShellExecuteW(
+                hwnd: *mut std::ffi::c_void,
+                lpOperation: *const u16,
+                lpFile: *const u16,
+                lpParameters: *const u16,
+                lpDirectory: *const u16,
+                nShowCmd: i32,
+            ) -> isize;
+        }
+
+        let result = unsafe {
+            ShellExecuteW(
+                ptr::null_mut(),
+                ptr::null(),
+                wide_path.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+                SW_SHOWNORMAL,
+            )
+        };
+
+        if result as usize > 32 {
+            Ok(serde_json::json!({ "ok": true }))
+        } else {
+            let error_msg = match result as i32 {
+                0 => "内存不足或shell扩展未能加载",
+                2 => "找不到指定的文件",
+                3 => "找不到指定的路径",
+                5 => "试图动态链接操作失败",
+                8 => "内存不足",
+                26 => "共享错误",
+                27 => "文件名不完整或无效",
+                28 => "超时",
+                29 => "DDE事务失败",
+                30 => "DDE事务无法完成",
+                31 => "没有文件关联",
+                32 => "DLL未找到",
+                _ => "未知错误",
+            };
+            Err(AppError::Io(format!("打开失败: {} (code: {})", error_msg, result)))
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        open::that(&normalized).map_err(|e| AppError::io(&e, format!("打开失败: {}", e)))?;
+        Ok(serde_json::json!({ "ok": true }))
+    }
+}
+
+/// 复制文件到目标路径
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn fs_copy_file(
+    sourcePath: String,
+    targetPath: String,
+    overwrite: bool,
+) -> Result<serde_json::Value, AppError> {
+    let source_normalized = normalize_path(&sourcePath);
+    let target_normalized = normalize_path(&targetPath);
+    let source = Path::new(&source_normalized);
+    let target = Path::new(&target_normalized);
+
+    if !source.exists() {
+        return Err(AppError::NotFound("源文件不存在".into()));
+    }
+
+    // 如果不允许覆盖且目标已存在
+    if !overwrite && target.exists() {
+        return Err(AppError::Validation("目标文件已存在".into()));
+    }
+
+    // 确保父目录存在
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(&e, format!("创建父目录失败: {}", e)))?;
+    }
+
+    fs::copy(source, target).map_err(|e| AppError::io(&e, format!("复制文件失败: {}", e)))?;
+
+    Ok(serde_json::json!({ "ok": true, "path": target.to_string_lossy().to_string() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mapped_dir_relative_path() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::db::schema::SCHEMA).unwrap();
+        conn.execute(
+            "INSERT INTO project_directories (id, project_id, dir_type_id, relative_path, created_at, updated_at)
+             VALUES ('d1', 'p1', 't-docs', 'docs/', 'now', 'now')",
+            [],
+        )
+        .unwrap();
+
+        let mapped = mapped_dir_relative_path_with_conn(&conn, "p1", "t-docs").unwrap();
+        assert_eq!(join_relative(&mapped, ""), "docs");
+        assert_eq!(join_relative(&mapped, "guides/"), "docs/guides");
+        assert!(mapped_dir_relative_path_with_conn(&conn, "p1", "t-code").is_err());
+    }
+
+    #[test]
+    fn test_fs_create_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path().to_str().unwrap();
+
+        // Create a mock project file for testing
+        let db_path = temp_dir.path().join("test.db");
+        fs::write(&db_path, "").unwrap();
+
+        // Note: This test requires a real project in the database
+        // In a full test suite, we would set up a test database
+        // For now, we test the path construction logic
+        let target_path = Path::new(project_path).join("test.txt");
+        fs::write(&target_path, "").unwrap();
+
+        assert!(target_path.exists());
+        fs::remove_file(&target_path).unwrap();
+    }
+
+    #[test]
+    fn test_fs_copy_file_no_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.txt");
+        let target = temp_dir.path().join("target.txt");
+
+        fs::write(&source, "content").unwrap();
+        fs::write(&target, "existing").unwrap();
+
+        let result = fs_copy_file(
+            source.to_string_lossy().to_string(),
+            target.to_string_lossy().to_string(),
+            false,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err(),
+            AppError::Validation("目标文件已存在".into())
+        );
+    }
+
+    #[test]
+    fn test_fs_copy_file_with_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.txt");
+        let target = temp_dir.path().join("target.txt");
+
+        fs::write(&source, "new content").unwrap();
+        fs::write(&target, "old content").unwrap();
+
+        let result = fs_copy_file(
+            source.to_string_lossy().to_string(),
+            target.to_string_lossy().to_string(),
+            true,
+        );
+
+        assert!(result.is_ok());
+        let content = fs::read_to_string(&target).unwrap();
+        assert_eq!(content, "new content");
+    }
+
+    #[test]
+    fn test_fs_copy_file_source_not_exists() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("nonexistent.txt");
+        let target = temp_dir.path().join("target.txt");
+
+        let result = fs_copy_file(
+            source.to_string_lossy().to_string(),
+            target.to_string_lossy().to_string(),
+            false,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err(),
+            AppError::NotFound("源文件不存在".into())
+        );
+    }
+
+    #[test]
+    fn test_fs_copy_file_creates_parent_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source.txt");
+        let target = temp_dir.path().join("nested/dirs/target.txt");
+
+        fs::write(&source, "content").unwrap();
+
+        let result = fs_copy_file(
+            source.to_string_lossy().to_string(),
+            target.to_string_lossy().to_string(),
+            false,
+        );
+
+        assert!(result.is_ok());
+        assert!(target.exists());
+    }
+
+    #[test]
+    fn test_fs_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_path = temp_dir.path().join("old_name.txt");
+        let new_name = "new_name.txt";
+
+        fs::write(&old_path, "content").unwrap();
+
+        let result = fs_rename(old_path.to_string_lossy().to_string(), new_name.to_string());
+
+        assert!(result.is_ok());
+        assert!(!old_path.exists());
+        assert!(temp_dir.path().join(new_name).exists());
+    }
+
+    #[test]
+    fn test_fs_delete_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("to_delete.txt");
+
+        fs::write(&file_path, "content").unwrap();
+        assert!(file_path.exists());
+
+        let result = fs_delete(file_path.to_string_lossy().to_string(), Some(false));
+
+        assert_eq!(result.unwrap()["mode"], "permanent");
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_fs_delete_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path().join("to_delete");
+
+        fs::create_dir(&dir_path).unwrap();
+        fs::write(dir_path.join("file.txt"), "content").unwrap();
+        assert!(dir_path.exists());
+
+        let result = fs_delete(dir_path.to_string_lossy().to_string(), Some(false));
+
+        assert!(result.is_ok());
+        assert!(!dir_path.exists());
+    }
+
+    #[test]
+    fn test_fs_read_text() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        let content = "Hello, World!";
+
+        fs::write(&file_path, content).unwrap();
+
+        let result = fs_read_text(file_path.to_string_lossy().to_string());
+
+        assert!(result.is_ok());
+        let json = result.unwrap();
+        assert_eq!(json["content"], content);
+    }
+
+    #[test]
+    fn test_fs_read_bytes_truncates_to_max_bytes() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("icon.bin");
+        fs::write(&file_path, [0u8, 1, 2, 3, 4, 5]).unwrap();
+        let path = file_path.to_string_lossy().to_string();
+
+        let json = fs_read_bytes(path.clone(), 4).unwrap();
+        assert_eq!(json["data"], "AAECAw==");
+        assert_eq!(json["truncated"], true);
+        assert_eq!(json["size"], 6);
+
+        let json = fs_read_bytes(path, 64).unwrap();
+        assert_eq!(json["data"], "AAECAwQF");
+        assert_eq!(json["truncated"], false);
+    }
+
+    #[test]
+    fn test_fs_write_text_creates_and_overwrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("notes/todo.md");
+
+        let result = fs_write_text(file_path.to_string_lossy().to_string(), "first".to_string());
+        assert_eq!(result.unwrap()["bytes"], 5);
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "first");
+
+        fs_write_text(file_path.to_string_lossy().to_string(), "second draft".to_string())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "second draft");
+
+        // 临时文件不应残留
+        let entries = fs::read_dir(file_path.parent().unwrap()).unwrap().count();
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn test_resolve_in_project_rejects_traversal() {
+        let root = Path::new("/projects/alpha");
+        assert_eq!(
+            resolve_in_project(root, "src/./main.rs").unwrap(),
+            root.join("src").join("main.rs")
+        );
+        assert!(resolve_in_project(root, "../beta/secret").is_err());
+        assert!(resolve_in_project(root, "src/../../beta").is_err());
+        assert!(resolve_in_project(root, "/etc/passwd").is_err());
+        assert!(resolve_in_project(root, "").is_err());
+    }
+
+    #[test]
+    fn test_move_path_creates_parents_and_respects_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("a.txt");
+        let to = temp_dir.path().join("nested/dir/a.txt");
+        fs::write(&from, "content").unwrap();
+
+        move_path(&from, &to, false).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "content");
+
+        fs::write(&from, "newer").unwrap();
+        assert_eq!(
+            move_path(&from, &to, false).unwrap_err(),
+            AppError::Validation("目标已存在".into())
+        );
+        move_path(&from, &to, true).unwrap();
+        assert_eq!(fs::read_to_string(&to).unwrap(), "newer");
+
+        let dir = temp_dir.path().join("nested");
+        assert!(move_path(&dir, &dir.join("dir/inner"), false).is_err());
+
+        assert!(move_path(&to, &dir, true).is_err());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "newer");
+    }
+
+    #[test]
+    fn test_copy_recursive_copies_nested_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("src");
+        fs::create_dir_all(from.join("inner")).unwrap();
+        fs::write(from.join("a.txt"), "a").unwrap();
+        fs::write(from.join("inner/b.txt"), "b").unwrap();
+
+        let to = temp_dir.path().join("copy/src");
+        copy_recursive(&from, &to).unwrap();
+
+        assert_eq!(fs::read_to_string(to.join("a.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(to.join("inner/b.txt")).unwrap(), "b");
+        assert!(from.join("inner/b.txt").exists());
+    }
+
+    #[test]
+    fn test_copy_path_rejects_overwriting_source_itself() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.txt");
+        fs::write(&file, "content").unwrap();
+
+        assert!(copy_path(&file, &file, true).is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "content");
+    }
+
+    #[test]
+    fn test_copy_path_rejects_overwriting_ancestor_of_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let parent = temp_dir.path().join("parent");
+        let file = parent.join("inner/a.txt");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, "content").unwrap();
+
+        assert!(copy_path(&file, &parent, true).is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "content");
+    }
+
+    #[test]
+    fn test_search_in_dir_skips_binary_and_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    println!(\"Hello\");\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("node_modules/pkg/index.js"), "hello").unwrap();
+        fs::write(root.join("data.bin"), b"hello\0world").unwrap();
+
+        let result = search_in_dir(root, "hello", false);
+        assert!(!result.truncated);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].path, "src/main.rs");
+        assert_eq!(result.matches[0].line_number, 2);
+        assert_eq!(result.matches[0].line_preview, "println!(\"Hello\");");
+
+        assert!(search_in_dir(root, "hello", true).matches.is_empty());
+    }
+
+    #[test]
+    fn test_fs_read_text_binary_and_lossy() {
+        let temp_dir = TempDir::new().unwrap();
+        let binary = temp_dir.path().join("image.bin");
+        let latin1 = temp_dir.path().join("latin1.txt");
+        fs::write(&binary, b"\x89PNG\0\0").unwrap();
+        fs::write(&latin1, b"caf\xe9").unwrap();
+
+        let json = fs_read_text(binary.to_string_lossy().to_string()).unwrap();
+        assert_eq!(json["binary"], true);
+        assert_eq!(json["content"], "");
+
+        let json = fs_read_text(latin1.to_string_lossy().to_string()).unwrap();
+        assert_eq!(json["lossy"], true);
+        assert_eq!(json["content"], "caf\u{fffd}");
+    }
+
+    #[test]
+    fn test_decode_text_drops_split_char_when_truncated() {
+        let bytes = "你好".as_bytes()[..4].to_vec();
+        assert_eq!(decode_text(bytes.clone(), true), ("你".to_string(), false));
+        assert!(decode_text(bytes, false).1);
+    }
+
+    #[test]
+    fn test_build_tree_sorts_and_paginates() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("b.txt"), "").unwrap();
+        fs::write(root.join("A.txt"), "").unwrap();
+        fs::create_dir(root.join("zeta")).unwrap();
+        fs::create_dir(root.join("Alpha")).unwrap();
+
+        let rules = IgnoreRules::load(root, true, &[]);
+        let tree = build_tree(root, "", &rules, None).unwrap();
+        let names: Vec<&str> = tree
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Alpha", "zeta", "A.txt", "b.txt"]);
+
+        let page = TreePage {
+            offset: 1,
+            limit: 2,
+        };
+        let tree = build_tree(root, "", &rules, Some(page)).unwrap();
+        let names: Vec<&str> = tree
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["zeta", "A.txt"]);
+        assert_eq!(tree.total_children, Some(4));
+    }
+
+    #[test]
+    fn test_fs_create_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        // Note: fs_create_dir requires a valid project_id to look up project path
+        // This tests the underlying directory creation logic
+        let dir_path = temp_dir.path().join("new_dir");
+
+        fs::create_dir_all(&dir_path).unwrap();
+        assert!(dir_path.exists());
+
+        fs::remove_dir(&dir_path).unwrap();
+    }
+}
+
+/// 读取二进制文件（用于文档预览）
+#[tauri::command]
+pub fn fs_read_binary(path: String) -> Result<serde_json::Value, AppError> {
+    let bytes = fs::read(&path).map_err(|e| AppError::io(&e, format!("读取文件失败: {}", e)))?;
+    // 将字节转换为 Base64 编码
+    let base64 = base64_encode(&bytes);
+    Ok(serde_json::json!({ "data": base64 }))
+}
+
+/// fs_read_bytes 单次返回的字节上限，避免向前端传输过大的内容
+const READ_BYTES_LIMIT: u64 = 5 * 1024 * 1024;
+
+/// 读取文件开头最多 `max_bytes` 字节并以 Base64 返回（用于字体、图标等二进制预览）
+/// 文件超出读取长度时标记 `truncated`，`size` 为文件实际大小
+#[tauri::command]
+pub fn fs_read_bytes(path: String, max_bytes: u64) -> Result<serde_json::Value, AppError> {
+    use std::io::Read;
+
+    let normalized = normalize_path(&path);
+    let file = fs::File::open(&normalized)
+        .map_err(|e| AppError::io(&e, format!("读取文件失败: {}", e)))?;
+    let size = file
+        .metadata()
+        .map_err(|e| AppError::io(&e, format!("读取文件失败: {}", e)))?
+        .len();
+
+    let limit = max_bytes.min(READ_BYTES_LIMIT);
+    let mut bytes = Vec::new();
+    file.take(limit)
+        .read_to_end(&mut bytes)
+        .map_err(|e| AppError::io(&e, format!("读取文件失败: {}", e)))?;
+
+    Ok(serde_json::json!({
+        "data": base64_encode(&bytes),
+        "truncated": size > limit,
+        "size": size
+    }))
+}
+
+/// Base64 编码函数
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::new();
+
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as usize;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as usize;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as usize;
+
+        result.push(ALPHABET[b0 >> 2] as char);
+        result.push(ALPHABET[((b0 & 0x03) << 4) | (b1 >> 4)] as char);
+
+        if chunk.len() > 1 {
+            result.push(ALPHABET[((b1 & 0x0f) << 2) | (b2 >> 6)] as char);
+        } else {
+            result.push('=');
+        }
+
+        if chunk.len() > 2 {
+            result.push(ALPHABET[b2 & 0x3f] as char);
+        } else {
+            result.push('=');
+        }
+    }
+
+    result
+}
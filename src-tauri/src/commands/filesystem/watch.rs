@@ -0,0 +1,187 @@
+//! 项目文件变化监听：基于 notify 递归监听项目目录，去抖后发送 fs://changed 事件
+
+use super::ignore::IgnoreRules;
+use super::relative_slash_path;
+use crate::commands::project::project_get;
+use crate::commands::workspace::Workspace;
+use crate::error::AppError;
+use notify::{RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// 合并连续变化的去抖时长（编辑器保存时常会先写临时文件再重命名）
+const FS_WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+struct FsWatcher {
+    stop_signal: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<()>,
+    /// 监听的项目所属的工作区，关闭工作区时据此停止
+    workspace_path: String,
+}
+
+/// 正在运行的文件监听线程（按 project_id 索引）
+static FS_WATCHERS: once_cell::sync::Lazy<Mutex<HashMap<String, FsWatcher>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 文件监听（启动）：项目目录下的文件变化去抖后以 fs://changed 事件发送受影响的相对路径
+#[tauri::command]
+pub fn fs_watch_start(
+    workspace: Workspace,
+    app_handle: AppHandle,
+    project_id: String,
+) -> Result<serde_json::Value, AppError> {
+    let project = project_get(workspace.clone(), project_id.clone())?;
+    let root = PathBuf::from(&project.project_path);
+    if !root.is_dir() {
+        return Err(AppError::NotFound("项目目录不存在".into()));
+    }
+
+    let mut watchers = FS_WATCHERS
+        .lock()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if let Some(watcher) = watchers.get(&project_id) {
+        if !watcher.handle.is_finished() {
+            return Ok(serde_json::json!({ "ok": true, "started": false }));
+        }
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| AppError::Io(format!("文件监视器创建失败: {}", e)))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| AppError::Io(format!("开始监视失败: {}", e)))?;
+
+    let stop_signal = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop_signal.clone();
+    let thread_project_id = project_id.clone();
+
+    let handle = std::thread::spawn(move || {
+        // watcher 随线程结束释放
+        let _watcher = watcher;
+        let rules = IgnoreRules::load(&root, true, &[]);
+        let mut pending: BTreeSet<String> = BTreeSet::new();
+        let mut last_event = Instant::now();
+
+        while !thread_stop.load(Ordering::Relaxed) {
+            match rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(Ok(event)) => {
+                    for path in event.paths {
+                        let relative = relative_slash_path(&root, &path);
+                        if relative.is_empty() || rules.is_ignored(&relative, path.is_dir()) {
+                            continue;
+                        }
+                        // 路径中任一上级目录被忽略时同样跳过（如 node_modules 内部的变化）
+                        if ancestors_ignored(&rules, &relative) {
+                            continue;
+                        }
+                        pending.insert(relative);
+                        last_event = Instant::now();
+                    }
+                }
+                Ok(Err(_)) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            if !pending.is_empty() && last_event.elapsed() >= FS_WATCH_DEBOUNCE {
+                let paths: Vec<String> = std::mem::take(&mut pending).into_iter().collect();
+                let _ = app_handle.emit(
+                    "fs://changed",
+                    serde_json::json!({ "projectId": thread_project_id, "paths": paths }),
+                );
+            }
+        }
+    });
+
+    watchers.insert(
+        project_id,
+        FsWatcher {
+            stop_signal,
+            handle,
+            workspace_path: workspace.path().to_string(),
+        },
+    );
+
+    Ok(serde_json::json!({ "ok": true, "started": true }))
+}
+
+/// 文件监听（停止）
+#[tauri::command]
+pub fn fs_watch_stop(project_id: String) -> Result<serde_json::Value, AppError> {
+    let stopped: Vec<FsWatcher> = {
+        let mut watchers = FS_WATCHERS
+            .lock()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        watchers.remove(&project_id).into_iter().collect()
+    };
+
+    let count = stopped.len();
+    join_fs_watchers(stopped);
+
+    Ok(serde_json::json!({ "ok": true, "stopped": count }))
+}
+
+/// 停止属于指定工作区的全部文件监听线程
+pub fn stop_fs_watchers_for_workspace(workspace_path: &str) {
+    let stopped: Vec<FsWatcher> = {
+        let mut watchers = match FS_WATCHERS.lock() {
+            Ok(watchers) => watchers,
+            Err(_) => return,
+        };
+        let ids: Vec<String> = watchers
+            .iter()
+            .filter(|(_, w)| w.workspace_path == workspace_path)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.iter().filter_map(|id| watchers.remove(id)).collect()
+    };
+    join_fs_watchers(stopped);
+}
+
+/// 通知监听线程退出并等待其结束
+fn join_fs_watchers(watchers: Vec<FsWatcher>) {
+    for watcher in &watchers {
+        watcher.stop_signal.store(true, Ordering::Relaxed);
+    }
+    for watcher in watchers {
+        let _ = watcher.handle.join();
+    }
+}
+
+/// 判断相对路径的任一上级目录是否被忽略
+fn ancestors_ignored(rules: &IgnoreRules, relative: &str) -> bool {
+    let mut prefix = String::new();
+    let mut parts = relative.split('/').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            break;
+        }
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(part);
+        if rules.is_ignored(&prefix, true) {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_ancestors_ignored() {
+        let rules = IgnoreRules::load(Path::new("/nonexistent"), true, &[]);
+        assert!(ancestors_ignored(&rules, "node_modules/pkg/index.js"));
+        assert!(ancestors_ignored(&rules, ".git/index"));
+        assert!(!ancestors_ignored(&rules, "src/main.rs"));
+        assert!(!ancestors_ignored(&rules, "README.md"));
+    }
+}
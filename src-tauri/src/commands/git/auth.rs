@@ -0,0 +1,303 @@
+//! Git 远程认证
+//! SSH 远程依次尝试 ssh-agent 和工作区配置的私钥，HTTPS 远程使用按主机保存的访问令牌
+//! （令牌优先保存在系统钥匙串中，钥匙串不可用时才存入工作区数据库）；
+//! 以上都不可用时可通过事件请求前端输入一次性凭证（不会保存），前端也可以取消本次输入
+
+use crate::commands::workspace::Workspace;
+use crate::error::AppError;
+use rusqlite::params;
+use std::collections::HashMap;
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 系统钥匙串中保存访问令牌的服务名，账户名为主机名
+const KEYRING_SERVICE: &str = "com.tzdxf.pm-app.git-credentials";
+
+/// 需要前端输入凭证时发出的事件
+pub const CREDENTIALS_NEEDED_EVENT: &str = "git://credentials-needed";
+
+/// 等待前端回应凭证的最长时间
+const CREDENTIALS_PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 前端回应的凭证：(用户名, 密码/令牌)
+type PromptedCredentials = (String, String);
+
+/// 正在等待前端输入凭证的操作：operation_id -> 凭证的发送端
+static PENDING_PROMPTS: once_cell::sync::Lazy<
+    Mutex<HashMap<String, mpsc::Sender<PromptedCredentials>>>,
+> = once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 向前端请求凭证所需的上下文
+pub struct CredentialPrompt {
+    app: AppHandle,
+    /// 克隆时仓库尚未登记，为 None
+    repo_id: Option<String>,
+    operation_id: String,
+}
+
+impl CredentialPrompt {
+    pub fn new(app: AppHandle, repo_id: &str) -> Self {
+        Self::with_repo_id(app, Some(repo_id.to_string()))
+    }
+
+    /// 克隆使用的请求上下文（事件中的 repoId 为 null）
+    pub fn for_clone(app: AppHandle) -> Self {
+        Self::with_repo_id(app, None)
+    }
+
+    fn with_repo_id(app: AppHandle, repo_id: Option<String>) -> Self {
+        Self {
+            app,
+            repo_id,
+            operation_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// 发出事件并阻塞等待前端通过 git_credentials_provide 回应
+    /// 前端通过 git_credentials_cancel 取消时返回 code 为 User 的错误，超时返回普通错误
+    fn request(&self, url: &str, username: Option<&str>) -> Result<PromptedCredentials, git2::Error> {
+        let receiver = register_prompt(&self.operation_id);
+        let _ = self.app.emit(
+            CREDENTIALS_NEEDED_EVENT,
+            serde_json::json!({
+                "operationId": self.operation_id,
+                "repoId": self.repo_id,
+                "url": url,
+                "username": username,
+            }),
+        );
+        let credentials = receiver.recv_timeout(CREDENTIALS_PROMPT_TIMEOUT);
+        PENDING_PROMPTS.lock().unwrap().remove(&self.operation_id);
+        credentials.map_err(|e| match e {
+            mpsc::RecvTimeoutError::Disconnected => credentials_cancelled_error(),
+            mpsc::RecvTimeoutError::Timeout => git2::Error::from_str("等待输入凭证超时"),
+        })
+    }
+}
+
+fn register_prompt(operation_id: &str) -> mpsc::Receiver<PromptedCredentials> {
+    let (sender, receiver) = mpsc::channel();
+    PENDING_PROMPTS
+        .lock()
+        .unwrap()
+        .insert(operation_id.to_string(), sender);
+    receiver
+}
+
+/// 把前端输入的凭证交给等待中的操作；没有对应的等待操作（未请求或已超时）时返回 false
+pub fn provide_credentials(operation_id: &str, username: String, secret: String) -> bool {
+    PENDING_PROMPTS
+        .lock()
+        .unwrap()
+        .remove(operation_id)
+        .map(|sender| sender.send((username, secret)).is_ok())
+        .unwrap_or(false)
+}
+
+/// 取消等待中的凭证请求：丢弃发送端，等待方随即以“用户取消”结束；
+/// 没有对应的等待操作时返回 false
+pub fn cancel_credentials(operation_id: &str) -> bool {
+    PENDING_PROMPTS.lock().unwrap().remove(operation_id).is_some()
+}
+
+/// 用户取消输入凭证时返回给 libgit2 的错误，调用方可按 code 为 User 识别
+fn credentials_cancelled_error() -> git2::Error {
+    git2::Error::new(
+        git2::ErrorCode::User,
+        git2::ErrorClass::Callback,
+        "用户取消输入凭证",
+    )
+}
+
+/// 判断 git 错误是否由用户取消输入凭证引起
+pub fn is_credentials_cancelled(e: &git2::Error) -> bool {
+    e.code() == git2::ErrorCode::User
+}
+
+/// 读取工作区配置的 SSH 私钥路径
+fn workspace_ssh_key_path(workspace: &Workspace) -> Option<String> {
+    crate::commands::workspace::workspace_settings(workspace).ssh_key_path
+}
+
+/// 展开路径开头的 `~` 为用户主目录
+fn expand_home(path: &str) -> std::path::PathBuf {
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| std::path::PathBuf::from(path)),
+        None => std::path::PathBuf::from(path),
+    }
+}
+
+/// 从远程 URL 中提取主机名（去掉协议、用户信息和端口），用作凭证的查找键
+pub fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let host = host_port.split(':').next()?.trim().to_lowercase();
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+/// 将主机的访问令牌写入系统钥匙串（覆盖已有令牌）
+/// 钥匙串不可用（如 Linux 上没有 Secret Service、钥匙串被锁定）时返回 Ok(false)，由调用方改存数据库
+pub fn store_token(host: &str, token: &str) -> Result<bool, AppError> {
+    match keyring::Entry::new(KEYRING_SERVICE, host).and_then(|entry| entry.set_password(token)) {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoStorageAccess(_)) | Err(keyring::Error::PlatformFailure(_)) => {
+            Ok(false)
+        }
+        Err(e) => Err(AppError::Io(format!("保存访问令牌到系统钥匙串失败: {}", e))),
+    }
+}
+
+/// 从系统钥匙串读取主机的访问令牌
+fn stored_token(host: &str) -> Option<String> {
+    keyring::Entry::new(KEYRING_SERVICE, host)
+        .and_then(|entry| entry.get_password())
+        .ok()
+}
+
+/// 按主机名查找已保存的 HTTPS 访问令牌（用户名、令牌）
+/// 数据库中有令牌说明保存时钥匙串不可用，直接使用；否则从钥匙串读取
+fn stored_credentials(workspace: &Workspace, url: &str) -> Option<(String, String)> {
+    let host = url_host(url)?;
+    let (username, token): (String, Option<String>) = {
+        let db_guard = crate::db::get_db(workspace.path()).ok()?;
+        let conn = db_guard.as_ref()?;
+        conn.query_row(
+            "SELECT username, token FROM git_credentials WHERE host = ?1",
+            params![host],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok()?
+    };
+    let token = match token {
+        Some(token) => token,
+        None => stored_token(&host)?,
+    };
+    Some((username, token))
+}
+
+/// 构造配置好凭证回调的 RemoteCallbacks，所有访问远程的 git 操作都应通过这里获取
+/// 调用方可在返回值上继续注册进度等回调
+pub fn make_callbacks<'a>(workspace: &Workspace) -> git2::RemoteCallbacks<'a> {
+    make_callbacks_with_prompt(workspace, None)
+}
+
+/// 同 make_callbacks，其余方式都失败时通过 prompt 请求前端输入凭证
+pub fn make_callbacks_with_prompt<'a>(
+    workspace: &Workspace,
+    prompt: Option<CredentialPrompt>,
+) -> git2::RemoteCallbacks<'a> {
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(credentials_callback(
+        workspace.clone(),
+        workspace_ssh_key_path(workspace),
+        prompt,
+    ));
+    callbacks
+}
+
+/// 构造 Git 凭证回调
+/// SSH 远程依次尝试 ssh-agent、工作区配置的私钥文件；HTTPS 远程使用按主机保存的访问令牌，
+/// 令牌不存在或被拒绝时请求前端输入（提供了 prompt 时）；其余情况回退到默认凭证。
+/// libgit2 在认证失败后会再次调用回调，因此每种方式只尝试一次，避免死循环。
+fn credentials_callback(
+    workspace: Workspace,
+    ssh_key_path: Option<String>,
+    prompt: Option<CredentialPrompt>,
+) -> impl FnMut(&str, Option<&str>, git2::CredentialType) -> Result<git2::Cred, git2::Error> {
+    let mut tried_agent = false;
+    let mut tried_key_file = false;
+    let mut tried_token = false;
+    let mut tried_prompt = false;
+
+    move |url, username_from_url, allowed_types| {
+        let username = username_from_url.unwrap_or("git");
+
+        if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) && !tried_token {
+            tried_token = true;
+            if let Some((stored_username, token)) = stored_credentials(&workspace, url) {
+                return git2::Cred::userpass_plaintext(&stored_username, &token);
+            }
+        }
+
+        if allowed_types.contains(git2::CredentialType::USERNAME) {
+            return git2::Cred::username(username);
+        }
+
+        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
+            if !tried_agent {
+                tried_agent = true;
+                if let Ok(cred) = git2::Cred::ssh_key_from_agent(username) {
+                    return Ok(cred);
+                }
+            }
+            if !tried_key_file {
+                tried_key_file = true;
+                if let Some(key_path) = ssh_key_path.as_deref() {
+                    return git2::Cred::ssh_key(username, None, &expand_home(key_path), None);
+                }
+            }
+        }
+
+        if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) && !tried_prompt {
+            if let Some(prompt) = prompt.as_ref() {
+                tried_prompt = true;
+                let (username, secret) = prompt.request(url, username_from_url)?;
+                return git2::Cred::userpass_plaintext(&username, &secret);
+            }
+        }
+
+        git2::Cred::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provide_credentials_reaches_pending_prompt() {
+        let receiver = register_prompt("op-1");
+        assert!(provide_credentials("op-1", "alice".into(), "s3cret".into()));
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(1)).unwrap(),
+            ("alice".to_string(), "s3cret".to_string())
+        );
+
+        // 每次等待只接受一次回应
+        assert!(!provide_credentials("op-1", "alice".into(), "again".into()));
+        assert!(!provide_credentials("unknown", "bob".into(), "x".into()));
+    }
+
+    #[test]
+    fn test_cancel_credentials_disconnects_pending_prompt() {
+        let receiver = register_prompt("op-cancel");
+        assert!(cancel_credentials("op-cancel"));
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(1)),
+            Err(mpsc::RecvTimeoutError::Disconnected)
+        );
+        assert!(!cancel_credentials("op-cancel"));
+        assert!(is_credentials_cancelled(&credentials_cancelled_error()));
+    }
+
+    #[test]
+    fn test_url_host_strips_scheme_user_and_port() {
+        assert_eq!(
+            url_host("https://user@GitHub.com:443/org/repo.git").as_deref(),
+            Some("github.com")
+        );
+        assert_eq!(
+            url_host("git@gitlab.example.com:org/repo.git").as_deref(),
+            Some("gitlab.example.com")
+        );
+        assert_eq!(url_host(""), None);
+    }
+}
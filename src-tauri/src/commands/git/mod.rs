@@ -0,0 +1,3016 @@
+pub mod auth;
+pub mod templates;
+
+use crate::commands::db_helpers::{map_git_repository_row, map_project_row, repo_path};
+use crate::error::AppError;
+use crate::with_db;
+use crate::commands::workspace::Workspace;
+use crate::types::*;
+use chrono::Utc;
+use git2::Repository;
+use notify::Watcher;
+use rusqlite::params;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+/// Global state to manage active file watchers using stop signals
+pub struct WatcherState {
+    pub watch_signals: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+}
+
+impl WatcherState {
+    pub fn new() -> Self {
+        WatcherState {
+            watch_signals: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+/// 列出项目的 Git 仓库（可按目录筛选）
+#[tauri::command]
+pub fn git_repo_list(
+    workspace: Workspace,
+    project_id: String,
+    folder: Option<String>,
+) -> Result<Vec<GitRepository>, AppError> {
+    with_db!(workspace, conn, {
+        if let Some(folder_name) = folder {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, project_id, name, path, folder, remote_url, branch, description, last_sync_at, last_status_checked_at, ide_override_json, sort_order, custom_name,
+                     last_commit_sha, last_commit_summary, last_commit_at
+                     FROM git_repositories WHERE project_id = ?1 AND folder = ?2 ORDER BY sort_order ASC, created_at DESC",
+                )
+                .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+
+            let result: Vec<GitRepository> = stmt
+                .query_map(params![project_id, folder_name], map_git_repository_row)
+                .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
+            Ok(result)
+        } else {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, project_id, name, path, folder, remote_url, branch, description, last_sync_at, last_status_checked_at, ide_override_json, sort_order, custom_name,
+                     last_commit_sha, last_commit_summary, last_commit_at
+                     FROM git_repositories WHERE project_id = ?1 ORDER BY sort_order ASC, created_at DESC",
+                )
+                .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+
+            let result: Vec<GitRepository> = stmt
+                .query_map(params![project_id], map_git_repository_row)
+                .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
+            Ok(result)
+        }
+    })
+}
+
+/// 工作区设置中的提交作者（名称和邮箱都填写时才生效）
+fn workspace_commit_author(workspace: &Workspace) -> Option<(String, String)> {
+    let settings = crate::commands::workspace::workspace_settings(workspace);
+    Some((settings.author_name?, settings.author_email?))
+}
+
+/// 提交使用的作者签名：工作区设置 > 仓库的 git 配置
+fn commit_signature(
+    workspace: &Workspace,
+    repo: &Repository,
+) -> Result<git2::Signature<'static>, AppError> {
+    commit_signature_with(repo, workspace_commit_author(workspace))
+}
+
+fn commit_signature_with(
+    repo: &Repository,
+    author: Option<(String, String)>,
+) -> Result<git2::Signature<'static>, AppError> {
+    if let Some((name, email)) = author {
+        return git2::Signature::now(&name, &email)
+            .map_err(|e| AppError::Git(format!("创建提交签名失败: {}", e)));
+    }
+    repo.signature().map(|sig| sig.to_owned()).map_err(|_| {
+        AppError::Validation(
+            "未配置提交作者，请在工作区设置中填写作者名称和邮箱，或在 git 配置中设置 user.name 和 user.email"
+                .to_string(),
+        )
+    })
+}
+
+/// 创建根提交，使新仓库拥有有效的 HEAD（工作区中已有 .gitignore 时一并提交）
+fn create_initial_commit(workspace: &Workspace, repo: &Repository) -> Result<(), AppError> {
+    let signature = commit_signature(workspace, repo)?;
+    let mut index = repo
+        .index()
+        .map_err(|e| AppError::Git(format!("读取索引失败: {}", e)))?;
+    let has_gitignore = repo
+        .workdir()
+        .map(|dir| dir.join(".gitignore").is_file())
+        .unwrap_or(false);
+    if has_gitignore {
+        index
+            .add_path(Path::new(".gitignore"))
+            .map_err(|e| AppError::Git(format!("添加 .gitignore 失败: {}", e)))?;
+        index.write().map_err(|e| AppError::Git(format!("写入索引失败: {}", e)))?;
+    }
+    let tree_id = index
+        .write_tree()
+        .map_err(|e| AppError::Git(format!("创建树失败: {}", e)))?;
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| AppError::Git(format!("读取空树失败: {}", e)))?;
+
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Initial commit",
+        &tree,
+        &[],
+    )
+    .map_err(|e| AppError::Git(format!("创建初始提交失败: {}", e)))?;
+    Ok(())
+}
+
+/// 创建新的本地 Git 仓库
+#[tauri::command]
+pub async fn git_repo_create(
+    workspace: Workspace,
+    project_id: String,
+    name: String,
+    initial_commit: Option<bool>,
+    gitignore_template: Option<String>,
+) -> Result<GitRepository, AppError> {
+    let initial_commit = initial_commit.unwrap_or(false);
+    let gitignore = match gitignore_template.as_deref().filter(|t| !t.trim().is_empty()) {
+        Some(name) => Some(templates::gitignore_template(name).ok_or_else(|| {
+            AppError::Validation(format!(
+                "未知的 .gitignore 模板: {}（可选: {}）",
+                name,
+                templates::TEMPLATE_NAMES.join(", ")
+            ))
+        })?),
+        None => None,
+    };
+
+    let project_path: String = with_db!(workspace, conn, {
+        conn.query_row(
+            "SELECT project_path FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::lookup(&e, format!("项目不存在: {}", e)))
+    })?;
+
+    let code_dir = Path::new(&project_path).join("code");
+    fs::create_dir_all(&code_dir)
+        .map_err(|e| AppError::io(&e, format!("创建 code 目录失败: {}", e)))?;
+
+    let repo_path = code_dir.join(&name);
+
+    let repo_path_clone = repo_path.clone();
+    let thread_workspace = workspace.clone();
+    tokio::task::spawn_blocking(move || {
+        let created = !repo_path_clone.exists();
+        let result = (|| {
+            let mut init_opts = git2::RepositoryInitOptions::new();
+            init_opts.initial_head("main");
+            let repo = Repository::init_opts(&repo_path_clone, &init_opts)
+                .map_err(|e| AppError::Git(format!("创建 Git 仓库失败: {}", e)))?;
+
+            if let Some(content) = gitignore {
+                fs::write(repo_path_clone.join(".gitignore"), content)
+                    .map_err(|e| AppError::io(&e, format!("写入 .gitignore 失败: {}", e)))?;
+            }
+
+            if initial_commit {
+                create_initial_commit(&thread_workspace, &repo)?;
+            }
+            Ok::<(), AppError>(())
+        })();
+        // 失败时（如未配置提交作者）删除本次新建的目录，避免留下未登记的半成品仓库
+        if result.is_err() && created {
+            let _ = fs::remove_dir_all(&repo_path_clone);
+        }
+        result
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("任务执行失败: {}", e)))??;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let folder = "code".to_string();
+
+    let sort_order: i32 = with_db!(workspace, conn, {
+        let max_sort: Option<i32> = conn
+            .query_row(
+                "SELECT MAX(sort_order) FROM git_repositories WHERE project_id = ?1",
+                params![project_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(None);
+        let next_sort = max_sort.unwrap_or(0) + 1;
+
+        conn.execute(
+            "INSERT INTO git_repositories (id, project_id, name, path, folder, created_at, updated_at, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                id,
+                project_id,
+                name,
+                repo_path.to_string_lossy().to_string(),
+                folder,
+                now,
+                now,
+                next_sort
+            ],
+        )
+        .map_err(|e| AppError::Db(format!("保存仓库失败: {}", e)))?;
+        Ok::<i32, AppError>(next_sort)
+    })?;
+
+    Ok(GitRepository {
+        id,
+        project_id,
+        name,
+        path: repo_path.to_string_lossy().to_string(),
+        folder: Some(folder),
+        remote_url: None,
+        branch: Some("main".to_string()),
+        description: None,
+        last_sync_at: None,
+        last_status_checked_at: None,
+        ide_override: None,
+        sort_order: Some(sort_order),
+        custom_name: None,
+        last_commit_sha: None,
+        last_commit_summary: None,
+        last_commit_at: None,
+    })
+}
+
+/// 进行中的克隆操作的取消标记（按客户端提供的 operation_id 索引）
+static CLONE_CANCEL_FLAGS: once_cell::sync::Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 克隆期间登记的取消标记，结束时（包括出错返回）自动注销
+struct CloneCancellation {
+    operation_id: Option<String>,
+    flag: Arc<AtomicBool>,
+}
+
+impl CloneCancellation {
+    fn register(operation_id: Option<String>) -> Self {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Some(id) = operation_id.as_ref() {
+            CLONE_CANCEL_FLAGS
+                .lock()
+                .unwrap()
+                .insert(id.clone(), flag.clone());
+        }
+        CloneCancellation { operation_id, flag }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for CloneCancellation {
+    fn drop(&mut self) {
+        if let Some(id) = self.operation_id.as_ref() {
+            CLONE_CANCEL_FLAGS.lock().unwrap().remove(id);
+        }
+    }
+}
+
+/// 取消进行中的克隆：在下一次传输进度回调时中止，并清理已下载的目录
+#[tauri::command]
+pub fn git_clone_cancel(operation_id: String) -> Result<serde_json::Value, AppError> {
+    let flag = CLONE_CANCEL_FLAGS.lock().unwrap().get(&operation_id).cloned();
+    if let Some(flag) = flag.as_ref() {
+        flag.store(true, Ordering::Relaxed);
+    }
+    Ok(serde_json::json!({ "ok": true, "cancelled": flag.is_some() }))
+}
+
+/// 从 URL 克隆 Git 仓库（支持进度、重试和取消）
+#[tauri::command]
+pub async fn git_repo_clone(
+    workspace: Workspace,
+    app_handle: AppHandle,
+    project_id: String,
+    input: GitCloneInput,
+) -> Result<GitRepository, AppError> {
+    let project_path: String = with_db!(workspace, conn, {
+        conn.query_row(
+            "SELECT project_path FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::lookup(&e, format!("项目不存在: {}", e)))
+    })?;
+
+    let target_dir = input.target_directory.as_deref().unwrap_or("code");
+    let target_base = Path::new(&project_path).join(target_dir);
+    fs::create_dir_all(&target_base)
+        .map_err(|e| AppError::io(&e, format!("创建目标目录失败: {}", e)))?;
+
+    let repo_path = target_base.join(&input.target_dir_name);
+    let remote_url = input.remote_url.clone();
+    let repo_path_clone = repo_path.clone();
+    let app_handle_clone = app_handle.clone();
+    let clone_task_id = uuid::Uuid::new_v4().to_string();
+    let depth = input.depth.filter(|d| *d > 0);
+    let requested_branch = input
+        .branch
+        .as_deref()
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .map(String::from);
+    let cancellation = CloneCancellation::register(input.operation_id.clone());
+
+    let _ = app_handle.emit(
+        "git:clone:progress",
+        GitCloneProgress {
+            stage: GitCloneStage::Starting,
+            progress: None,
+            message: format!("开始克隆仓库 {}", input.target_dir_name),
+            retry_count: 0,
+            error: None,
+        },
+    );
+
+    let max_retries = 3;
+    let mut last_error = String::new();
+
+    for attempt in 0..max_retries {
+        if attempt > 0 {
+            let _ = app_handle.emit(
+                "git:clone:progress",
+                GitCloneProgress {
+                    stage: GitCloneStage::Starting,
+                    progress: None,
+                    message: format!(
+                        "重试克隆 ({}/{}): {}",
+                        attempt + 1,
+                        max_retries,
+                        input.target_dir_name
+                    ),
+                    retry_count: attempt as u32,
+                    error: Some(last_error.clone()),
+                },
+            );
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            if cancellation.is_cancelled() {
+                let _ = fs::remove_dir_all(&repo_path);
+                return Err(AppError::Git("克隆已取消".into()));
+            }
+        }
+
+        let result = tokio::task::spawn_blocking({
+            let repo_path_clone = repo_path_clone.clone();
+            let remote_url = remote_url.clone();
+            let app_handle_clone = app_handle_clone.clone();
+            let _clone_task_id = clone_task_id.clone();
+            let repo_target = input.target_dir_name.clone();
+            let branch = requested_branch.clone();
+            let cancel_flag = cancellation.flag.clone();
+            let workspace = workspace.clone();
+
+            move || {
+                let _ = app_handle_clone.emit(
+                    "git:clone:progress",
+                    GitCloneProgress {
+                        stage: GitCloneStage::Connecting,
+                        progress: Some(0.0),
+                        message: "正在连接远程仓库...".to_string(),
+                        retry_count: attempt as u32,
+                        error: None,
+                    },
+                );
+
+                if repo_path_clone.exists() {
+                    let _ = fs::remove_dir_all(&repo_path_clone);
+                }
+
+                let mut callbacks = auth::make_callbacks_with_prompt(
+                    &workspace,
+                    Some(auth::CredentialPrompt::for_clone(app_handle_clone.clone())),
+                );
+
+                // 按百分比节流，避免每个对象都向前端发送事件
+                let progress_handle = app_handle_clone.clone();
+                let mut last_percent: Option<usize> = None;
+                let transfer_cancel_flag = cancel_flag.clone();
+                callbacks.transfer_progress(move |stats| {
+                    // 返回 false 会让 libgit2 中止传输
+                    if transfer_cancel_flag.load(Ordering::Relaxed) {
+                        return false;
+                    }
+                    let total = stats.total_objects();
+                    let received = stats.received_objects();
+                    let percent = (received * 100).checked_div(total).unwrap_or(0);
+                    if last_percent != Some(percent) {
+                        last_percent = Some(percent);
+                        let _ = progress_handle.emit(
+                            "git://clone-progress",
+                            GitCloneTransferProgress {
+                                repo_target: repo_target.clone(),
+                                received_objects: received,
+                                total_objects: total,
+                                bytes: stats.received_bytes(),
+                            },
+                        );
+                    }
+                    true
+                });
+
+                let mut fetch_options = git2::FetchOptions::new();
+                fetch_options.remote_callbacks(callbacks);
+                if let Some(depth) = depth {
+                    fetch_options.depth(depth as i32);
+                }
+
+                let mut builder = git2::build::RepoBuilder::new();
+                builder.fetch_options(fetch_options);
+                if let Some(branch) = branch.as_deref() {
+                    builder.branch(branch);
+                }
+
+                let clone_result = match builder.clone(&remote_url, &repo_path_clone) {
+                    // 部分服务器或传输协议不支持 libgit2 的浅克隆，回退到 git 命令行
+                    Err(e)
+                        if depth.is_some()
+                            && e.code() != git2::ErrorCode::NotFound
+                            && !cancel_flag.load(Ordering::Relaxed) =>
+                    {
+                        let _ = fs::remove_dir_all(&repo_path_clone);
+                        shallow_clone_with_cli(
+                            &remote_url,
+                            &repo_path_clone,
+                            depth.unwrap_or(1),
+                            branch.as_deref(),
+                            &cancel_flag,
+                        )
+                        .map_err(|cli_err| {
+                            git2::Error::from_str(&format!("{}；{}", e.message(), cli_err))
+                        })
+                    }
+                    other => other.map(|_| ()),
+                };
+
+                // 取消发生在传输结束之后时克隆仍会成功返回，同样按取消处理
+                let clone_result = match clone_result {
+                    Ok(()) if cancel_flag.load(Ordering::Relaxed) => {
+                        Err(git2::Error::from_str("克隆已取消"))
+                    }
+                    other => other,
+                };
+
+                match clone_result {
+                    Ok(_) => {
+                        let _ = app_handle_clone.emit(
+                            "git:clone:progress",
+                            GitCloneProgress {
+                                stage: GitCloneStage::Completed,
+                                progress: Some(100.0),
+                                message: "克隆完成".to_string(),
+                                retry_count: attempt as u32,
+                                error: None,
+                            },
+                        );
+                        Ok(())
+                    }
+                    Err(e) if cancel_flag.load(Ordering::Relaxed) => {
+                        let _ = app_handle_clone.emit(
+                            "git:clone:progress",
+                            GitCloneProgress {
+                                stage: GitCloneStage::Cancelled,
+                                progress: None,
+                                message: "克隆已取消".to_string(),
+                                retry_count: attempt as u32,
+                                error: None,
+                            },
+                        );
+                        Err(e)
+                    }
+                    Err(e) => {
+                        let error_msg = format!("克隆失败: {}", e);
+                        let _ = app_handle_clone.emit(
+                            "git:clone:progress",
+                            GitCloneProgress {
+                                stage: GitCloneStage::Failed,
+                                progress: None,
+                                message: error_msg.clone(),
+                                retry_count: attempt as u32,
+                                error: Some(error_msg),
+                            },
+                        );
+                        Err(e)
+                    }
+                }
+            }
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("任务执行失败: {}", e)))?;
+
+        match result {
+            // 取消标志可能在阻塞任务返回后才被置位，成功时也要再检查一次
+            _ if cancellation.is_cancelled() => {
+                let _ = fs::remove_dir_all(&repo_path);
+                return Err(AppError::Git("克隆已取消".into()));
+            }
+            Ok(()) => break,
+            // 用户放弃输入凭证时重试只会再次询问，直接结束
+            Err(e) if auth::is_credentials_cancelled(&e) => {
+                let _ = fs::remove_dir_all(&repo_path);
+                return Err(AppError::Git("用户取消输入凭证".into()));
+            }
+            // 指定的分支在远程不存在时重试无意义，直接返回明确的错误
+            Err(e) if e.code() == git2::ErrorCode::NotFound && requested_branch.is_some() => {
+                let _ = fs::remove_dir_all(&repo_path);
+                return Err(AppError::NotFound(format!(
+                    "远程分支不存在: {}",
+                    requested_branch.as_deref().unwrap_or_default()
+                )));
+            }
+            Err(_) if attempt < max_retries - 1 => {
+                last_error = format!("尝试 {} 失败", attempt + 1);
+                continue;
+            }
+            Err(e) => {
+                last_error = format!("克隆失败: {}", e);
+                return Err(AppError::Git(last_error));
+            }
+        }
+    }
+
+    let repo_path_clone2 = repo_path.clone();
+    let (branch_name, remote_url_result) = tokio::task::spawn_blocking(move || {
+        let repo =
+            Repository::open(&repo_path_clone2).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+        let branch = head_branch_name(&repo);
+        let remote = repo.remotes().ok().and_then(|r| {
+            r.iter()
+                .next()
+                .flatten()
+                .and_then(|name| repo.find_remote(name).ok().and_then(|remote| remote.url().map(String::from)))
+        });
+        Ok::<(Option<String>, Option<String>), AppError>((branch, remote))
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("任务执行失败: {}", e)))??;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let repo_name = input.name.unwrap_or_else(|| input.target_dir_name.clone());
+    let folder = repo_path
+        .parent()
+        .and_then(|parent| parent.strip_prefix(&project_path).ok())
+        .and_then(|rel| rel.components().next())
+        .and_then(|c| c.as_os_str().to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "root".to_string());
+
+    let sort_order: i32 = with_db!(workspace, conn, {
+        let max_sort: Option<i32> = conn
+            .query_row(
+                "SELECT MAX(sort_order) FROM git_repositories WHERE project_id = ?1",
+                params![project_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(None);
+        let next_sort = max_sort.unwrap_or(0) + 1;
+
+        conn.execute(
+            "INSERT INTO git_repositories (id, project_id, name, path, folder, remote_url, branch, last_sync_at, created_at, updated_at, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                id,
+                project_id,
+                repo_name,
+                repo_path.to_string_lossy().to_string(),
+                folder,
+                remote_url,
+                branch_name,
+                now,
+                now,
+                now,
+                next_sort
+            ],
+        )
+        .map_err(|e| AppError::Db(format!("保存仓库失败: {}", e)))?;
+        Ok::<i32, AppError>(next_sort)
+    })?;
+
+    Ok(GitRepository {
+        id,
+        project_id,
+        name: repo_name,
+        path: repo_path.to_string_lossy().to_string(),
+        folder: Some(folder),
+        remote_url: remote_url_result,
+        branch: branch_name,
+        description: None,
+        last_sync_at: Some(now),
+        last_status_checked_at: None,
+        ide_override: None,
+        sort_order: Some(sort_order),
+        custom_name: None,
+        last_commit_sha: None,
+        last_commit_summary: None,
+        last_commit_at: None,
+    })
+}
+
+/// Git 仓库更新输入
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitRepoUpdateInput {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub ide_override: Option<IdeConfig>,
+}
+
+/// 更新 Git 仓库信息（名称、描述、IDE覆盖）
+#[tauri::command]
+pub fn git_repo_update(
+    workspace: Workspace,
+    repo_id: String,
+    patch: GitRepoUpdateInput,
+) -> Result<GitRepository, AppError> {
+    with_db!(workspace, conn, {
+        let now = Utc::now().to_rfc3339();
+
+        repo_path(conn, &repo_id)?;
+        let current_repo: GitRepository = conn
+            .query_row(
+                "SELECT id, project_id, name, path, folder, remote_url, branch, description, last_sync_at, last_status_checked_at, ide_override_json, sort_order, custom_name,
+                 last_commit_sha, last_commit_summary, last_commit_at
+                 FROM git_repositories WHERE id = ?1",
+                params![repo_id],
+                map_git_repository_row,
+            )
+            .map_err(|e| AppError::Db(format!("读取仓库失败: {}", e)))?;
+
+        let name = patch.name.unwrap_or(current_repo.name);
+        let description = patch.description.or(current_repo.description);
+        let ide_override = patch.ide_override.or(current_repo.ide_override);
+
+        let ide_override_json = ide_override
+            .as_ref()
+            .and_then(|i| serde_json::to_string(i).ok());
+
+        conn.execute(
+            "UPDATE git_repositories SET name = ?1, description = ?2, ide_override_json = ?3, updated_at = ?4 WHERE id = ?5",
+            params![name, description, ide_override_json, now, repo_id],
+        )
+        .map_err(|e| AppError::Db(format!("更新仓库失败: {}", e)))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, project_id, name, path, folder, remote_url, branch, description, last_sync_at, last_status_checked_at, ide_override_json, sort_order, custom_name,
+                 last_commit_sha, last_commit_summary, last_commit_at
+                 FROM git_repositories WHERE id = ?1",
+            )
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+
+        let repo: GitRepository = stmt
+            .query_row(params![repo_id], map_git_repository_row)
+            .map_err(|e| AppError::Db(format!("读取仓库失败: {}", e)))?;
+        Ok(repo)
+    })
+}
+
+/// 重新排序 Git 仓库
+#[tauri::command]
+pub fn git_repo_reorder(
+    workspace: Workspace,
+    project_id: String,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<GitRepository>, AppError> {
+    with_db!(workspace, conn, {
+        conn.execute("BEGIN TRANSACTION", params![])
+            .map_err(|e| AppError::Db(format!("开始事务失败: {}", e)))?;
+
+        for (index, repo_id) in ordered_ids.iter().enumerate() {
+            let sort_order = index as i32;
+            conn.execute(
+                "UPDATE git_repositories SET sort_order = ?1 WHERE id = ?2 AND project_id = ?3",
+                params![sort_order, repo_id, project_id],
+            )
+            .map_err(|e| {
+                let _ = conn.execute("ROLLBACK", params![]);
+                AppError::Db(format!("更新排序失败: {}", e))
+            })?;
+        }
+
+        conn.execute("COMMIT", params![])
+            .map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, project_id, name, path, folder, remote_url, branch, description, last_sync_at, last_status_checked_at, ide_override_json, sort_order, custom_name,
+                 last_commit_sha, last_commit_summary, last_commit_at
+                 FROM git_repositories WHERE project_id = ?1 ORDER BY sort_order ASC",
+            )
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+
+        let repos: Vec<GitRepository> = stmt
+            .query_map(params![project_id], map_git_repository_row)
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
+        Ok(repos)
+    })
+}
+
+/// 从远程 URL 提取仓库名称
+/// 支持 https://host/org/repo.git、git@host:org/repo.git、末尾斜杠和不带 .git 的地址
+#[tauri::command]
+pub fn git_extract_repo_name(remote_url: String) -> Result<String, AppError> {
+    let url = remote_url.trim().trim_end_matches(['/', '\\']);
+    if url.is_empty() {
+        return Err(AppError::Validation("远程地址不能为空".into()));
+    }
+
+    // 带协议的地址必须在主机名之后包含仓库路径
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest
+            .split_once('/')
+            .map(|(_, path)| path)
+            .ok_or_else(|| AppError::Validation("远程地址缺少仓库路径".to_string()))?,
+        None => url,
+    };
+
+    let last = path
+        .rsplit(['/', '\\', ':'])
+        .next()
+        .filter(|_| path.contains(['/', '\\', ':']))
+        .ok_or_else(|| AppError::Validation("无法识别的远程地址".to_string()))?;
+    let name = last.strip_suffix(".git").unwrap_or(last);
+
+    let valid = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if !valid {
+        return Err(AppError::Validation(format!("无法从远程地址提取仓库名称: {}", remote_url)));
+    }
+
+    Ok(name.to_string())
+}
+
+/// 拉取仓库（网络操作在阻塞线程池中执行，不占用命令执行器）
+#[tauri::command]
+pub async fn git_repo_pull(
+    workspace: Workspace,
+    app_handle: AppHandle,
+    repo_id: String,
+) -> Result<GitPullResult, AppError> {
+    let prompt = auth::CredentialPrompt::new(app_handle, &repo_id);
+    tokio::task::spawn_blocking(move || pull_repo(&workspace, repo_id, Some(prompt)))
+        .await
+        .map_err(|e| AppError::Internal(format!("任务执行失败: {}", e)))?
+}
+
+fn pull_repo(
+    workspace: &Workspace,
+    repo_id: String,
+    prompt: Option<auth::CredentialPrompt>,
+) -> Result<GitPullResult, AppError> {
+    let (path, cached_branch) = with_db!(workspace, conn, {
+        let path = repo_path(conn, &repo_id)?;
+        let branch: Option<String> = conn
+            .query_row(
+                "SELECT branch FROM git_repositories WHERE id = ?1",
+                params![repo_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Db(format!("读取仓库失败: {}", e)))?;
+        Ok::<_, AppError>((path, branch))
+    })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+
+    let target = match resolve_pull_target(&repo)? {
+        Some(target) => target,
+        // 空仓库还没有分支，拉取 origin 的默认分支：优先使用缓存的分支名，
+        // 没有缓存时查询远程并写入 branch 列，后续操作无需再次联网
+        None => {
+            let default_branch = match cached_branch.filter(|b| !b.is_empty()) {
+                Some(branch) => Ok(branch),
+                None => repo
+                    .find_remote("origin")
+                    .map_err(|e| AppError::Git(format!("找不到远程 origin: {}", e)))
+                    .and_then(|mut origin| resolve_remote_default_branch(workspace, &mut origin))
+                    .and_then(|branch| {
+                        store_branch(workspace, &repo_id, &branch)?;
+                        Ok(branch)
+                    }),
+            };
+            match default_branch {
+                Ok(branch) => PullTarget::on_origin(branch),
+                Err(e) => {
+                    return Ok(GitPullResult {
+                        ok: false,
+                        message: None,
+                        synced_at: None,
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+    };
+
+    let mut remote = match repo.find_remote(&target.remote) {
+        Ok(r) => r,
+        Err(e) => {
+            return Ok(GitPullResult {
+                ok: false,
+                message: None,
+                synced_at: None,
+                error: Some(format!("找不到远程 {}: {}", target.remote, e)),
+            });
+        }
+    };
+
+    let refspec = target.refspec();
+
+    let callbacks = auth::make_callbacks_with_prompt(workspace, prompt);
+
+    match remote.fetch(
+        &[refspec.as_str()],
+        Some(&mut git2::FetchOptions::new().remote_callbacks(callbacks)),
+        None,
+    ) {
+        Ok(_) => {}
+        Err(e) => {
+            return Ok(GitPullResult {
+                ok: false,
+                message: None,
+                synced_at: None,
+                error: Some(format!("拉取失败: {}", e)),
+            });
+        }
+    }
+
+    let message = match merge_fetched_branch(workspace, &repo, &target) {
+        Ok(MergeOutcome::UpToDate) => "已是最新".to_string(),
+        Ok(MergeOutcome::FastForward) => "拉取成功（快进）".to_string(),
+        Ok(MergeOutcome::Merged) => "拉取成功，已创建合并提交".to_string(),
+        // 存在冲突时保留合并状态，交由用户在外部解决，不更新同步时间
+        Ok(MergeOutcome::Conflicts(paths)) => {
+            return Ok(GitPullResult {
+                ok: false,
+                message: Some(format!("合并存在冲突，请解决以下文件后提交: {}", paths.join(", "))),
+                synced_at: None,
+                error: Some("合并冲突".to_string()),
+            });
+        }
+        Err(e) => {
+            return Ok(GitPullResult {
+                ok: false,
+                message: None,
+                synced_at: None,
+                error: Some(e.to_string()),
+            });
+        }
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let last_commit = head_commit_info(&repo);
+
+    with_db!(workspace, conn, {
+        conn.execute(
+            "UPDATE git_repositories SET branch = ?1, last_sync_at = ?2, updated_at = ?2 WHERE id = ?3",
+            params![target.local_branch, now, repo_id],
+        )
+        .map_err(|e| AppError::Db(format!("更新同步时间失败: {}", e)))?;
+        store_last_commit_with_conn(conn, &repo_id, last_commit.as_ref())
+    })?;
+
+    Ok(GitPullResult {
+        ok: true,
+        message: Some(message),
+        synced_at: Some(now),
+        error: None,
+    })
+}
+
+/// 将分支名写入仓库记录的 branch 列
+fn store_branch(workspace: &Workspace, repo_id: &str, branch: &str) -> Result<(), AppError> {
+    let now = Utc::now().to_rfc3339();
+    with_db!(workspace, conn, {
+        conn.execute(
+            "UPDATE git_repositories SET branch = ?1, updated_at = ?2 WHERE id = ?3",
+            params![branch, now, repo_id],
+        )
+        .map(|_| ())
+        .map_err(|e| AppError::Db(format!("更新分支失败: {}", e)))
+    })
+}
+
+/// 拉取目标：当前分支的上游分支，以及它在本地对应的远程跟踪引用
+struct PullTarget {
+    remote: String,
+    /// 本地分支名
+    local_branch: String,
+    /// 远程上的分支引用，如 refs/heads/main
+    merge_ref: String,
+    /// 本地的远程跟踪引用，如 refs/remotes/origin/main
+    tracking_ref: String,
+}
+
+impl PullTarget {
+    /// origin 上与本地同名的分支
+    fn on_origin(branch: String) -> Self {
+        PullTarget {
+            remote: "origin".to_string(),
+            merge_ref: format!("refs/heads/{}", branch),
+            tracking_ref: format!("refs/remotes/origin/{}", branch),
+            local_branch: branch,
+        }
+    }
+
+    fn refspec(&self) -> String {
+        format!("+{}:{}", self.merge_ref, self.tracking_ref)
+    }
+}
+
+/// 按当前检出的分支确定拉取目标：使用分支配置的上游（branch.<name>.remote / merge），
+/// 未配置上游时使用 origin 上的同名分支；空仓库（HEAD 尚无提交）返回 None，分离 HEAD 时拒绝拉取
+fn resolve_pull_target(repo: &Repository) -> Result<Option<PullTarget>, AppError> {
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => return Ok(None),
+        Err(e) => return Err(AppError::Git(format!("读取当前分支失败: {}", e))),
+    };
+    if !head.is_branch() {
+        return Err(AppError::Validation(
+            "当前处于分离 HEAD 状态，请先切换到分支再拉取".to_string(),
+        ));
+    }
+    let head_ref = head
+        .name()
+        .ok_or_else(|| AppError::Validation("分支名称无效".to_string()))?;
+    let local_branch = head
+        .shorthand()
+        .ok_or_else(|| AppError::Validation("分支名称无效".to_string()))?
+        .to_string();
+
+    let buf_value =
+        |value: Result<git2::Buf, git2::Error>| value.ok().and_then(|buf| buf.as_str().map(String::from));
+    let merge_ref = repo
+        .config()
+        .and_then(|config| config.get_string(&format!("branch.{}.merge", local_branch)))
+        .ok();
+    let upstream = (
+        buf_value(repo.branch_upstream_remote(head_ref)),
+        merge_ref,
+        buf_value(repo.branch_upstream_name(head_ref)),
+    );
+    Ok(Some(match upstream {
+        (Some(remote), Some(merge_ref), Some(tracking_ref)) => PullTarget {
+            remote,
+            local_branch,
+            merge_ref,
+            tracking_ref,
+        },
+        _ => PullTarget::on_origin(local_branch),
+    }))
+}
+
+/// 合并远程分支的结果
+enum MergeOutcome {
+    UpToDate,
+    FastForward,
+    Merged,
+    Conflicts(Vec<String>),
+}
+
+/// 将拉取到的上游分支合并到当前 HEAD（可快进时直接快进，否则尝试真正的合并）
+fn merge_fetched_branch(
+    workspace: &Workspace,
+    repo: &Repository,
+    upstream: &PullTarget,
+) -> Result<MergeOutcome, AppError> {
+    let remote_ref = repo
+        .find_reference(&upstream.tracking_ref)
+        .map_err(|e| AppError::NotFound(format!("找不到远程分支 {}: {}", upstream.tracking_ref, e)))?;
+    let fetched = repo
+        .reference_to_annotated_commit(&remote_ref)
+        .map_err(|e| AppError::Git(format!("读取远程提交失败: {}", e)))?;
+
+    let (analysis, _) = repo
+        .merge_analysis(&[&fetched])
+        .map_err(|e| AppError::Git(format!("合并分析失败: {}", e)))?;
+
+    if analysis.is_up_to_date() {
+        return Ok(MergeOutcome::UpToDate);
+    }
+
+    if analysis.is_fast_forward() || analysis.is_unborn() {
+        let target = fetched.id();
+        // 先以安全模式检出目标树，本地修改会与之冲突时中止而不是覆盖
+        let target_object = repo
+            .find_object(target, None)
+            .map_err(|e| AppError::Git(format!("读取远程提交失败: {}", e)))?;
+        repo.checkout_tree(&target_object, Some(git2::build::CheckoutBuilder::new().safe()))
+            .map_err(|e| AppError::Git(format!("检出失败（本地修改可能被覆盖）: {}", e)))?;
+        match repo.head() {
+            Ok(mut head) if head.is_branch() => {
+                head.set_target(target, "pm-app: fast-forward")
+                    .map_err(|e| AppError::Git(format!("快进失败: {}", e)))?;
+            }
+            _ => {
+                // 空仓库：直接创建本地分支并指向远程提交
+                let ref_name = format!("refs/heads/{}", upstream.local_branch);
+                repo.reference(&ref_name, target, true, "pm-app: initial pull")
+                    .map_err(|e| AppError::Git(format!("创建本地分支失败: {}", e)))?;
+                repo.set_head(&ref_name)
+                    .map_err(|e| AppError::Git(format!("切换分支失败: {}", e)))?;
+            }
+        }
+        return Ok(MergeOutcome::FastForward);
+    }
+
+    if !analysis.is_normal() {
+        return Err(AppError::Validation("无法合并远程分支".to_string()));
+    }
+
+    repo.merge(&[&fetched], None, None)
+        .map_err(|e| AppError::Git(format!("合并失败: {}", e)))?;
+
+    let mut index = repo
+        .index()
+        .map_err(|e| AppError::Git(format!("读取索引失败: {}", e)))?;
+    if index.has_conflicts() {
+        let mut paths: Vec<String> = index
+            .conflicts()
+            .map_err(|e| AppError::Git(format!("读取冲突失败: {}", e)))?
+            .filter_map(|c| c.ok())
+            .filter_map(|c| c.our.or(c.their).or(c.ancestor))
+            .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+            .collect();
+        paths.dedup();
+        return Ok(MergeOutcome::Conflicts(paths));
+    }
+
+    let signature = commit_signature(workspace, repo)?;
+    let tree_id = index
+        .write_tree()
+        .map_err(|e| AppError::Git(format!("写入合并树失败: {}", e)))?;
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| AppError::Git(format!("读取合并树失败: {}", e)))?;
+    let head_commit = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| AppError::Git(format!("读取当前提交失败: {}", e)))?;
+    let fetched_commit = repo
+        .find_commit(fetched.id())
+        .map_err(|e| AppError::Git(format!("读取远程提交失败: {}", e)))?;
+
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &format!(
+            "Merge remote-tracking branch '{}'",
+            upstream.tracking_ref.trim_start_matches("refs/remotes/")
+        ),
+        &tree,
+        &[&head_commit, &fetched_commit],
+    )
+    .map_err(|e| AppError::Git(format!("创建合并提交失败: {}", e)))?;
+    repo.cleanup_state()
+        .map_err(|e| AppError::Git(format!("清理合并状态失败: {}", e)))?;
+
+    Ok(MergeOutcome::Merged)
+}
+
+/// 从 origin 获取所有远程引用（不合并、不修改工作区），prune 为 true 时清理已删除的远程分支
+#[tauri::command]
+pub async fn git_repo_fetch(
+    workspace: Workspace,
+    app_handle: AppHandle,
+    repo_id: String,
+    prune: bool,
+) -> Result<serde_json::Value, AppError> {
+    let prompt = auth::CredentialPrompt::new(app_handle, &repo_id);
+    tokio::task::spawn_blocking(move || fetch_repo(&workspace, repo_id, prune, Some(prompt)))
+        .await
+        .map_err(|e| AppError::Internal(format!("任务执行失败: {}", e)))?
+}
+
+fn fetch_repo(
+    workspace: &Workspace,
+    repo_id: String,
+    prune: bool,
+    prompt: Option<auth::CredentialPrompt>,
+) -> Result<serde_json::Value, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+    let mut remote = repo
+        .find_remote("origin")
+        .map_err(|e| AppError::NotFound(format!("找不到远程 origin: {}", e)))?;
+
+    let mut updated_refs: u32 = 0;
+    {
+        let mut callbacks = auth::make_callbacks_with_prompt(workspace, prompt);
+        callbacks.update_tips(|_, _, _| {
+            updated_refs += 1;
+            true
+        });
+
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(callbacks);
+        fetch_options.prune(if prune {
+            git2::FetchPrune::On
+        } else {
+            git2::FetchPrune::Unspecified
+        });
+
+        // 传入空 refspec 列表时使用远程配置中的全部 fetch refspec
+        remote
+            .fetch::<&str>(&[], Some(&mut fetch_options), None)
+            .map_err(|e| AppError::Git(format!("获取失败: {}", e)))?;
+    }
+
+    let now = Utc::now().to_rfc3339();
+
+    with_db!(workspace, conn, {
+        conn.execute(
+            "UPDATE git_repositories SET last_sync_at = ?1, updated_at = ?2 WHERE id = ?3",
+            params![now, now, repo_id],
+        )
+        .map_err(|e| AppError::Db(format!("更新同步时间失败: {}", e)))
+    })?;
+
+    Ok(serde_json::json!({
+        "ok": true,
+        "updated_refs": updated_refs,
+        "synced_at": now
+    }))
+}
+
+/// 连接远程并查询其 HEAD 指向的默认分支名
+fn resolve_remote_default_branch(
+    workspace: &Workspace,
+    remote: &mut git2::Remote,
+) -> Result<String, AppError> {
+    let callbacks = auth::make_callbacks(workspace);
+
+    let connection = remote
+        .connect_auth(git2::Direction::Fetch, Some(callbacks), None)
+        .map_err(|e| AppError::Git(format!("连接远程失败: {}", e)))?;
+    let default_ref = connection
+        .default_branch()
+        .map_err(|e| AppError::Git(format!("获取远程默认分支失败: {}", e)))?;
+    let default_ref = default_ref
+        .as_str()
+        .ok_or_else(|| AppError::Validation("远程默认分支名称无效".to_string()))?;
+
+    Ok(default_ref
+        .strip_prefix("refs/heads/")
+        .unwrap_or(default_ref)
+        .to_string())
+}
+
+/// 推送当前分支到 origin（force 为 true 时强制推送）
+#[tauri::command]
+pub async fn git_repo_push(
+    workspace: Workspace,
+    app_handle: AppHandle,
+    repo_id: String,
+    force: bool,
+) -> Result<GitPushResult, AppError> {
+    let prompt = auth::CredentialPrompt::new(app_handle, &repo_id);
+    tokio::task::spawn_blocking(move || push_repo(&workspace, repo_id, force, Some(prompt)))
+        .await
+        .map_err(|e| AppError::Internal(format!("任务执行失败: {}", e)))?
+}
+
+fn push_repo(
+    workspace: &Workspace,
+    repo_id: String,
+    force: bool,
+    prompt: Option<auth::CredentialPrompt>,
+) -> Result<GitPushResult, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+
+    let head = repo
+        .head()
+        .map_err(|e| AppError::Git(format!("获取当前分支失败: {}", e)))?;
+    if !head.is_branch() {
+        return Ok(GitPushResult {
+            ok: false,
+            message: None,
+            pushed_ref: None,
+            synced_at: None,
+            error: Some("当前不在任何分支上，无法推送".to_string()),
+        });
+    }
+    let ref_name = head
+        .name()
+        .ok_or_else(|| AppError::Validation("分支名称无效".to_string()))?
+        .to_string();
+    let refspec = if force {
+        format!("+{}:{}", ref_name, ref_name)
+    } else {
+        format!("{}:{}", ref_name, ref_name)
+    };
+
+    let mut remote = match repo.find_remote("origin") {
+        Ok(r) => r,
+        Err(e) => {
+            return Ok(GitPushResult {
+                ok: false,
+                message: None,
+                pushed_ref: None,
+                synced_at: None,
+                error: Some(format!("找不到远程 origin: {}", e)),
+            });
+        }
+    };
+
+    // 远程拒绝（如非快进）时 libgit2 不一定返回错误，而是通过该回调报告
+    let mut rejected: Option<String> = None;
+    let push_result = {
+        let mut callbacks = auth::make_callbacks_with_prompt(workspace, prompt);
+        callbacks.push_update_reference(|refname, status| {
+            if let Some(msg) = status {
+                rejected = Some(format!("{}: {}", refname, msg));
+            }
+            Ok(())
+        });
+
+        let mut push_options = git2::PushOptions::new();
+        push_options.remote_callbacks(callbacks);
+        remote.push(&[refspec.as_str()], Some(&mut push_options))
+    };
+
+    if let Err(e) = push_result {
+        return Ok(GitPushResult {
+            ok: false,
+            message: None,
+            pushed_ref: Some(ref_name),
+            synced_at: None,
+            error: Some(format!("推送失败: {}", e.message())),
+        });
+    }
+
+    if let Some(reason) = rejected {
+        return Ok(GitPushResult {
+            ok: false,
+            message: None,
+            pushed_ref: Some(ref_name),
+            synced_at: None,
+            error: Some(format!("远程拒绝推送: {}", reason)),
+        });
+    }
+
+    let now = Utc::now().to_rfc3339();
+
+    with_db!(workspace, conn, {
+        conn.execute(
+            "UPDATE git_repositories SET last_sync_at = ?1, updated_at = ?2 WHERE id = ?3",
+            params![now, now, repo_id],
+        )
+        .map_err(|e| AppError::Db(format!("更新同步时间失败: {}", e)))
+    })?;
+
+    Ok(GitPushResult {
+        ok: true,
+        message: Some("推送成功".to_string()),
+        pushed_ref: Some(ref_name),
+        synced_at: Some(now),
+        error: None,
+    })
+}
+
+/// 将 git 时间（含时区偏移）转换为 RFC3339 字符串
+fn git_time_to_rfc3339(time: git2::Time) -> String {
+    let offset = chrono::FixedOffset::east_opt(time.offset_minutes() * 60)
+        .unwrap_or_else(|| chrono::FixedOffset::east_opt(0).unwrap());
+    chrono::DateTime::from_timestamp(time.seconds(), 0)
+        .map(|dt| dt.with_timezone(&offset).to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// 获取提交历史（从 HEAD 开始，最多返回 limit 条）
+#[tauri::command]
+pub fn git_log(
+    workspace: Workspace,
+    repo_id: String,
+    limit: u32,
+) -> Result<Vec<GitCommit>, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| AppError::Git(format!("遍历提交失败: {}", e)))?;
+
+    // 空仓库没有 HEAD，直接返回空列表
+    if revwalk.push_head().is_err() {
+        return Ok(Vec::new());
+    }
+    revwalk
+        .set_sorting(git2::Sort::TIME)
+        .map_err(|e| AppError::Git(format!("遍历提交失败: {}", e)))?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk.take(limit as usize) {
+        let oid = oid.map_err(|e| AppError::Git(format!("遍历提交失败: {}", e)))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| AppError::Git(format!("读取提交失败: {}", e)))?;
+        let author = commit.author();
+        let sha = oid.to_string();
+
+        commits.push(GitCommit {
+            short_sha: sha.chars().take(7).collect(),
+            sha,
+            summary: commit.summary().unwrap_or("").to_string(),
+            author_name: author.name().unwrap_or("").to_string(),
+            author_email: author.email().unwrap_or("").to_string(),
+            timestamp: git_time_to_rfc3339(commit.time()),
+        });
+    }
+
+    Ok(commits)
+}
+
+/// 列出本地和远程分支，并标记当前分支
+#[tauri::command]
+pub fn git_branch_list(workspace: Workspace, repo_id: String) -> Result<GitBranchList, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+
+    let branch_names = |branch_type: git2::BranchType| -> Result<Vec<String>, AppError> {
+        let branches = repo
+            .branches(Some(branch_type))
+            .map_err(|e| AppError::Git(format!("读取分支失败: {}", e)))?;
+        let mut names = Vec::new();
+        for branch in branches {
+            let (branch, _) = branch.map_err(|e| AppError::Git(format!("读取分支失败: {}", e)))?;
+            // 跳过 origin/HEAD 这类符号引用
+            if branch.get().symbolic_target().is_some() {
+                continue;
+            }
+            if let Ok(Some(name)) = branch.name() {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    };
+
+    let local = branch_names(git2::BranchType::Local)?;
+    let remote = branch_names(git2::BranchType::Remote)?;
+    let current = repo
+        .head()
+        .ok()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand().map(String::from));
+
+    Ok(GitBranchList {
+        local,
+        remote,
+        current,
+    })
+}
+
+/// 切换分支（create 为 true 时先基于当前 HEAD 创建分支）
+#[tauri::command]
+pub fn git_checkout_branch(
+    workspace: Workspace,
+    repo_id: String,
+    branch: String,
+    create: bool,
+) -> Result<String, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+
+    if is_worktree_dirty(&repo)? {
+        return Err(AppError::Git("工作区有未提交的更改，请先提交或暂存后再切换分支".into()));
+    }
+
+    if create {
+        let head_commit = repo
+            .head()
+            .and_then(|h| h.peel_to_commit())
+            .map_err(|e| AppError::Validation(format!("当前 HEAD 没有提交，无法创建分支: {}", e)))?;
+        repo.branch(&branch, &head_commit, false)
+            .map_err(|e| AppError::Git(format!("创建分支失败: {}", e)))?;
+    } else if repo.find_branch(&branch, git2::BranchType::Local).is_err() {
+        // 本地不存在时，尝试基于 origin 上的同名分支创建跟踪分支
+        let remote_name = format!("origin/{}", branch);
+        let remote_branch = repo
+            .find_branch(&remote_name, git2::BranchType::Remote)
+            .map_err(|_| AppError::NotFound(format!("分支不存在: {}", branch)))?;
+        let remote_commit = remote_branch
+            .get()
+            .peel_to_commit()
+            .map_err(|e| AppError::Git(format!("读取远程分支失败: {}", e)))?;
+        let mut local_branch = repo
+            .branch(&branch, &remote_commit, false)
+            .map_err(|e| AppError::Git(format!("创建分支失败: {}", e)))?;
+        local_branch
+            .set_upstream(Some(&remote_name))
+            .map_err(|e| AppError::Git(format!("设置上游分支失败: {}", e)))?;
+    }
+
+    let ref_name = format!("refs/heads/{}", branch);
+    let target = repo
+        .revparse_single(&ref_name)
+        .map_err(|e| AppError::NotFound(format!("分支不存在: {} ({})", branch, e)))?;
+
+    repo.checkout_tree(&target, Some(git2::build::CheckoutBuilder::new().safe()))
+        .map_err(|e| AppError::Git(format!("切换分支失败: {}", e)))?;
+    repo.set_head(&ref_name)
+        .map_err(|e| AppError::Git(format!("切换分支失败: {}", e)))?;
+
+    store_branch(&workspace, &repo_id, &branch)?;
+
+    Ok(branch)
+}
+
+/// 保存 HTTPS 访问令牌（令牌按主机存入系统钥匙串，钥匙串不可用时存入工作区数据库；令牌不会通过任何命令返回）
+#[tauri::command]
+pub fn git_credentials_set(
+    workspace: Workspace,
+    host: String,
+    username: String,
+    token: String,
+) -> Result<serde_json::Value, AppError> {
+    let host =
+        auth::url_host(&host).ok_or_else(|| AppError::Validation("主机名不能为空".to_string()))?;
+    if username.trim().is_empty() {
+        return Err(AppError::Validation("用户名不能为空".into()));
+    }
+    if token.is_empty() {
+        return Err(AppError::Validation("访问令牌不能为空".into()));
+    }
+
+    // 先写入钥匙串，保证数据库中有记录的主机都能取到令牌；写入成功时清掉数据库中的旧令牌
+    let in_keyring = auth::store_token(&host, &token)?;
+    let db_token = if in_keyring { None } else { Some(token) };
+
+    let now = Utc::now().to_rfc3339();
+
+    with_db!(workspace, conn, {
+        conn.execute(
+            "INSERT INTO git_credentials (host, username, token, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)
+             ON CONFLICT(host) DO UPDATE SET username = ?2, token = ?3, updated_at = ?4",
+            params![host, username.trim(), db_token, now],
+        )
+        .map_err(|e| AppError::Db(format!("保存凭证失败: {}", e)))?;
+        Ok::<(), AppError>(())
+    })?;
+
+    Ok(serde_json::json!({ "ok": true, "host": host, "inKeyring": in_keyring }))
+}
+
+/// 回应 git://credentials-needed 事件：把本次输入的凭证交给等待中的操作（不保存）
+#[tauri::command]
+pub fn git_credentials_provide(
+    operation_id: String,
+    username: String,
+    secret: String,
+) -> Result<serde_json::Value, AppError> {
+    if !auth::provide_credentials(&operation_id, username, secret) {
+        return Err(AppError::NotFound("没有等待输入凭证的操作，可能已超时".into()));
+    }
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+/// 回应 git://credentials-needed 事件：用户放弃输入，等待中的操作以“用户取消”失败
+#[tauri::command]
+pub fn git_credentials_cancel(operation_id: String) -> Result<serde_json::Value, AppError> {
+    if !auth::cancel_credentials(&operation_id) {
+        return Err(AppError::NotFound("没有等待输入凭证的操作，可能已超时".into()));
+    }
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+/// 检测工作区是否有未提交的更改（暂存区或工作目录中的新增、修改、删除）
+fn is_worktree_dirty(repo: &Repository) -> Result<bool, AppError> {
+    let statuses = repo
+        .statuses(None)
+        .map_err(|e| AppError::Git(format!("获取状态失败: {}", e)))?;
+
+    Ok(statuses.iter().any(|s| {
+        let status = s.status();
+        status.is_index_new()
+            || status.is_index_modified()
+            || status.is_index_deleted()
+            || status.is_wt_new()
+            || status.is_wt_modified()
+            || status.is_wt_deleted()
+    }))
+}
+
+/// 暂存当前工作区修改，返回暂存提交的 OID
+#[tauri::command]
+pub fn git_repo_stash_save(
+    workspace: Workspace,
+    repo_id: String,
+    message: Option<String>,
+) -> Result<serde_json::Value, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let mut repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+    let stasher = commit_signature(&workspace, &repo)?;
+
+    match repo.stash_save(&stasher, message.as_deref().unwrap_or("pm-app stash"), None) {
+        Ok(oid) => Ok(serde_json::json!({
+            "ok": true,
+            "stash_id": oid.to_string()
+        })),
+        // 没有可暂存的修改时 libgit2 返回 NotFound
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(serde_json::json!({
+            "ok": false,
+            "message": "没有需要暂存的修改"
+        })),
+        Err(e) => Err(AppError::Git(format!("暂存失败: {}", e.message()))),
+    }
+}
+
+/// 恢复并删除最近一次暂存
+#[tauri::command]
+pub fn git_repo_stash_pop(
+    workspace: Workspace,
+    repo_id: String,
+) -> Result<serde_json::Value, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let mut repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+
+    match repo.stash_pop(0, None) {
+        Ok(()) => Ok(serde_json::json!({ "ok": true })),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(serde_json::json!({
+            "ok": false,
+            "message": "没有可恢复的暂存"
+        })),
+        Err(e)
+            if matches!(
+                e.code(),
+                git2::ErrorCode::Conflict | git2::ErrorCode::MergeConflict
+            ) =>
+        {
+            Err(AppError::Validation(format!(
+                "恢复暂存时与工作区修改冲突，请先提交或撤销相关文件: {}",
+                e.message()
+            )))
+        }
+        Err(e) => Err(AppError::Git(format!("恢复暂存失败: {}", e.message()))),
+    }
+}
+
+/// 获取工作区变更文件列表（同一文件的暂存区与工作区变更分别列出）
+#[tauri::command]
+pub fn git_repo_changes(
+    workspace: Workspace,
+    repo_id: String,
+) -> Result<Vec<FileChange>, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true);
+    let statuses = repo
+        .statuses(Some(&mut opts))
+        .map_err(|e| AppError::Git(format!("获取状态失败: {}", e)))?;
+
+    let mut changes = Vec::new();
+    for entry in statuses.iter() {
+        let status = entry.status();
+        let fallback = entry.path().unwrap_or_default().to_string();
+
+        let index_change = if status.is_index_new() {
+            Some(FileChangeStatus::New)
+        } else if status.is_index_modified() || status.is_index_typechange() {
+            Some(FileChangeStatus::Modified)
+        } else if status.is_index_deleted() {
+            Some(FileChangeStatus::Deleted)
+        } else if status.is_index_renamed() {
+            Some(FileChangeStatus::Renamed)
+        } else {
+            None
+        };
+        if let Some(change) = index_change {
+            let path = entry
+                .head_to_index()
+                .and_then(|d| d.new_file().path().map(|p| p.to_string_lossy().to_string()))
+                .unwrap_or_else(|| fallback.clone());
+            changes.push(FileChange {
+                path,
+                status: change,
+                staged: true,
+            });
+        }
+
+        let worktree_change = if status.is_wt_new() {
+            Some(FileChangeStatus::Untracked)
+        } else if status.is_wt_modified() || status.is_wt_typechange() {
+            Some(FileChangeStatus::Modified)
+        } else if status.is_wt_deleted() {
+            Some(FileChangeStatus::Deleted)
+        } else if status.is_wt_renamed() {
+            Some(FileChangeStatus::Renamed)
+        } else {
+            None
+        };
+        if let Some(change) = worktree_change {
+            let path = entry
+                .index_to_workdir()
+                .and_then(|d| d.new_file().path().map(|p| p.to_string_lossy().to_string()))
+                .unwrap_or(fallback);
+            changes.push(FileChange {
+                path,
+                status: change,
+                staged: false,
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+/// 获取单个文件的统一 diff（staged 为 true 时比较暂存区与 HEAD，否则比较工作区与暂存区）
+#[tauri::command]
+pub fn git_diff(
+    workspace: Workspace,
+    repo_id: String,
+    path: String,
+    staged: bool,
+) -> Result<String, AppError> {
+    let repo_path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&repo_path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+
+    let mut opts = git2::DiffOptions::new();
+    opts.pathspec(&path)
+        .disable_pathspec_match(true)
+        .include_untracked(true)
+        .show_untracked_content(true);
+
+    let diff = if staged {
+        // 尚无提交时 HEAD 树为空，与空树比较
+        let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))
+    } else {
+        repo.diff_index_to_workdir(None, Some(&mut opts))
+    }
+    .map_err(|e| AppError::Git(format!("生成 diff 失败: {}", e)))?;
+
+    if diff.deltas().any(|d| d.flags().is_binary()) {
+        return Ok("Binary files differ".to_string());
+    }
+
+    let mut patch = String::new();
+    let mut binary = false;
+    diff.print(git2::DiffFormat::Patch, |_, _, line| {
+        match line.origin() {
+            '+' | '-' | ' ' => patch.push(line.origin()),
+            'B' => {
+                binary = true;
+                return false;
+            }
+            _ => {}
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })
+    .or_else(|e| if binary { Ok(()) } else { Err(e) })
+    .map_err(|e| AppError::Git(format!("生成 diff 失败: {}", e)))?;
+
+    if binary {
+        return Ok("Binary files differ".to_string());
+    }
+    Ok(patch)
+}
+
+/// 列出仓库标签（附注标签与轻量标签），按所指提交时间倒序
+#[tauri::command]
+pub fn git_tag_list(workspace: Workspace, repo_id: String) -> Result<Vec<GitTag>, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+    let names = repo
+        .tag_names(None)
+        .map_err(|e| AppError::Git(format!("读取标签失败: {}", e)))?;
+
+    let mut tags: Vec<(i64, GitTag)> = Vec::new();
+    for name in names.iter().flatten() {
+        let object = match repo.revparse_single(&format!("refs/tags/{}", name)) {
+            Ok(object) => object,
+            Err(_) => continue,
+        };
+
+        // 附注标签指向标签对象，轻量标签直接指向提交
+        let message = object
+            .as_tag()
+            .and_then(|tag| tag.message())
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty());
+        let commit = match object.peel_to_commit() {
+            Ok(commit) => commit,
+            Err(_) => continue,
+        };
+
+        tags.push((
+            commit.time().seconds(),
+            GitTag {
+                name: name.to_string(),
+                target_sha: commit.id().to_string(),
+                message,
+            },
+        ));
+    }
+
+    tags.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+    Ok(tags.into_iter().map(|(_, tag)| tag).collect())
+}
+
+/// 列出仓库的所有远程
+#[tauri::command]
+pub fn git_remote_list(workspace: Workspace, repo_id: String) -> Result<Vec<GitRemote>, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+    let names = repo
+        .remotes()
+        .map_err(|e| AppError::Git(format!("读取远程列表失败: {}", e)))?;
+
+    Ok(names
+        .iter()
+        .flatten()
+        .map(|name| GitRemote {
+            name: name.to_string(),
+            url: repo
+                .find_remote(name)
+                .ok()
+                .and_then(|r| r.url().map(String::from)),
+        })
+        .collect())
+}
+
+/// 设置远程地址（远程不存在时创建），修改 origin 时同步更新 remote_url 列
+#[tauri::command]
+pub fn git_remote_set_url(
+    workspace: Workspace,
+    repo_id: String,
+    name: String,
+    url: String,
+) -> Result<serde_json::Value, AppError> {
+    let name = name.trim().to_string();
+    let url = url.trim().to_string();
+    if name.is_empty() || url.is_empty() {
+        return Err(AppError::Validation("远程名称和地址不能为空".into()));
+    }
+
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+    let created = match repo.find_remote(&name) {
+        Ok(_) => {
+            repo.remote_set_url(&name, &url)
+                .map_err(|e| AppError::Git(format!("设置远程地址失败: {}", e)))?;
+            false
+        }
+        Err(_) => {
+            repo.remote(&name, &url)
+                .map_err(|e| AppError::Git(format!("创建远程失败: {}", e)))?;
+            true
+        }
+    };
+
+    if name == "origin" {
+        let now = Utc::now().to_rfc3339();
+        with_db!(workspace, conn, {
+            conn.execute(
+                "UPDATE git_repositories SET remote_url = ?1, updated_at = ?2 WHERE id = ?3",
+                params![url, now, repo_id],
+            )
+            .map_err(|e| AppError::Db(format!("更新远程地址失败: {}", e)))
+        })?;
+    }
+
+    Ok(serde_json::json!({ "ok": true, "created": created }))
+}
+
+/// 获取 Git 仓库状态（本地）：ahead/behind 与检查时间取自上次检查缓存，dirty 实时计算
+#[tauri::command]
+pub fn git_repo_status_get(
+    workspace: Workspace,
+    repo_id: String,
+) -> Result<GitRepoStatus, AppError> {
+    let (path, cached_json) = with_db!(workspace, conn, {
+        let path = repo_path(conn, &repo_id)?;
+        let cached_json: Option<String> = conn
+            .query_row(
+                "SELECT last_status_json FROM git_repositories WHERE id = ?1",
+                params![repo_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Db(format!("查询仓库失败: {}", e)))?;
+        Ok::<_, AppError>((path, cached_json))
+    })?;
+
+    let mut status = local_repo_status(repo_id, &path)?;
+    if let Some(json) = cached_json {
+        apply_cached_status(&mut status, &json);
+    }
+    Ok(status)
+}
+
+/// 批量刷新时同时检查的仓库数量上限
+const REFRESH_ALL_PARALLELISM: usize = 4;
+
+/// 刷新项目下所有仓库的本地状态，单个仓库失败时记录在其 last_error 中
+#[tauri::command]
+pub async fn git_repo_refresh_all(
+    workspace: Workspace,
+    project_id: String,
+) -> Result<Vec<GitRepoStatus>, AppError> {
+    let repos: Vec<(String, String, Option<String>)> = with_db!(workspace, conn, {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, path, last_status_json FROM git_repositories
+                 WHERE project_id = ?1 ORDER BY sort_order ASC, created_at DESC",
+            )
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+        let rows = stmt
+            .query_map(params![project_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
+        Ok::<_, AppError>(rows)
+    })?;
+
+    tokio::task::spawn_blocking(move || refresh_local_statuses(repos, REFRESH_ALL_PARALLELISM))
+        .await
+        .map_err(|e| AppError::Internal(format!("任务执行失败: {}", e)))
+}
+
+/// 由固定数量的线程依次领取仓库并计算状态，结果按输入顺序返回
+fn refresh_local_statuses(
+    repos: Vec<(String, String, Option<String>)>,
+    parallelism: usize,
+) -> Vec<GitRepoStatus> {
+    let next = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<GitRepoStatus>>> =
+        repos.iter().map(|_| Mutex::new(None)).collect();
+
+    std::thread::scope(|scope| {
+        for _ in 0..parallelism.clamp(1, repos.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some((repo_id, path, cached_json)) = repos.get(index) else {
+                    break;
+                };
+                let status = match local_repo_status(repo_id.clone(), path) {
+                    Ok(mut status) => {
+                        if let Some(json) = cached_json {
+                            apply_cached_status(&mut status, json);
+                        }
+                        status
+                    }
+                    Err(e) => GitRepoStatus {
+                        repo_id: repo_id.clone(),
+                        branch: None,
+                        detached: false,
+                        head_sha: None,
+                        dirty: false,
+                        ahead: 0,
+                        behind: 0,
+                        last_checked_at: Utc::now().to_rfc3339(),
+                        network: NetworkState::Unknown,
+                        last_error: Some(e.to_string()),
+                    },
+                };
+                *results[index].lock().unwrap() = Some(status);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .filter_map(|slot| slot.into_inner().unwrap())
+        .collect()
+}
+
+/// last_status_json 中缓存的远程比较结果
+#[derive(serde::Deserialize)]
+struct CachedRepoStatus {
+    #[serde(default)]
+    ahead: i32,
+    #[serde(default)]
+    behind: i32,
+    last_checked_at: Option<String>,
+}
+
+/// 用缓存的 ahead/behind 与检查时间覆盖本地状态（缓存无法解析时保持不变）
+fn apply_cached_status(status: &mut GitRepoStatus, json: &str) {
+    if let Ok(cached) = serde_json::from_str::<CachedRepoStatus>(json) {
+        status.ahead = cached.ahead;
+        status.behind = cached.behind;
+        if let Some(checked_at) = cached.last_checked_at {
+            status.last_checked_at = checked_at;
+        }
+    }
+}
+
+/// 当前分支相对上游分支（本地的远程跟踪引用）的领先/落后提交数，无上游时为 (0, 0)
+fn upstream_ahead_behind(repo: &Repository) -> (i32, i32) {
+    let counts = (|| {
+        let head = repo.head().ok()?;
+        let branch = git2::Branch::wrap(head);
+        let upstream = branch.upstream().ok()?;
+        let local = branch.get().target()?;
+        let remote = upstream.get().target()?;
+        repo.graph_ahead_behind(local, remote).ok()
+    })();
+    counts
+        .map(|(ahead, behind)| (ahead as i32, behind as i32))
+        .unwrap_or((0, 0))
+}
+
+/// 当前检出的分支名；HEAD 分离或仓库为空时为 None，避免把短 SHA 当作分支名
+fn head_branch_name(repo: &Repository) -> Option<String> {
+    repo.head()
+        .ok()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand().map(String::from))
+}
+
+/// HEAD 分离时返回当前提交的 SHA
+fn detached_head_sha(repo: &Repository) -> Option<String> {
+    if !repo.head_detached().unwrap_or(false) {
+        return None;
+    }
+    repo.head().ok()?.target().map(|oid| oid.to_string())
+}
+
+/// HEAD 指向的提交：(sha, 摘要, 提交时间)，空仓库或 HEAD 无效时为 None
+fn head_commit_info(repo: &Repository) -> Option<(String, String, String)> {
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    let committed_at = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)?;
+    Some((
+        commit.id().to_string(),
+        commit.summary().unwrap_or_default().to_string(),
+        committed_at.to_rfc3339(),
+    ))
+}
+
+/// 缓存仓库的 HEAD 提交信息，供仓库列表直接读取
+fn store_last_commit_with_conn(
+    conn: &rusqlite::Connection,
+    repo_id: &str,
+    commit: Option<&(String, String, String)>,
+) -> Result<(), AppError> {
+    let (sha, summary, at) = match commit {
+        Some((sha, summary, at)) => (Some(sha), Some(summary), Some(at)),
+        None => (None, None, None),
+    };
+    conn.execute(
+        "UPDATE git_repositories SET last_commit_sha = ?1, last_commit_summary = ?2, last_commit_at = ?3 WHERE id = ?4",
+        params![sha, summary, at, repo_id],
+    )
+    .map_err(|e| AppError::Db(format!("更新提交信息失败: {}", e)))?;
+    Ok(())
+}
+
+/// 读取仓库的本地状态（分支、是否有未提交修改），不访问网络
+fn local_repo_status(repo_id: String, path: &str) -> Result<GitRepoStatus, AppError> {
+    let repo = Repository::open(path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+    let branch = head_branch_name(&repo);
+    let head_sha = detached_head_sha(&repo);
+    let dirty = is_worktree_dirty(&repo)?;
+
+    let now = Utc::now().to_rfc3339();
+
+    Ok(GitRepoStatus {
+        repo_id,
+        branch,
+        detached: head_sha.is_some(),
+        head_sha,
+        dirty,
+        ahead: 0,
+        behind: 0,
+        last_checked_at: now,
+        network: NetworkState::Unknown,
+        last_error: None,
+    })
+}
+
+/// 检查 Git 仓库状态（允许网络请求）
+/// 远程探测最长阻塞 NETWORK_PROBE_TIMEOUT，因此在阻塞线程池中执行，避免卡住界面
+#[tauri::command]
+pub async fn git_repo_status_check(
+    workspace: Workspace,
+    repo_id: String,
+) -> Result<GitRepoStatus, AppError> {
+    tokio::task::spawn_blocking(move || check_repo_status(&workspace, repo_id))
+        .await
+        .map_err(|e| AppError::Internal(format!("任务执行失败: {}", e)))?
+}
+
+fn check_repo_status(workspace: &Workspace, repo_id: String) -> Result<GitRepoStatus, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let repo =
+        Repository::open(&path).map_err(|e| AppError::Git(format!("打开仓库失败: {}", e)))?;
+    let branch = head_branch_name(&repo);
+    let head_sha = detached_head_sha(&repo);
+    let dirty = is_worktree_dirty(&repo)?;
+
+    let (network, last_error) = probe_remote_network(workspace, &path);
+
+    let (ahead, behind) = upstream_ahead_behind(&repo);
+    let last_commit = head_commit_info(&repo);
+    let now = Utc::now().to_rfc3339();
+    let status_json =
+        serde_json::json!({ "dirty": dirty, "ahead": ahead, "behind": behind, "last_checked_at": now })
+            .to_string();
+
+    with_db!(workspace, conn, {
+        conn.execute(
+            "UPDATE git_repositories SET last_status_checked_at = ?1, last_status_json = ?2 WHERE id = ?3",
+            params![now, status_json, repo_id],
+        )
+        .ok();
+        store_last_commit_with_conn(conn, &repo_id, last_commit.as_ref()).ok();
+        Ok::<(), AppError>(())
+    })?;
+
+    Ok(GitRepoStatus {
+        repo_id,
+        branch,
+        detached: head_sha.is_some(),
+        head_sha,
+        dirty,
+        ahead,
+        behind,
+        last_checked_at: now,
+        network,
+        last_error,
+    })
+}
+
+/// 命令行浅克隆轮询取消标志的间隔
+const CLI_CLONE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 使用 git 命令行进行浅克隆（libgit2 浅克隆失败时的回退方案）
+/// 取消标志被置位时结束子进程
+fn shallow_clone_with_cli(
+    remote_url: &str,
+    target: &Path,
+    depth: u32,
+    branch: Option<&str>,
+    cancel_flag: &AtomicBool,
+) -> Result<(), AppError> {
+    let git = which::which("git")
+        .map_err(|_| AppError::NotFound("浅克隆失败，且未找到 git 命令行工具".to_string()))?;
+
+    let mut cmd = std::process::Command::new(git);
+    cmd.arg("clone").arg("--depth").arg(depth.to_string());
+    if let Some(branch) = branch {
+        cmd.arg("--branch").arg(branch);
+    }
+    cmd.arg(remote_url)
+        .arg(target)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped());
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| AppError::io(&e, format!("执行 git 命令失败: {}", e)))?;
+    // 单独线程读取 stderr，避免管道写满后阻塞子进程
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = std::io::Read::read_to_end(&mut stderr, &mut buf);
+            buf
+        })
+    });
+
+    let status = loop {
+        if cancel_flag.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AppError::Git("克隆已取消".into()));
+        }
+        match child
+            .try_wait()
+            .map_err(|e| AppError::io(&e, format!("等待 git 命令失败: {}", e)))?
+        {
+            Some(status) => break status,
+            None => std::thread::sleep(CLI_CLONE_POLL_INTERVAL),
+        }
+    };
+
+    if status.success() {
+        Ok(())
+    } else {
+        let stderr = stderr_reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        Err(AppError::Git(format!(
+            "git clone --depth 失败: {}",
+            String::from_utf8_lossy(&stderr).trim()
+        )))
+    }
+}
+
+/// 远程连通性探测超时时间
+const NETWORK_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 探测 origin 远程的连通性
+/// 没有配置远程时返回 Unknown；连接成功（或服务器返回认证错误）视为 Online；
+/// 网络类错误或超时视为 Offline。探测在独立线程中进行，超时后不再等待其结果。
+fn probe_remote_network(workspace: &Workspace, path: &str) -> (NetworkState, Option<String>) {
+    let has_remote = Repository::open(path)
+        .ok()
+        .map(|repo| repo.find_remote("origin").is_ok())
+        .unwrap_or(false);
+    if !has_remote {
+        return (NetworkState::Unknown, None);
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let path = path.to_string();
+    let workspace = workspace.clone();
+
+    std::thread::spawn(move || {
+        let result = Repository::open(&path).and_then(|repo| {
+            let mut remote = repo.find_remote("origin")?;
+            let callbacks = auth::make_callbacks(&workspace);
+            remote
+                .connect_auth(git2::Direction::Fetch, Some(callbacks), None)
+                .map(|_| ())
+        });
+        let _ = tx.send(result);
+    });
+
+    match rx.recv_timeout(NETWORK_PROBE_TIMEOUT) {
+        Ok(Ok(())) => (NetworkState::Online, None),
+        Ok(Err(e)) if e.code() == git2::ErrorCode::Auth => (NetworkState::Online, None),
+        Ok(Err(e)) => match e.class() {
+            git2::ErrorClass::Net
+            | git2::ErrorClass::Os
+            | git2::ErrorClass::Ssl
+            | git2::ErrorClass::Ssh
+            | git2::ErrorClass::Http => (
+                NetworkState::Offline,
+                Some(format!("连接远程失败: {}", e.message())),
+            ),
+            _ => (
+                NetworkState::Unknown,
+                Some(format!("连接远程失败: {}", e.message())),
+            ),
+        },
+        Err(_) => (
+            NetworkState::Offline,
+            Some("连接远程超时".to_string()),
+        ),
+    }
+}
+
+/// 状态监听的轮询间隔
+const STATUS_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 后台状态监听线程
+struct StatusWatcher {
+    stop_signal: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<()>,
+    /// 监听的仓库所属的工作区，关闭工作区时据此停止
+    workspace_path: String,
+}
+
+/// 正在运行的状态监听线程（按 repo_id 索引）
+static STATUS_WATCHERS: once_cell::sync::Lazy<Mutex<HashMap<String, StatusWatcher>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Git 状态监听（启动）：后台定期检查本地状态，分支或 dirty 变化时发送 git://status-changed
+#[tauri::command]
+pub fn git_status_watch_start(
+    workspace: Workspace,
+    app_handle: AppHandle,
+    repo_id: String,
+) -> Result<serde_json::Value, AppError> {
+    let path = with_db!(workspace, conn, { repo_path(conn, &repo_id) })?;
+
+    let mut watchers = STATUS_WATCHERS
+        .lock()
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    if let Some(watcher) = watchers.get(&repo_id) {
+        if !watcher.handle.is_finished() {
+            return Ok(serde_json::json!({ "ok": true, "started": false }));
+        }
+    }
+
+    let stop_signal = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop_signal.clone();
+    let thread_repo_id = repo_id.clone();
+
+    let handle = std::thread::spawn(move || {
+        let mut last: Option<(Option<String>, bool)> = None;
+
+        while !thread_stop.load(Ordering::Relaxed) {
+            if let Ok(status) = local_repo_status(thread_repo_id.clone(), &path) {
+                let current = (status.branch.clone(), status.dirty);
+                if last.as_ref() != Some(&current) {
+                    // 首次读取只记录基线，不发送事件
+                    if last.is_some() {
+                        let _ = app_handle.emit("git://status-changed", &status);
+                    }
+                    last = Some(current);
+                }
+            }
+
+            // 分段休眠，使停止信号能被及时响应
+            let mut waited = Duration::ZERO;
+            while waited < STATUS_WATCH_INTERVAL && !thread_stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(200));
+                waited += Duration::from_millis(200);
+            }
+        }
+    });
+
+    watchers.insert(
+        repo_id,
+        StatusWatcher {
+            stop_signal,
+            handle,
+            workspace_path: workspace.path().to_string(),
+        },
+    );
+
+    Ok(serde_json::json!({ "ok": true, "started": true }))
+}
+
+/// Git 状态监听（停止）：未指定 repo_id 时停止当前工作区的全部监听
+#[tauri::command]
+pub fn git_status_watch_stop(
+    workspace: Workspace,
+    repo_id: Option<String>,
+) -> Result<serde_json::Value, AppError> {
+    let stopped: Vec<StatusWatcher> = {
+        let mut watchers = STATUS_WATCHERS
+            .lock()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        match repo_id {
+            Some(id) => watchers.remove(&id).into_iter().collect(),
+            None => take_workspace_watchers(&mut watchers, workspace.path()),
+        }
+    };
+
+    let count = stopped.len();
+    join_status_watchers(stopped);
+
+    Ok(serde_json::json!({ "ok": true, "stopped": count }))
+}
+
+/// 停止属于指定工作区的全部状态监听线程
+pub fn stop_status_watchers_for_workspace(workspace_path: &str) {
+    let stopped: Vec<StatusWatcher> = {
+        let mut watchers = match STATUS_WATCHERS.lock() {
+            Ok(watchers) => watchers,
+            Err(_) => return,
+        };
+        take_workspace_watchers(&mut watchers, workspace_path)
+    };
+    join_status_watchers(stopped);
+}
+
+/// 从表中取出属于指定工作区的监听
+fn take_workspace_watchers(
+    watchers: &mut HashMap<String, StatusWatcher>,
+    workspace_path: &str,
+) -> Vec<StatusWatcher> {
+    let ids: Vec<String> = watchers
+        .iter()
+        .filter(|(_, w)| w.workspace_path == workspace_path)
+        .map(|(id, _)| id.clone())
+        .collect();
+    ids.iter().filter_map(|id| watchers.remove(id)).collect()
+}
+
+/// 通知监听线程退出并等待其结束
+fn join_status_watchers(watchers: Vec<StatusWatcher>) {
+    for watcher in &watchers {
+        watcher.stop_signal.store(true, Ordering::Relaxed);
+    }
+    for watcher in watchers {
+        let _ = watcher.handle.join();
+    }
+}
+
+/// 设置仓库的自定义显示名称（不修改磁盘目录，传入空值时清除）
+#[tauri::command]
+pub fn git_repo_rename(
+    workspace: Workspace,
+    repo_id: String,
+    custom_name: Option<String>,
+) -> Result<GitRepository, AppError> {
+    let custom_name = custom_name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    let now = Utc::now().to_rfc3339();
+
+    with_db!(workspace, conn, {
+        let affected = conn
+            .execute(
+                "UPDATE git_repositories SET custom_name = ?1, updated_at = ?2 WHERE id = ?3",
+                params![custom_name, now, repo_id],
+            )
+            .map_err(|e| AppError::Db(format!("更新仓库名称失败: {}", e)))?;
+        if affected == 0 {
+            return Err(AppError::NotFound("仓库不存在".into()));
+        }
+
+        conn.query_row(
+            "SELECT id, project_id, name, path, folder, remote_url, branch, description, last_sync_at, last_status_checked_at, ide_override_json, sort_order, custom_name,
+             last_commit_sha, last_commit_summary, last_commit_at
+             FROM git_repositories WHERE id = ?1",
+            params![repo_id],
+            map_git_repository_row,
+        )
+        .map_err(|e| AppError::Db(format!("查询仓库失败: {}", e)))
+    })
+}
+
+/// 设置仓库的一行说明（传入空值时清除）
+#[tauri::command]
+pub fn git_repo_set_description(
+    workspace: Workspace,
+    repo_id: String,
+    description: Option<String>,
+) -> Result<GitRepository, AppError> {
+    let description = description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty());
+    let now = Utc::now().to_rfc3339();
+
+    with_db!(workspace, conn, {
+        let affected = conn
+            .execute(
+                "UPDATE git_repositories SET description = ?1, updated_at = ?2 WHERE id = ?3",
+                params![description, now, repo_id],
+            )
+            .map_err(|e| AppError::Db(format!("更新仓库说明失败: {}", e)))?;
+        if affected == 0 {
+            return Err(AppError::NotFound("仓库不存在".into()));
+        }
+
+        conn.query_row(
+            "SELECT id, project_id, name, path, folder, remote_url, branch, description, last_sync_at, last_status_checked_at, ide_override_json, sort_order, custom_name,
+             last_commit_sha, last_commit_summary, last_commit_at
+             FROM git_repositories WHERE id = ?1",
+            params![repo_id],
+            map_git_repository_row,
+        )
+        .map_err(|e| AppError::Db(format!("查询仓库失败: {}", e)))
+    })
+}
+
+/// 删除 Git 仓库（delete_files 为 true 时同时删除本地目录，目录已不存在时忽略）
+#[tauri::command]
+pub fn git_repo_delete(
+    workspace: Workspace,
+    repo_id: String,
+    delete_files: bool,
+) -> Result<serde_json::Value, AppError> {
+    let (path, name): (String, String) = with_db!(workspace, conn, {
+        let path = repo_path(conn, &repo_id)?;
+        let name: String = conn
+            .query_row(
+                "SELECT name FROM git_repositories WHERE id = ?1",
+                params![repo_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Db(format!("读取仓库失败: {}", e)))?;
+        Ok::<_, AppError>((path, name))
+    })?;
+
+    // 先删除本地目录，失败时保留仓库记录，避免目录成为应用中看不到的孤儿
+    if delete_files {
+        let repo_path = Path::new(&path);
+        if repo_path.exists() {
+            fs::remove_dir_all(repo_path).map_err(|e| AppError::io(&e, format!("删除本地目录失败: {}", e)))?;
+        }
+    }
+
+    with_db!(workspace, conn, {
+        conn.execute("DELETE FROM git_repositories WHERE id = ?1", params![repo_id])
+            .map_err(|e| AppError::Db(format!("删除仓库记录失败: {}", e)))
+    })?;
+
+    Ok(serde_json::json!({
+        "ok": true,
+        "deleted": name,
+        "local_deleted": delete_files
+    }))
+}
+
+/// 扫描 code 目录下的 Git 仓库并自动导入数据库
+#[tauri::command]
+pub fn git_repo_scan(
+    workspace: Workspace,
+    project_id: String,
+) -> Result<serde_json::Value, AppError> {
+    let project: crate::types::Project = with_db!(workspace, conn, {
+        conn.query_row(
+            "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at, archived, sort_order, created_at FROM projects WHERE id = ?1",
+            params![project_id],
+            map_project_row,
+        )
+        .map_err(|e| AppError::lookup(&e, format!("项目不存在：{}", e)))
+    })?;
+
+    // 扫描期间不持有数据库锁，只在读取已登记路径和写入结果时短暂获取
+    let existing_paths = with_db!(workspace, conn, { existing_repo_paths(conn, &project.id) })?;
+    let discovered = discover_git_repos(Path::new(&project.project_path), &existing_paths)?;
+    let scanned = with_db!(workspace, conn, {
+        insert_discovered_repos(conn, &project.id, discovered)
+    })?;
+
+    Ok(serde_json::json!({
+        "ok": true,
+        "scanned": scanned
+    }))
+}
+
+/// 将项目目录中已有的 Git 仓库登记到数据库（不克隆）
+#[tauri::command]
+pub fn git_repo_import(
+    workspace: Workspace,
+    project_id: String,
+    relative_path: String,
+) -> Result<GitRepository, AppError> {
+    let project_path: String = with_db!(workspace, conn, {
+        conn.query_row(
+            "SELECT project_path FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::lookup(&e, format!("项目不存在: {}", e)))
+    })?;
+
+    let project_root = Path::new(&project_path)
+        .canonicalize()
+        .map_err(|e| AppError::io(&e, format!("项目目录不存在: {}", e)))?;
+    let repo_path = project_root
+        .join(relative_path.trim())
+        .canonicalize()
+        .map_err(|e| AppError::io(&e, format!("目录不存在: {}", e)))?;
+    if !repo_path.starts_with(&project_root) || repo_path == project_root {
+        return Err(AppError::Validation("仓库路径必须位于项目目录内".into()));
+    }
+
+    let repo = Repository::open(&repo_path)
+        .map_err(|_| AppError::Validation("该目录不是 Git 仓库".to_string()))?;
+    let branch = head_branch_name(&repo);
+    let remote_url = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|r| r.url().map(String::from));
+
+    let path_str = repo_path.to_string_lossy().to_string();
+    // 已登记的路径可能未经规范化（如克隆时直接拼接），两种形式都需比较
+    let raw_path = Path::new(&project_path)
+        .join(relative_path.trim())
+        .to_string_lossy()
+        .to_string();
+    let name = repo_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let folder = repo_path
+        .parent()
+        .and_then(|parent| parent.strip_prefix(&project_root).ok())
+        .and_then(|rel| rel.components().next())
+        .and_then(|c| c.as_os_str().to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "root".to_string());
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    let sort_order: i32 = with_db!(workspace, conn, {
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM git_repositories WHERE project_id = ?1 AND (path = ?2 OR path = ?3)",
+                params![project_id, path_str, raw_path],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count > 0)
+            .map_err(|e| AppError::Db(format!("查询仓库失败: {}", e)))?;
+        if exists {
+            return Err(AppError::Validation("该仓库已添加到项目中".into()));
+        }
+
+        let max_sort: Option<i32> = conn
+            .query_row(
+                "SELECT MAX(sort_order) FROM git_repositories WHERE project_id = ?1",
+                params![project_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(None);
+        let next_sort = max_sort.unwrap_or(0) + 1;
+
+        conn.execute(
+            "INSERT INTO git_repositories (id, project_id, name, path, folder, remote_url, branch, created_at, updated_at, sort_order)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![id, project_id, name, path_str, folder, remote_url, branch, now, now, next_sort],
+        )
+        .map_err(|e| AppError::Db(format!("保存仓库失败: {}", e)))?;
+        Ok::<i32, AppError>(next_sort)
+    })?;
+
+    Ok(GitRepository {
+        id,
+        project_id,
+        name,
+        path: path_str,
+        folder: Some(folder),
+        remote_url,
+        branch,
+        description: None,
+        last_sync_at: None,
+        last_status_checked_at: None,
+        ide_override: None,
+        sort_order: Some(sort_order),
+        custom_name: None,
+        last_commit_sha: None,
+        last_commit_summary: None,
+        last_commit_at: None,
+    })
+}
+
+/// 内部扫描函数（复用已有连接）—— 由 dir_type.rs 调用
+pub fn git_repo_scan_internal(
+    conn: &rusqlite::Connection,
+    project_id: String,
+    project_path: &Path,
+) -> Result<serde_json::Value, AppError> {
+    git_repo_scan_with_conn(conn, project_id, project_path)
+}
+
+/// 内部扫描函数（带连接参数）
+pub fn git_repo_scan_with_conn(
+    conn: &rusqlite::Connection,
+    project_id: String,
+    project_path: &Path,
+) -> Result<serde_json::Value, AppError> {
+    let existing_paths = existing_repo_paths(conn, &project_id)?;
+    let discovered = discover_git_repos(project_path, &existing_paths)?;
+    let scanned = insert_discovered_repos(conn, &project_id, discovered)?;
+
+    Ok(serde_json::json!({
+        "ok": true,
+        "scanned": scanned
+    }))
+}
+
+/// 项目目录中发现的、尚未登记的 Git 仓库
+struct DiscoveredRepo {
+    name: String,
+    path: String,
+    folder: String,
+    remote_url: Option<String>,
+    branch: Option<String>,
+}
+
+/// 项目已登记的仓库路径
+fn existing_repo_paths(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+) -> Result<std::collections::HashSet<String>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT path FROM git_repositories WHERE project_id = ?1")
+        .map_err(|e| AppError::Db(format!("查询仓库失败：{}", e)))?;
+
+    let paths = stmt
+        .query_map(params![project_id], |row| row.get(0))
+        .map_err(|e| AppError::Db(format!("读取仓库失败：{}", e)))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(paths)
+}
+
+/// 扫描项目目录的一级子目录中的 Git 仓库（只访问文件系统，不需要数据库连接）
+fn discover_git_repos(
+    project_path: &Path,
+    existing_paths: &std::collections::HashSet<String>,
+) -> Result<Vec<DiscoveredRepo>, AppError> {
+    if !project_path.exists() || !project_path.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let entries =
+        fs::read_dir(project_path).map_err(|e| AppError::io(&e, format!("读取目录失败：{}", e)))?;
+
+    let mut discovered = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if dir_name.starts_with('.') {
+            continue;
+        }
+
+        let path_str = path.to_string_lossy().to_string();
+        if existing_paths.contains(&path_str) {
+            continue;
+        }
+
+        if !path.join(".git").exists() {
+            continue;
+        }
+
+        let repo = match Repository::open(&path) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let remote_url = repo
+            .find_remote("origin")
+            .ok()
+            .and_then(|r| r.url().map(String::from));
+
+        let branch = head_branch_name(&repo);
+
+        let folder = path
+            .parent()
+            .and_then(|parent| parent.strip_prefix(project_path).ok())
+            .and_then(|rel| rel.components().next())
+            .and_then(|c| c.as_os_str().to_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "root".to_string());
+
+        discovered.push(DiscoveredRepo {
+            name,
+            path: path_str,
+            folder,
+            remote_url,
+            branch,
+        });
+    }
+    Ok(discovered)
+}
+
+/// 登记扫描到的仓库，返回 "名称 (目录)" 列表；期间已被其他操作登记的路径会跳过
+fn insert_discovered_repos(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+    repos: Vec<DiscoveredRepo>,
+) -> Result<Vec<String>, AppError> {
+    let now = Utc::now().to_rfc3339();
+    let mut scanned = Vec::new();
+
+    for repo in repos {
+        let exists: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM git_repositories WHERE project_id = ?1 AND path = ?2)",
+                params![project_id, repo.path],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Db(format!("查询仓库失败：{}", e)))?;
+        if exists {
+            continue;
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO git_repositories (id, project_id, name, path, folder, remote_url, branch, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![id, project_id, repo.name, repo.path, repo.folder, repo.remote_url, repo.branch, now, now],
+        )
+        .map_err(|e| AppError::Db(format!("保存仓库失败：{}", e)))?;
+
+        scanned.push(format!("{} ({})", repo.name, repo.folder));
+    }
+    Ok(scanned)
+}
+
+/// 监视目录变化（顶级文件/文件夹的创建和删除）
+#[tauri::command]
+pub fn watch_directory(
+    app_handle: AppHandle,
+    watcher_state: State<'_, WatcherState>,
+    directory: String,
+) -> Result<String, AppError> {
+    use notify::{RecursiveMode, recommended_watcher};
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+
+    let path = PathBuf::from(&directory);
+
+    if !path.exists() {
+        return Err(AppError::NotFound("目录不存在".into()));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = recommended_watcher(tx)
+        .map_err(|e| AppError::Io(format!("文件监视器创建失败: {}", e)))?;
+
+    // 仅监视顶级目录，非递归
+    watcher
+        .watch(&path, RecursiveMode::NonRecursive)
+        .map_err(|e| AppError::Io(format!("开始监视失败: {}", e)))?;
+
+    let watch_id = uuid::Uuid::new_v4().to_string();
+    let watch_id_clone = watch_id.clone();
+    let stop_signal = Arc::new(AtomicBool::new(false));
+    let stop_signal_clone = stop_signal.clone();
+
+    // CRITICAL FIX: Store stop signal in global state
+    // This allows unwatch_directory to signal the thread to stop
+    {
+        let mut signals = watcher_state.watch_signals.lock().unwrap();
+        signals.insert(watch_id.clone(), stop_signal);
+    }
+
+    // 在后台线程中处理事件
+    // CRITICAL FIX: Watcher is moved into thread closure, preventing premature drop
+    std::thread::spawn(move || {
+        for event in rx {
+            // Check if we should stop watching
+            if stop_signal_clone.load(std::sync::atomic::Ordering::Relaxed) {
+                break;
+            }
+
+            match event {
+                Ok(notify::Event {
+                    kind: notify::EventKind::Create(_) | notify::EventKind::Remove(_),
+                    ..
+                }) => {
+                    // 向前端发送事件
+                    let _ = app_handle.emit("git:directory:changed", &watch_id_clone);
+                }
+                _ => {}
+            }
+        }
+        // watcher is dropped here when thread ends, proper cleanup
+        drop(watcher);
+    });
+
+    Ok(watch_id)
+}
+
+/// 停止监视目录
+#[tauri::command]
+pub fn unwatch_directory(
+    watcher_state: State<'_, WatcherState>,
+    watch_id: String,
+) -> Result<(), AppError> {
+    // CRITICAL FIX: Actually signal the watcher thread to stop
+    let mut signals = watcher_state.watch_signals.lock().unwrap();
+    if let Some(stop_signal) = signals.remove(&watch_id) {
+        stop_signal.store(true, std::sync::atomic::Ordering::Relaxed);
+        Ok(())
+    } else {
+        Err(AppError::NotFound("Watch ID not found".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(url: &str) -> Result<String, AppError> {
+        git_extract_repo_name(url.to_string())
+    }
+
+    #[test]
+    fn test_commit_signature_prefers_workspace_author() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Repo User").unwrap();
+        config.set_str("user.email", "repo@example.com").unwrap();
+
+        let sig = commit_signature_with(&repo, None).unwrap();
+        assert_eq!(sig.name(), Some("Repo User"));
+
+        let author = Some(("Workspace User".to_string(), "ws@example.com".to_string()));
+        let sig = commit_signature_with(&repo, author).unwrap();
+        assert_eq!(sig.name(), Some("Workspace User"));
+        assert_eq!(sig.email(), Some("ws@example.com"));
+    }
+
+    #[test]
+    fn test_head_commit_info_tolerates_empty_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert!(head_commit_info(&repo).is_none());
+
+        let sig = git2::Signature::now("Tester", "t@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, "Initial commit\n\nbody", &tree, &[])
+            .unwrap();
+
+        let (sha, summary, _) = head_commit_info(&repo).unwrap();
+        assert_eq!(sha, oid.to_string());
+        assert_eq!(summary, "Initial commit");
+    }
+
+    #[test]
+    fn test_refresh_local_statuses_keeps_order_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let repos: Vec<(String, String, Option<String>)> = (0..5)
+            .map(|i| {
+                let path = dir.path().join(format!("repo{}", i));
+                if i != 2 {
+                    Repository::init(&path).unwrap();
+                }
+                (format!("r{}", i), path.to_string_lossy().to_string(), None)
+            })
+            .collect();
+
+        let statuses = refresh_local_statuses(repos, 2);
+        let ids: Vec<&str> = statuses.iter().map(|s| s.repo_id.as_str()).collect();
+        assert_eq!(ids, ["r0", "r1", "r2", "r3", "r4"]);
+        assert!(statuses[2].last_error.is_some());
+        assert!(statuses.iter().enumerate().all(|(i, s)| i == 2 || s.last_error.is_none()));
+    }
+
+    #[test]
+    fn test_detached_head_has_no_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let sig = git2::Signature::now("Tester", "t@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let oid = repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[]).unwrap();
+        assert!(head_branch_name(&repo).is_some());
+        assert!(detached_head_sha(&repo).is_none());
+
+        repo.set_head_detached(oid).unwrap();
+        assert!(head_branch_name(&repo).is_none());
+        assert_eq!(detached_head_sha(&repo), Some(oid.to_string()));
+
+        let status = local_repo_status("r1".to_string(), dir.path().to_str().unwrap()).unwrap();
+        assert!(status.detached);
+        assert!(status.branch.is_none());
+    }
+
+    #[test]
+    fn test_resolve_pull_target_follows_branch_upstream() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert!(resolve_pull_target(&repo).unwrap().is_none());
+
+        let sig = git2::Signature::now("Tester", "t@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[]).unwrap();
+        let branch = head_branch_name(&repo).unwrap();
+
+        let target = resolve_pull_target(&repo).unwrap().unwrap();
+        assert_eq!(
+            target.refspec(),
+            format!("+refs/heads/{0}:refs/remotes/origin/{0}", branch)
+        );
+
+        repo.remote("upstream", "https://example.com/repo.git").unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str(&format!("branch.{}.remote", branch), "upstream").unwrap();
+        config.set_str(&format!("branch.{}.merge", branch), "refs/heads/trunk").unwrap();
+
+        let target = resolve_pull_target(&repo).unwrap().unwrap();
+        assert_eq!(target.remote, "upstream");
+        assert_eq!(target.local_branch, branch);
+        assert_eq!(target.refspec(), "+refs/heads/trunk:refs/remotes/upstream/trunk");
+
+        let head = repo.head().unwrap().target().unwrap();
+        repo.set_head_detached(head).unwrap();
+        assert!(matches!(
+            resolve_pull_target(&repo),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_clone_cancel_flips_registered_flag() {
+        let cancellation = CloneCancellation::register(Some("op-1".to_string()));
+        assert!(!cancellation.is_cancelled());
+
+        let result = git_clone_cancel("op-1".to_string()).unwrap();
+        assert_eq!(result["cancelled"], true);
+        assert!(cancellation.is_cancelled());
+
+        drop(cancellation);
+        let result = git_clone_cancel("op-1".to_string()).unwrap();
+        assert_eq!(result["cancelled"], false);
+    }
+
+    #[test]
+    fn test_apply_cached_status() {
+        let mut status = GitRepoStatus {
+            repo_id: "r1".to_string(),
+            branch: Some("main".to_string()),
+            detached: false,
+            head_sha: None,
+            dirty: true,
+            ahead: 0,
+            behind: 0,
+            last_checked_at: "now".to_string(),
+            network: NetworkState::Unknown,
+            last_error: None,
+        };
+
+        apply_cached_status(&mut status, "not json");
+        assert_eq!((status.ahead, status.behind), (0, 0));
+
+        apply_cached_status(
+            &mut status,
+            r#"{"dirty":false,"ahead":2,"behind":5,"last_checked_at":"2024-01-01T00:00:00Z"}"#,
+        );
+        assert_eq!((status.ahead, status.behind), (2, 5));
+        assert_eq!(status.last_checked_at, "2024-01-01T00:00:00Z");
+        assert!(status.dirty);
+    }
+
+    #[test]
+    fn test_extract_repo_name_https() {
+        assert_eq!(extract("https://github.com/org/repo.git").unwrap(), "repo");
+        assert_eq!(extract("https://github.com/org/repo").unwrap(), "repo");
+        assert_eq!(extract("https://github.com/org/repo/").unwrap(), "repo");
+        assert_eq!(extract("https://github.com/org/repo.git/").unwrap(), "repo");
+    }
+
+    #[test]
+    fn test_extract_repo_name_ssh() {
+        assert_eq!(extract("git@github.com:org/repo.git").unwrap(), "repo");
+        assert_eq!(extract("git@github.com:repo.git").unwrap(), "repo");
+        assert_eq!(extract("ssh://git@github.com/org/repo.git").unwrap(), "repo");
+    }
+
+    #[test]
+    fn test_extract_repo_name_keeps_dots_in_name() {
+        assert_eq!(extract("https://github.com/org/my.repo.git").unwrap(), "my.repo");
+        assert_eq!(extract("  https://gitlab.com/group/sub/repo-name  ").unwrap(), "repo-name");
+    }
+
+    #[test]
+    fn test_extract_repo_name_invalid() {
+        assert!(extract("").is_err());
+        assert!(extract("   ").is_err());
+        assert!(extract("not a url").is_err());
+        assert!(extract("https://github.com").is_err());
+        assert!(extract("https://github.com/").is_err());
+        assert!(extract("https://github.com/org/.git").is_err());
+    }
+}
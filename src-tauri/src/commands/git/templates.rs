@@ -0,0 +1,146 @@
+//! 内置的 .gitignore 模板
+
+const NODE: &str = "\
+# Dependencies
+node_modules/
+.pnpm-store/
+
+# Build output
+dist/
+build/
+.next/
+.nuxt/
+coverage/
+
+# Logs
+npm-debug.log*
+yarn-debug.log*
+yarn-error.log*
+pnpm-debug.log*
+
+# Environment
+.env
+.env.local
+.env.*.local
+
+# Editor / OS
+.vscode/
+.idea/
+.DS_Store
+";
+
+const RUST: &str = "\
+# Build output
+/target/
+**/*.rs.bk
+
+# Editor / OS
+.vscode/
+.idea/
+.DS_Store
+";
+
+const PYTHON: &str = "\
+# Byte-compiled files
+__pycache__/
+*.py[cod]
+
+# Virtual environments
+.venv/
+venv/
+env/
+
+# Packaging
+build/
+dist/
+*.egg-info/
+
+# Tooling
+.pytest_cache/
+.mypy_cache/
+.coverage
+htmlcov/
+
+# Environment
+.env
+
+# Editor / OS
+.vscode/
+.idea/
+.DS_Store
+";
+
+const JAVA: &str = "\
+# Build output
+target/
+build/
+out/
+*.class
+
+# Gradle
+.gradle/
+
+# Packages
+*.jar
+*.war
+
+# Editor / OS
+.idea/
+*.iml
+.vscode/
+.DS_Store
+";
+
+const GO: &str = "\
+# Binaries
+*.exe
+*.dll
+*.so
+*.dylib
+/bin/
+
+# Test output
+*.test
+*.out
+
+# Vendored dependencies
+vendor/
+
+# Editor / OS
+.vscode/
+.idea/
+.DS_Store
+";
+
+/// 所有内置模板名称
+pub const TEMPLATE_NAMES: &[&str] = &["node", "rust", "python", "java", "go"];
+
+/// 按名称（不区分大小写）获取 .gitignore 模板内容
+pub fn gitignore_template(name: &str) -> Option<&'static str> {
+    match name.trim().to_lowercase().as_str() {
+        "node" => Some(NODE),
+        "rust" => Some(RUST),
+        "python" => Some(PYTHON),
+        "java" => Some(JAVA),
+        "go" => Some(GO),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_template_names_resolve() {
+        for name in TEMPLATE_NAMES {
+            assert!(gitignore_template(name).is_some(), "missing template {}", name);
+        }
+    }
+
+    #[test]
+    fn test_template_lookup_is_case_insensitive() {
+        assert_eq!(gitignore_template(" Rust "), gitignore_template("rust"));
+        assert!(gitignore_template("cobol").is_none());
+    }
+}
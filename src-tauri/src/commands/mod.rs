@@ -5,7 +5,9 @@ pub mod filesystem;
 pub mod git;
 pub mod module;
 pub mod project;
+pub mod search;
 pub mod template;
+pub mod theme;
 pub mod workspace;
 pub mod task;
 
@@ -15,6 +17,8 @@ pub use filesystem::*;
 pub use git::*;
 pub use module::*;
 pub use project::*;
+pub use search::*;
 pub use template::*;
+pub use theme::*;
 pub use workspace::*;
 pub use task::*;
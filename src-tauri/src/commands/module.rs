@@ -1,3 +1,5 @@
+use crate::commands::workspace::Workspace;
+use crate::error::AppError;
 use crate::types::*;
 use crate::with_db;
 use crate::commands::db_helpers::map_module_row;
@@ -6,21 +8,21 @@ use rusqlite::params;
 
 /// 列出所有模块
 #[tauri::command]
-pub fn module_list() -> Result<Vec<Module>, String> {
-    with_db!(conn, {
+pub fn module_list(workspace: Workspace) -> Result<Vec<Module>, AppError> {
+    with_db!(workspace, conn, {
         let mut stmt = conn
             .prepare(
                 "SELECT id, key, name, description, version, capabilities_json, config_schema_json,
                         default_config_json, icon, is_built_in, created_at, updated_at
                  FROM modules ORDER BY is_built_in DESC, name",
             )
-            .map_err(|e| format!("查询失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
 
         let modules = stmt
             .query_map([], map_module_row)
-            .map_err(|e| format!("查询失败: {}", e))?
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
             .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("读取数据失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
 
         // 如果数据库中没有模块，返回内置模块
         if modules.is_empty() {
@@ -256,8 +258,8 @@ fn get_builtin_modules() -> Vec<Module> {
 
 /// 根据 ID 获取模块
 #[tauri::command]
-pub fn module_get(id: String) -> Result<Module, String> {
-    with_db!(conn, {
+pub fn module_get(workspace: Workspace, id: String) -> Result<Module, AppError> {
+    with_db!(workspace, conn, {
         let result: Result<Module, _> = conn.query_row(
             "SELECT id, key, name, description, version, capabilities_json, config_schema_json,
                     default_config_json, icon, is_built_in, created_at, updated_at
@@ -274,7 +276,7 @@ pub fn module_get(id: String) -> Result<Module, String> {
                 builtin
                     .into_iter()
                     .find(|m| m.id == id)
-                    .ok_or_else(|| format!("模块不存在: {}", id))
+                    .ok_or_else(|| AppError::NotFound(format!("模块不存在: {}", id)))
             }
         }
     })
@@ -282,8 +284,8 @@ pub fn module_get(id: String) -> Result<Module, String> {
 
 /// 根据 key 获取模块
 #[tauri::command]
-pub fn module_get_by_key(key: String) -> Result<Module, String> {
-    with_db!(conn, {
+pub fn module_get_by_key(workspace: Workspace, key: String) -> Result<Module, AppError> {
+    with_db!(workspace, conn, {
         let result: Result<Module, _> = conn.query_row(
             "SELECT id, key, name, description, version, capabilities_json, config_schema_json,
                     default_config_json, icon, is_built_in, created_at, updated_at
@@ -300,7 +302,7 @@ pub fn module_get_by_key(key: String) -> Result<Module, String> {
                 builtin
                     .into_iter()
                     .find(|m| m.key == key)
-                    .ok_or_else(|| format!("模块不存在: {}", key))
+                    .ok_or_else(|| AppError::NotFound(format!("模块不存在: {}", key)))
             }
         }
     })
@@ -308,17 +310,17 @@ pub fn module_get_by_key(key: String) -> Result<Module, String> {
 
 /// 创建模块
 #[tauri::command]
-pub fn module_create(input: serde_json::Value) -> Result<Module, String> {
+pub fn module_create(workspace: Workspace, input: serde_json::Value) -> Result<Module, AppError> {
     let key = input
         .get("key")
         .and_then(|v| v.as_str())
-        .ok_or("缺少模块 key")?
+        .ok_or_else(|| AppError::Validation("缺少模块 key".to_string()))?
         .to_string();
 
     let name = input
         .get("name")
         .and_then(|v| v.as_str())
-        .ok_or("缺少模块名称")?
+        .ok_or_else(|| AppError::Validation("缺少模块名称".to_string()))?
         .to_string();
 
     let description = input
@@ -403,13 +405,13 @@ pub fn module_create(input: serde_json::Value) -> Result<Module, String> {
     let now = Utc::now().to_rfc3339();
 
     let capabilities_json = serde_json::to_string(&capabilities)
-        .map_err(|e| format!("序列化失败: {}", e))?;
+        .map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
     let config_schema_json = serde_json::to_string(&config_schema)
-        .map_err(|e| format!("序列化失败: {}", e))?;
+        .map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
     let default_config_json = serde_json::to_string(&default_config)
-        .map_err(|e| format!("序列化失败: {}", e))?;
+        .map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute(
             "INSERT INTO modules (id, key, name, description, version, capabilities_json,
                                  config_schema_json, default_config_json, icon, is_built_in, created_at, updated_at)
@@ -428,7 +430,7 @@ pub fn module_create(input: serde_json::Value) -> Result<Module, String> {
                 now.clone()
             ],
         )
-        .map_err(|e| format!("创建模块失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("创建模块失败: {}", e)))?;
     });
 
     Ok(Module {
@@ -449,13 +451,17 @@ pub fn module_create(input: serde_json::Value) -> Result<Module, String> {
 
 /// 更新模块
 #[tauri::command]
-pub fn module_update(id: String, patch: serde_json::Value) -> Result<Module, String> {
+pub fn module_update(
+    workspace: Workspace,
+    id: String,
+    patch: serde_json::Value,
+) -> Result<Module, AppError> {
     // 获取当前模块
-    let module = module_get(id.clone())?;
+    let module = module_get(workspace.clone(), id.clone())?;
 
     // 不能修改内置模块
     if module.is_built_in {
-        return Err("不能修改内置模块".to_string());
+        return Err(AppError::Validation("不能修改内置模块".into()));
     }
 
     let name = patch
@@ -538,14 +544,14 @@ pub fn module_update(id: String, patch: serde_json::Value) -> Result<Module, Str
 
     let now = Utc::now().to_rfc3339();
 
-    let capabilities_json =
-        serde_json::to_string(&capabilities).map_err(|e| format!("序列化失败: {}", e))?;
-    let config_schema_json =
-        serde_json::to_string(&config_schema).map_err(|e| format!("序列化失败: {}", e))?;
-    let default_config_json =
-        serde_json::to_string(&default_config).map_err(|e| format!("序列化失败: {}", e))?;
+    let capabilities_json = serde_json::to_string(&capabilities)
+        .map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
+    let config_schema_json = serde_json::to_string(&config_schema)
+        .map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
+    let default_config_json = serde_json::to_string(&default_config)
+        .map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute(
             "UPDATE modules SET name = ?1, description = ?2, version = ?3, capabilities_json = ?4,
                                config_schema_json = ?5, default_config_json = ?6, icon = ?7, updated_at = ?8
@@ -562,7 +568,7 @@ pub fn module_update(id: String, patch: serde_json::Value) -> Result<Module, Str
                 id.clone()
             ],
         )
-        .map_err(|e| format!("更新模块失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("更新模块失败: {}", e)))?;
     });
 
     Ok(Module {
@@ -583,13 +589,13 @@ pub fn module_update(id: String, patch: serde_json::Value) -> Result<Module, Str
 
 /// 删除模块
 #[tauri::command]
-pub fn module_delete(id: String) -> Result<(), String> {
+pub fn module_delete(workspace: Workspace, id: String) -> Result<(), AppError> {
     // 不能删除内置模块
     if id.starts_with("builtin:") {
-        return Err("不能删除内置模块".to_string());
+        return Err(AppError::Validation("不能删除内置模块".into()));
     }
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         // 检查是否有目录使用此模块
         let count: i32 = conn
             .query_row(
@@ -597,17 +603,17 @@ pub fn module_delete(id: String) -> Result<(), String> {
                 params![id],
                 |row| row.get(0),
             )
-            .map_err(|e| format!("查询失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
 
         if count > 0 {
-            return Err(format!(
+            return Err(AppError::Validation(format!(
                 "有 {} 个目录正在使用此模块，请先移除模块绑定",
                 count
-            ));
+            )));
         }
 
         conn.execute("DELETE FROM modules WHERE id = ?1", params![id])
-            .map_err(|e| format!("删除模块失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("删除模块失败: {}", e)))?;
 
         Ok(())
     })
@@ -616,10 +622,11 @@ pub fn module_delete(id: String) -> Result<(), String> {
 /// 验证模块配置
 #[tauri::command]
 pub fn module_validate_config(
+    workspace: Workspace,
     id: String,
     config: serde_json::Value,
-) -> Result<serde_json::Value, String> {
-    let module = module_get(id)?;
+) -> Result<serde_json::Value, AppError> {
+    let module = module_get(workspace.clone(), id)?;
 
     let schema = &module.config_schema;
     let mut errors: Vec<String> = Vec::new();
@@ -1,10 +1,12 @@
 use crate::commands::db_helpers::map_project_row;
-use crate::with_db;
-use crate::commands::workspace::get_workspace_path;
+use crate::commands::filesystem::move_to_trash;
+use crate::error::AppError;
+use crate::{with_db, with_db_mut};
+use crate::commands::workspace::{normalize_workspace_path, Workspace};
 use crate::types::*;
 use chrono::Utc;
 use rusqlite::params;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 /// 项目创建输入
 #[derive(serde::Deserialize)]
@@ -25,55 +27,115 @@ pub struct ProjectUpdateInput {
     pub ide_override: Option<IdeConfig>,
 }
 
-/// 列出所有项目（只返回可见项目）
+/// 列出所有项目（只返回可见项目，include_archived 为 true 时包含已归档项目，指定 tag 时只返回带该标签的项目）
 #[tauri::command]
-pub fn projects_list() -> Result<Vec<Project>, String> {
-    with_db!(conn, {
+pub fn projects_list(
+    workspace: Workspace,
+    include_archived: Option<bool>,
+    tag: Option<String>,
+    sort: Option<String>,
+) -> Result<Vec<Project>, AppError> {
+    let include_archived = include_archived.unwrap_or(false);
+    let tag = tag.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let order_by = match sort.as_deref().unwrap_or("recent") {
+        "recent" => "updated_at DESC",
+        "manual" => "sort_order ASC, updated_at DESC",
+        other => return Err(AppError::Validation(format!("不支持的排序方式: {}", other))),
+    };
+
+    with_db!(workspace, conn, {
+        let sql = format!(
+            "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at, archived, sort_order, created_at FROM projects
+             WHERE visible = 1 AND (?1 OR archived = 0)
+               AND (?2 IS NULL OR EXISTS (SELECT 1 FROM project_tags t WHERE t.project_id = projects.id AND t.tag = ?2))
+             ORDER BY {}",
+            order_by
+        );
         let mut stmt = conn
-            .prepare(
-                "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at FROM projects WHERE visible = 1 ORDER BY updated_at DESC",
-            )
-            .map_err(|e| format!("查询失败: {}", e))?;
+            .prepare(&sql)
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
 
-        let projects: Vec<Project> = stmt
-            .query_map([], map_project_row)
-            .map_err(|e| format!("查询失败: {}", e))?
+        let mut projects: Vec<Project> = stmt
+            .query_map(params![include_archived, tag], map_project_row)
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
             .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("读取数据失败: {}", e))?;
+            .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
+
+        let mut tags_by_project = load_all_project_tags(conn)?;
+        for project in projects.iter_mut() {
+            project.tags = tags_by_project.remove(&project.id).unwrap_or_default();
+        }
         Ok(projects)
     })
 }
 
+/// 校验项目显示配置：主题色须为 #RGB 或 #RRGGBB，主题模式须为 light/dark/system
+fn validate_project_display(display: &ProjectDisplay) -> Result<(), AppError> {
+    if let Some(color) = &display.theme_color {
+        let valid = color
+            .strip_prefix('#')
+            .map(|hex| {
+                (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+            })
+            .unwrap_or(false);
+        if !valid {
+            return Err(AppError::Validation(format!("主题色格式无效（应为 #RGB 或 #RRGGBB）: {}", color)));
+        }
+    }
+    if let Some(mode) = &display.theme_mode {
+        if !["light", "dark", "system"].contains(&mode.as_str()) {
+            return Err(AppError::Validation(format!("主题模式无效（应为 light、dark 或 system）: {}", mode)));
+        }
+    }
+    Ok(())
+}
+
+/// 校验项目名称：项目目录直接以名称命名，名称必须是单个普通路径组件（不含分隔符、`.` 或 `..`）
+fn validate_project_name(name: &str) -> Result<(), AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::Validation("项目名称不能为空".into()));
+    }
+    let mut components = Path::new(name).components();
+    let single_normal = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    );
+    if !single_normal {
+        return Err(AppError::Validation(format!("项目名称无效: {}", name)));
+    }
+    Ok(())
+}
+
 /// 创建项目
 #[tauri::command]
-pub fn project_create(input: ProjectCreateInput) -> Result<Project, String> {
-    // 首先检查工作区是否打开
-    let workspace_path = match get_workspace_path() {
-        Some(p) => p,
-        None => {
-            return Err("未打开工作区，请先在工作区页面选择或创建一个工作区".to_string())
-        }
-    };
+pub fn project_create(
+    workspace: Workspace,
+    input: ProjectCreateInput,
+) -> Result<Project, AppError> {
+    let workspace_path = workspace.path();
 
     let id = uuid::Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
 
     // 验证项目名称
     if input.name.trim().is_empty() {
-        return Err("项目名称不能为空".to_string());
+        return Err(AppError::Validation("项目名称不能为空".into()));
+    }
+    if let Some(display) = &input.display {
+        validate_project_display(display)?;
     }
 
     // 创建项目目录
-    let project_path = Path::new(&workspace_path).join(&input.name);
+    let project_path = Path::new(workspace_path).join(&input.name);
 
     // 检查目录是否已存在
     if project_path.exists() {
-        return Err(format!("项目目录已存在: {}", project_path.display()));
+        return Err(AppError::Validation(format!("项目目录已存在: {}", project_path.display())));
     }
 
     // 创建目录
     std::fs::create_dir_all(&project_path)
-        .map_err(|e| format!("创建项目目录失败: {} - {}", project_path.display(), e))?;
+        .map_err(|e| AppError::io(&e, format!("创建项目目录失败: {} - {}", project_path.display(), e)))?;
 
     // 序列化 display
     let display_json = input
@@ -81,10 +143,17 @@ pub fn project_create(input: ProjectCreateInput) -> Result<Project, String> {
         .as_ref()
         .and_then(|d| serde_json::to_string(d).ok());
 
-    with_db!(conn, {
+    let sort_order: i32 = with_db!(workspace, conn, {
+        // 新项目排在手动排序的末尾
+        let sort_order: i32 = conn
+            .query_row("SELECT COALESCE(MAX(sort_order) + 1, 0) FROM projects", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| AppError::Db(format!("查询排序失败: {}", e)))?;
+
         conn.execute(
-            "INSERT INTO projects (id, name, description, project_path, display_json, visible, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO projects (id, name, description, project_path, display_json, visible, sort_order, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 id,
                 input.name,
@@ -92,12 +161,13 @@ pub fn project_create(input: ProjectCreateInput) -> Result<Project, String> {
                 project_path.to_string_lossy().to_string(),
                 display_json,
                 1, // visible = 1 默认可见
+                sort_order,
                 now,
                 now
             ],
         )
-        .map_err(|e| format!("创建项目记录失败: {}", e))?;
-        Ok::<(), String>(())
+        .map_err(|e| AppError::Db(format!("创建项目记录失败: {}", e)))?;
+        Ok::<i32, AppError>(sort_order)
     })?;
 
     Ok(Project {
@@ -108,39 +178,420 @@ pub fn project_create(input: ProjectCreateInput) -> Result<Project, String> {
         display: input.display,
         ide_override: None,
         visible: true,
+        created_at: now.clone(),
         updated_at: now,
+        archived: false,
+        sort_order,
+        tags: Vec::new(),
     })
 }
 
-/// 获取项目
+/// 将已有目录登记为项目（不创建也不清空目录，可位于工作区内外）
 #[tauri::command]
-pub fn project_get(id: String) -> Result<Project, String> {
-    with_db!(conn, {
-        conn.query_row(
-            "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at FROM projects WHERE id = ?1",
-            params![id],
-            map_project_row,
+pub fn project_import(
+    workspace: Workspace,
+    path: String,
+    name: Option<String>,
+) -> Result<Project, AppError> {
+    let dir = Path::new(&path);
+    if !dir.is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", path)));
+    }
+    let project_path = normalize_workspace_path(&path);
+
+    let name = match name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+        Some(name) => name,
+        None => Path::new(&project_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| AppError::Validation("无法从路径推断项目名称".to_string()))?,
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    let sort_order: i32 = with_db!(workspace, conn, {
+        let registered: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM projects WHERE visible = 1 AND (project_path = ?1 OR project_path = ?2)",
+                params![project_path, path],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+        if registered {
+            return Err(AppError::Validation(format!("该目录已登记为项目: {}", project_path)));
+        }
+
+        let sort_order: i32 = conn
+            .query_row("SELECT COALESCE(MAX(sort_order) + 1, 0) FROM projects", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| AppError::Db(format!("查询排序失败: {}", e)))?;
+
+        conn.execute(
+            "INSERT INTO projects (id, name, project_path, visible, sort_order, created_at, updated_at)
+             VALUES (?1, ?2, ?3, 1, ?4, ?5, ?5)",
+            params![id, name, project_path, sort_order, now],
         )
-        .map_err(|e| format!("项目不存在: {}", e))
+        .map_err(|e| AppError::Db(format!("创建项目记录失败: {}", e)))?;
+        Ok::<i32, AppError>(sort_order)
+    })?;
+
+    Ok(Project {
+        id,
+        name,
+        description: None,
+        project_path,
+        display: None,
+        ide_override: None,
+        visible: true,
+        created_at: now.clone(),
+        updated_at: now,
+        archived: false,
+        sort_order,
+        tags: Vec::new(),
     })
 }
 
-/// 更新项目
+/// 复制项目：复制显示/IDE 设置与目录映射，并在磁盘上创建新目录（不复制 Git 仓库）
 #[tauri::command]
-pub fn project_update(id: String, patch: ProjectUpdateInput) -> Result<Project, String> {
+pub fn project_duplicate(
+    workspace: Workspace,
+    id: String,
+    new_name: String,
+) -> Result<Project, AppError> {
+    let workspace_path = workspace.path();
+
+    validate_project_name(&new_name)?;
+
+    let source = project_get(workspace.clone(), id.clone())?;
+
+    let project_path = Path::new(workspace_path).join(&new_name);
+    if project_path.exists() {
+        return Err(AppError::Validation(format!("项目目录已存在: {}", project_path.display())));
+    }
+    std::fs::create_dir_all(&project_path)
+        .map_err(|e| AppError::io(&e, format!("创建项目目录失败: {} - {}", project_path.display(), e)))?;
+
+    let new_id = uuid::Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
+    let display_json = source
+        .display
+        .as_ref()
+        .and_then(|d| serde_json::to_string(d).ok());
+    let ide_override_json = source
+        .ide_override
+        .as_ref()
+        .and_then(|i| serde_json::to_string(i).ok());
+    let path_str = project_path.to_string_lossy().to_string();
+
+    let result = with_db_mut!(workspace, conn, {
+        (|| {
+            let tx = conn.transaction().map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+
+            tx.execute(
+                "INSERT INTO projects (id, name, description, project_path, display_json, ide_override_json, visible, sort_order, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1, (SELECT COALESCE(MAX(sort_order) + 1, 0) FROM projects), ?7, ?7)",
+                params![
+                    new_id,
+                    new_name,
+                    source.description,
+                    path_str,
+                    display_json,
+                    ide_override_json,
+                    now
+                ],
+            )
+            .map_err(|e| AppError::Db(format!("创建项目记录失败: {}", e)))?;
+
+            let relative_paths = copy_project_directories(&tx, &id, &new_id, &now)?;
+
+            // 按映射创建子目录，保持与源项目一致的结构；全部成功后才提交
+            for relative in relative_paths {
+                std::fs::create_dir_all(project_path.join(&relative))
+                    .map_err(|e| AppError::io(&e, format!("创建目录失败: {} - {}", relative, e)))?;
+            }
+
+            tx.commit().map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))
+        })()
+    });
+
+    // 任一步失败都删除新建的目录，不留下没有记录的空目录
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&project_path);
+        return Err(e);
+    }
+
+    project_get(workspace, new_id)
+}
+
+/// 将源项目的目录映射复制到目标项目（生成新 id），返回复制的相对路径
+fn copy_project_directories(
+    conn: &rusqlite::Connection,
+    from_project_id: &str,
+    to_project_id: &str,
+    now: &str,
+) -> Result<Vec<String>, AppError> {
+    let dirs = project_template_dirs_with_conn(conn, from_project_id)?;
+    insert_project_directories(conn, to_project_id, &dirs, now)?;
+    Ok(dirs.into_iter().map(|d| d.relative_path).collect())
+}
+
+/// 读取项目的目录映射（按目录类型排序）
+fn project_template_dirs_with_conn(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+) -> Result<Vec<ProjectTemplateDir>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT dir_type_id, relative_path FROM project_directories WHERE project_id = ?1 ORDER BY dir_type_id",
+        )
+        .map_err(|e| AppError::Db(format!("查询目录映射失败: {}", e)))?;
+    let dirs = stmt
+        .query_map(params![project_id], |row| {
+            Ok(ProjectTemplateDir {
+                dir_type_id: row.get(0)?,
+                relative_path: row.get(1)?,
+            })
+        })
+        .map_err(|e| AppError::Db(format!("查询目录映射失败: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Db(format!("读取目录映射失败: {}", e)))?;
+    Ok(dirs)
+}
+
+/// 为项目写入一组目录映射（每条生成新 id）
+fn insert_project_directories(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+    dirs: &[ProjectTemplateDir],
+    now: &str,
+) -> Result<(), AppError> {
+    for dir in dirs {
+        conn.execute(
+            "INSERT INTO project_directories (id, project_id, dir_type_id, relative_path, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![
+                uuid::Uuid::new_v4().to_string(),
+                project_id,
+                dir.dir_type_id,
+                dir.relative_path,
+                now
+            ],
+        )
+        .map_err(|e| AppError::Db(format!("写入目录映射失败: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// 从 project_templates 行映射为 ProjectTemplate
+fn map_project_template_row(row: &rusqlite::Row) -> rusqlite::Result<ProjectTemplate> {
+    let display_json: Option<String> = row.get(2)?;
+    let directories_json: String = row.get(3)?;
+    Ok(ProjectTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        display: display_json.and_then(|json| serde_json::from_str(&json).ok()),
+        directories: serde_json::from_str(&directories_json).unwrap_or_default(),
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+/// 将项目的目录布局与显示设置保存为命名模板（同名模板会被覆盖）
+#[tauri::command]
+pub fn project_template_save(
+    workspace: Workspace,
+    project_id: String,
+    template_name: String,
+) -> Result<ProjectTemplate, AppError> {
+    let template_name = template_name.trim().to_string();
+    if template_name.is_empty() {
+        return Err(AppError::Validation("模板名称不能为空".into()));
+    }
+
+    let project = project_get(workspace.clone(), project_id.clone())?;
+    let now = Utc::now().to_rfc3339();
+    let display_json = project
+        .display
+        .as_ref()
+        .and_then(|d| serde_json::to_string(d).ok());
+
+    with_db!(workspace, conn, {
+        let dirs = project_template_dirs_with_conn(conn, &project_id)?;
+        let directories_json =
+            serde_json::to_string(&dirs).map_err(|e| AppError::Internal(format!("序列化目录布局失败: {}", e)))?;
+
+        conn.execute(
+            "INSERT INTO project_templates (id, name, display_json, directories_json, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+             ON CONFLICT(name) DO UPDATE SET display_json = excluded.display_json,
+               directories_json = excluded.directories_json, updated_at = excluded.updated_at",
+            params![
+                uuid::Uuid::new_v4().to_string(),
+                template_name,
+                display_json,
+                directories_json,
+                now
+            ],
+        )
+        .map_err(|e| AppError::Db(format!("保存项目模板失败: {}", e)))?;
 
-    let mut project: Project = with_db!(conn, {
         conn.query_row(
-            "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at FROM projects WHERE id = ?1",
-            params![id],
-            map_project_row,
+            "SELECT id, name, display_json, directories_json, created_at, updated_at FROM project_templates WHERE name = ?1",
+            params![template_name],
+            map_project_template_row,
+        )
+        .map_err(|e| AppError::Db(format!("读取项目模板失败: {}", e)))
+    })
+}
+
+/// 列出项目模板
+#[tauri::command]
+pub fn project_templates_list(workspace: Workspace) -> Result<Vec<ProjectTemplate>, AppError> {
+    with_db!(workspace, conn, {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, display_json, directories_json, created_at, updated_at FROM project_templates ORDER BY name",
+            )
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+        let templates = stmt
+            .query_map([], map_project_template_row)
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
+        Ok(templates)
+    })
+}
+
+/// 基于模板创建项目：创建目录并预置模板中的目录映射
+/// 项目记录与目录映射在同一事务中写入，任一步失败都会删除新建的目录
+#[tauri::command]
+pub fn project_create_from_template(
+    workspace: Workspace,
+    name: String,
+    template_name: String,
+) -> Result<Project, AppError> {
+    let workspace_path = workspace.path();
+    validate_project_name(&name)?;
+
+    let template: ProjectTemplate = with_db!(workspace, conn, {
+        conn.query_row(
+            "SELECT id, name, display_json, directories_json, created_at, updated_at FROM project_templates WHERE name = ?1",
+            params![template_name],
+            map_project_template_row,
         )
-        .map_err(|e| format!("项目不存在：{}", e))
+        .map_err(|e| AppError::lookup(&e, format!("项目模板不存在: {}", template_name)))
     })?;
 
-    // 更新字段
+    let project_path = Path::new(workspace_path).join(&name);
+    if project_path.exists() {
+        return Err(AppError::Validation(format!("项目目录已存在: {}", project_path.display())));
+    }
+    std::fs::create_dir_all(&project_path)
+        .map_err(|e| AppError::io(&e, format!("创建项目目录失败: {} - {}", project_path.display(), e)))?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let display_json = template
+        .display
+        .as_ref()
+        .and_then(|d| serde_json::to_string(d).ok());
+    let path_str = project_path.to_string_lossy().to_string();
+
+    let result = with_db_mut!(workspace, conn, {
+        (|| {
+            let tx = conn.transaction().map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+
+            tx.execute(
+                "INSERT INTO projects (id, name, project_path, display_json, visible, sort_order, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, 1, (SELECT COALESCE(MAX(sort_order) + 1, 0) FROM projects), ?5, ?5)",
+                params![id, name, path_str, display_json, now],
+            )
+            .map_err(|e| AppError::Db(format!("创建项目记录失败: {}", e)))?;
+
+            insert_project_directories(&tx, &id, &template.directories, &now)?;
+
+            for dir in &template.directories {
+                std::fs::create_dir_all(project_path.join(&dir.relative_path))
+                    .map_err(|e| AppError::io(&e, format!("创建目录失败: {} - {}", dir.relative_path, e)))?;
+            }
+
+            tx.commit().map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))
+        })()
+    });
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&project_path);
+        return Err(e);
+    }
+
+    project_get(workspace, id)
+}
+
+/// 获取项目
+#[tauri::command]
+pub fn project_get(workspace: Workspace, id: String) -> Result<Project, AppError> {
+    with_db!(workspace, conn, {
+        let mut project = conn
+            .query_row(
+                "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at, archived, sort_order, created_at FROM projects WHERE id = ?1",
+                params![id],
+                map_project_row,
+            )
+            .map_err(|e| AppError::lookup(&e, format!("项目不存在: {}", e)))?;
+        project.tags = project_tags_with_conn(conn, &id)?;
+        Ok(project)
+    })
+}
+
+/// 更新项目
+#[tauri::command]
+pub fn project_update(
+    workspace: Workspace,
+    id: String,
+    patch: ProjectUpdateInput,
+) -> Result<Project, AppError> {
+    if let Some(display) = &patch.display {
+        validate_project_display(display)?;
+    }
+    let now = Utc::now().to_rfc3339();
+
+    let mut project: Project = with_db!(workspace, conn, {
+        let mut project = conn
+            .query_row(
+                "SELECT id, name, description, project_path, display_json, ide_override_json, visible, updated_at, archived, sort_order, created_at FROM projects WHERE id = ?1",
+                params![id],
+                map_project_row,
+            )
+            .map_err(|e| AppError::lookup(&e, format!("项目不存在：{}", e)))?;
+        project.tags = project_tags_with_conn(conn, &id)?;
+        Ok::<Project, AppError>(project)
+    })?;
+
+    // 更新字段；名称变化时同步重命名磁盘目录
+    let mut renamed_from: Option<String> = None;
     if let Some(name) = patch.name {
+        validate_project_name(&name)?;
+        if name != project.name {
+            let old_path = Path::new(&project.project_path).to_path_buf();
+            let new_path = old_path
+                .parent()
+                .map(|parent| parent.join(&name))
+                .ok_or_else(|| AppError::Validation("项目路径无效".to_string()))?;
+
+            // 大小写不敏感的文件系统上仅改变大小写时，目标路径与原目录是同一个
+            let same_dir = match (old_path.canonicalize(), new_path.canonicalize()) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            };
+            if new_path.exists() && !same_dir {
+                return Err(AppError::Validation(format!("目标目录已存在: {}", new_path.display())));
+            }
+
+            renamed_from = Some(project.project_path.clone());
+            project.project_path = new_path.to_string_lossy().to_string();
+        }
         project.name = name;
     }
     if let Some(description) = patch.description {
@@ -162,67 +613,648 @@ pub fn project_update(id: String, patch: ProjectUpdateInput) -> Result<Project,
         .as_ref()
         .and_then(|i| serde_json::to_string(i).ok());
 
-    with_db!(conn, {
-        conn.execute(
-            "UPDATE projects SET name = ?1, description = ?2, display_json = ?3, ide_override_json = ?4, visible = ?5, updated_at = ?6 WHERE id = ?7",
-            params![
-                project.name,
-                project.description,
-                display_json,
-                ide_override_json,
-                project.visible,
-                now,
-                id
-            ],
-        )
-        .map_err(|e| format!("更新项目失败: {}", e))?;
-        Ok::<(), String>(())
-    })?;
+    // 目录在事务内、仓库路径改写之后才移动：改写时旧目录仍在，可按规范化路径匹配
+    let mut moved = false;
+    let result = with_db_mut!(workspace, conn, {
+        (|| {
+            let tx = conn.transaction().map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+
+            tx.execute(
+                "UPDATE projects SET name = ?1, description = ?2, display_json = ?3, ide_override_json = ?4, visible = ?5, project_path = ?6, updated_at = ?7 WHERE id = ?8",
+                params![
+                    project.name,
+                    project.description,
+                    display_json,
+                    ide_override_json,
+                    project.visible,
+                    project.project_path,
+                    now,
+                    id
+                ],
+            )
+            .map_err(|e| AppError::Db(format!("更新项目失败: {}", e)))?;
+
+            if let Some(old_base) = renamed_from.as_deref() {
+                rebase_repository_paths(&tx, &id, old_base, &project.project_path)?;
+                if Path::new(old_base).exists() {
+                    std::fs::rename(old_base, &project.project_path)
+                        .map_err(|e| AppError::io(&e, format!("重命名项目目录失败: {}", e)))?;
+                    moved = true;
+                }
+            }
+
+            tx.commit().map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))
+        })()
+    });
+
+    // 提交失败时把目录改回原名，保持磁盘与记录一致
+    if let Err(e) = result {
+        if let (true, Some(old_base)) = (moved, renamed_from.as_deref()) {
+            let _ = std::fs::rename(&project.project_path, old_base);
+        }
+        return Err(e);
+    }
 
     project.updated_at = now;
     Ok(project)
 }
 
+/// 将项目下仓库记录的路径从旧目录前缀替换为新目录前缀
+fn rebase_repository_paths(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+    old_base: &str,
+    new_base: &str,
+) -> Result<(), AppError> {
+    let mut stmt = conn
+        .prepare("SELECT id, path FROM git_repositories WHERE project_id = ?1")
+        .map_err(|e| AppError::Db(format!("查询仓库失败: {}", e)))?;
+    let repos: Vec<(String, String)> = stmt
+        .query_map(params![project_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| AppError::Db(format!("查询仓库失败: {}", e)))?
+        .filter_map(|r| r.ok())
+        .collect();
+    drop(stmt);
+
+    for (repo_id, repo_path) in repos {
+        if let Some(new_path) = rebase_path(&repo_path, old_base, new_base) {
+            conn.execute(
+                "UPDATE git_repositories SET path = ?1 WHERE id = ?2",
+                params![new_path.to_string_lossy().to_string(), repo_id],
+            )
+            .map_err(|e| AppError::Db(format!("更新仓库路径失败: {}", e)))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 将 path 从 old_base 下换到 new_base 下，不在 old_base 下时返回 None
+/// 先按原样比较，不匹配时再比较规范化后的路径（记录的路径可能经由符号链接或写法不同），
+/// 规范化需要路径仍存在于磁盘上
+pub(crate) fn rebase_path(path: &str, old_base: &str, new_base: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    if let Ok(relative) = path.strip_prefix(old_base) {
+        return Some(Path::new(new_base).join(relative));
+    }
+    let canonical_base = Path::new(old_base).canonicalize().ok()?;
+    let canonical_path = path.canonicalize().ok()?;
+    canonical_path
+        .strip_prefix(&canonical_base)
+        .ok()
+        .map(|relative| Path::new(new_base).join(relative))
+}
+
 /// 删除项目（软删除 - 隐藏项目）
+/// `delete_files` 为 true 时一并将磁盘上的项目目录（含其中的仓库）移入回收站；
+/// 导入的项目可能位于工作区之外，因此不做永久删除。移入回收站失败时记录保持不变
 #[tauri::command]
-pub fn project_delete(id: String) -> Result<serde_json::Value, String> {
-    with_db!(conn, {
-        // 验证项目存在
-        let exists: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM projects WHERE id = ?1",
-                params![id],
-                |row| row.get(0),
-            )
-            .map_err(|e| format!("查询失败: {}", e))?;
+pub fn project_delete(
+    workspace: Workspace,
+    id: String,
+    delete_files: Option<bool>,
+) -> Result<serde_json::Value, AppError> {
+    with_db_mut!(workspace, conn, {
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+        let project_path = delete_project_records_in(&tx, &id)?;
 
-        if !exists {
-            return Err("项目不存在".to_string());
+        if delete_files.unwrap_or(false) {
+            ensure_deletable_project_dir(&project_path, workspace.path())?;
+            let dir = Path::new(&project_path);
+            if dir.exists() {
+                move_to_trash(dir)?;
+            }
         }
 
-        // 软删除：将 visible 设置为 0
-        conn.execute("UPDATE projects SET visible = 0 WHERE id = ?1", params![id])
-            .map_err(|e| format!("隐藏项目失败: {}", e))?;
-        Ok::<(), String>(())
+        tx.commit()
+            .map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))
     })?;
 
     Ok(serde_json::json!({ "ok": true }))
 }
 
+/// 软删除单个项目的记录，由调用方负责事务
+fn delete_project_records_in(conn: &rusqlite::Connection, id: &str) -> Result<String, AppError> {
+    // 验证项目存在
+    let project_path: String = conn
+        .query_row(
+            "SELECT project_path FROM projects WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::lookup(&e, "项目不存在".to_string()))?;
+
+    // 软删除：将 visible 设置为 0
+    conn.execute("UPDATE projects SET visible = 0 WHERE id = ?1", params![id])
+        .map_err(|e| AppError::Db(format!("隐藏项目失败: {}", e)))?;
+
+    // 级联清理目录与仓库记录（本地目录保留，恢复项目后可重新扫描导入）
+    conn.execute("DELETE FROM project_directories WHERE project_id = ?1", params![id])
+        .map_err(|e| AppError::Db(format!("删除目录记录失败: {}", e)))?;
+    conn.execute("DELETE FROM git_repositories WHERE project_id = ?1", params![id])
+        .map_err(|e| AppError::Db(format!("删除仓库记录失败: {}", e)))?;
+
+    Ok(project_path)
+}
+
+/// 校验项目目录可以安全删除：不能是工作区根目录或其上级目录
+fn ensure_deletable_project_dir(project_path: &str, workspace_path: &str) -> Result<(), AppError> {
+    let resolve = |p: &str| {
+        Path::new(p)
+            .canonicalize()
+            .unwrap_or_else(|_| Path::new(p).to_path_buf())
+    };
+    let project = resolve(project_path);
+    let workspace = resolve(workspace_path);
+
+    if project.as_os_str().is_empty() || workspace.starts_with(&project) {
+        return Err(AppError::Validation("项目路径指向工作区根目录，拒绝删除".to_string()));
+    }
+    Ok(())
+}
+
+/// 按给定顺序重写项目的手动排序
+#[tauri::command]
+pub fn projects_reorder(
+    workspace: Workspace,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<Project>, AppError> {
+    with_db_mut!(workspace, conn, {
+        (|| {
+            let tx = conn.transaction().map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+            for (index, project_id) in ordered_ids.iter().enumerate() {
+                tx.execute(
+                    "UPDATE projects SET sort_order = ?1 WHERE id = ?2",
+                    params![index as i32, project_id],
+                )
+                .map_err(|e| AppError::Db(format!("更新排序失败: {}", e)))?;
+            }
+            tx.commit().map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))
+        })()
+    })?;
+
+    projects_list(workspace, Some(true), None, Some("manual".to_string()))
+}
+
+/// 归档或取消归档项目（归档项目不出现在默认列表中，但仍可按 id 获取）
+#[tauri::command]
+pub fn project_set_archived(
+    workspace: Workspace,
+    id: String,
+    archived: bool,
+) -> Result<Project, AppError> {
+    let now = Utc::now().to_rfc3339();
+
+    with_db!(workspace, conn, { set_archived_with_conn(conn, &id, archived, &now) })?;
+
+    project_get(workspace, id)
+}
+
+fn set_archived_with_conn(
+    conn: &rusqlite::Connection,
+    id: &str,
+    archived: bool,
+    now: &str,
+) -> Result<(), AppError> {
+    let affected = conn
+        .execute(
+            "UPDATE projects SET archived = ?1, updated_at = ?2 WHERE id = ?3",
+            params![archived, now, id],
+        )
+        .map_err(|e| AppError::Db(format!("更新归档状态失败: {}", e)))?;
+    if affected == 0 {
+        return Err(AppError::NotFound("项目不存在".to_string()));
+    }
+    Ok(())
+}
+
+/// 批量删除项目（仅数据库记录，本地目录保留），任一失败时全部回滚
+#[tauri::command]
+pub fn projects_bulk_delete(
+    workspace: Workspace,
+    ids: Vec<String>,
+) -> Result<BulkResult, AppError> {
+    with_db_mut!(workspace, conn, {
+        run_bulk_with_conn(conn, &ids, |conn, id| {
+            delete_project_records_in(conn, id).map(|_| ())
+        })
+    })
+}
+
+/// 批量设置归档状态，任一失败时全部回滚
+#[tauri::command]
+pub fn projects_bulk_set_archived(
+    workspace: Workspace,
+    ids: Vec<String>,
+    archived: bool,
+) -> Result<BulkResult, AppError> {
+    let now = Utc::now().to_rfc3339();
+
+    with_db_mut!(workspace, conn, {
+        run_bulk_with_conn(conn, &ids, |conn, id| {
+            set_archived_with_conn(conn, id, archived, &now)
+        })
+    })
+}
+
+/// 在单个事务中对每个项目执行操作并记录结果；全部成功才提交
+fn run_bulk_with_conn(
+    conn: &mut rusqlite::Connection,
+    ids: &[String],
+    op: impl Fn(&rusqlite::Connection, &str) -> Result<(), AppError>,
+) -> Result<BulkResult, AppError> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+
+    let mut results: Vec<BulkItemResult> = ids
+        .iter()
+        .map(|id| match op(&tx, id) {
+            Ok(()) => BulkItemResult {
+                id: id.clone(),
+                ok: true,
+                error: None,
+            },
+            Err(e) => BulkItemResult {
+                id: id.clone(),
+                ok: false,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect();
+
+    // 有失败项时丢弃事务，已执行的操作随之回滚
+    let committed = results.iter().all(|r| r.ok);
+    if committed {
+        tx.commit().map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))?;
+    } else {
+        // 回滚后原本成功的项同样没有生效，不能再报告为成功
+        for result in results.iter_mut().filter(|r| r.ok) {
+            result.ok = false;
+            result.error = Some("已回滚".to_string());
+        }
+    }
+
+    Ok(BulkResult { committed, results })
+}
+
+/// 列出项目标签
+#[tauri::command]
+pub fn project_tags_list(
+    workspace: Workspace,
+    project_id: String,
+) -> Result<Vec<String>, AppError> {
+    with_db!(workspace, conn, { project_tags_with_conn(conn, &project_id) })
+}
+
+/// 为项目添加标签（已存在同名标签时忽略，不区分大小写），返回最新标签列表
+#[tauri::command]
+pub fn project_add_tag(
+    workspace: Workspace,
+    project_id: String,
+    tag: String,
+) -> Result<Vec<String>, AppError> {
+    with_db!(workspace, conn, { add_project_tag_with_conn(conn, &project_id, &tag) })
+}
+
+/// 移除项目标签（不区分大小写），返回最新标签列表
+#[tauri::command]
+pub fn project_remove_tag(
+    workspace: Workspace,
+    project_id: String,
+    tag: String,
+) -> Result<Vec<String>, AppError> {
+    with_db!(workspace, conn, {
+        conn.execute(
+            "DELETE FROM project_tags WHERE project_id = ?1 AND tag = ?2",
+            params![project_id, tag.trim()],
+        )
+        .map_err(|e| AppError::Db(format!("移除标签失败: {}", e)))?;
+        project_tags_with_conn(conn, &project_id)
+    })
+}
+
+fn add_project_tag_with_conn(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+    tag: &str,
+) -> Result<Vec<String>, AppError> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(AppError::Validation("标签不能为空".to_string()));
+    }
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM projects WHERE id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+    if !exists {
+        return Err(AppError::NotFound("项目不存在".to_string()));
+    }
+
+    // tag 列使用 NOCASE 排序规则，主键冲突即表示已有同名标签
+    conn.execute(
+        "INSERT OR IGNORE INTO project_tags (project_id, tag, created_at) VALUES (?1, ?2, ?3)",
+        params![project_id, tag, Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| AppError::Db(format!("添加标签失败: {}", e)))?;
+
+    project_tags_with_conn(conn, project_id)
+}
+
+fn project_tags_with_conn(
+    conn: &rusqlite::Connection,
+    project_id: &str,
+) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT tag FROM project_tags WHERE project_id = ?1 ORDER BY tag")
+        .map_err(|e| AppError::Db(format!("查询标签失败: {}", e)))?;
+    let tags = stmt
+        .query_map(params![project_id], |row| row.get(0))
+        .map_err(|e| AppError::Db(format!("查询标签失败: {}", e)))?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| AppError::Db(format!("读取标签失败: {}", e)))?;
+    Ok(tags)
+}
+
+/// 一次性读取所有项目的标签，避免列表查询时逐个项目查询
+fn load_all_project_tags(
+    conn: &rusqlite::Connection,
+) -> Result<std::collections::HashMap<String, Vec<String>>, AppError> {
+    let mut stmt = conn
+        .prepare("SELECT project_id, tag FROM project_tags ORDER BY tag")
+        .map_err(|e| AppError::Db(format!("查询标签失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| AppError::Db(format!("查询标签失败: {}", e)))?;
+
+    let mut tags: std::collections::HashMap<String, Vec<String>> =
+        std::collections::HashMap::new();
+    for (project_id, tag) in rows.filter_map(|r| r.ok()) {
+        tags.entry(project_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
 /// 显示项目（恢复隐藏的项目）
 #[tauri::command]
-pub fn project_show(id: String) -> Result<Project, String> {
+pub fn project_show(workspace: Workspace, id: String) -> Result<Project, AppError> {
     let now = Utc::now().to_rfc3339();
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
         conn.execute(
             "UPDATE projects SET visible = 1, updated_at = ?1 WHERE id = ?2",
             params![now, id],
         )
-        .map_err(|e| format!("显示项目失败: {}", e))?;
-        Ok::<(), String>(())
+        .map_err(|e| AppError::Db(format!("显示项目失败: {}", e)))?;
+        Ok::<(), AppError>(())
     })?;
 
     // 返回更新后的项目
-    project_get(id)
+    project_get(workspace, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SCHEMA;
+    use rusqlite::Connection;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute(
+            "INSERT INTO projects (id, name, project_path, created_at, updated_at)
+             VALUES ('p1', 'Alpha', '/tmp/alpha', '2024-01-01', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_validate_project_display() {
+        let display = |color: Option<&str>, mode: Option<&str>| ProjectDisplay {
+            theme_color: color.map(String::from),
+            theme_mode: mode.map(String::from),
+        };
+
+        assert!(validate_project_display(&display(None, None)).is_ok());
+        assert!(validate_project_display(&display(Some("#fA0"), Some("dark"))).is_ok());
+        assert!(validate_project_display(&display(Some("#1E90FF"), Some("system"))).is_ok());
+        assert!(validate_project_display(&display(Some("blue"), None)).is_err());
+        assert!(validate_project_display(&display(Some("1E90FF"), None)).is_err());
+        assert!(validate_project_display(&display(Some("#1E90F"), None)).is_err());
+        assert!(validate_project_display(&display(Some("#GGGGGG"), None)).is_err());
+        assert!(validate_project_display(&display(None, Some("custom"))).is_err());
+    }
+
+    #[test]
+    fn test_delete_project_records_leaves_no_orphans() {
+        let conn = setup();
+        conn.execute_batch(
+            "INSERT INTO project_directories (id, project_id, dir_type_id, relative_path, created_at, updated_at)
+             VALUES ('d1', 'p1', 'docs', 'docs', '2024-01-01', '2024-01-01');
+             INSERT INTO git_repositories (id, project_id, name, path, created_at, updated_at)
+             VALUES ('r1', 'p1', 'app', '/tmp/alpha/code/app', '2024-01-01', '2024-01-01');",
+        )
+        .unwrap();
+
+        let path = delete_project_records_in(&conn, "p1").unwrap();
+        assert_eq!(path, "/tmp/alpha");
+
+        let orphans: i64 = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM project_directories WHERE project_id = 'p1')
+                      + (SELECT COUNT(*) FROM git_repositories WHERE project_id = 'p1')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(orphans, 0);
+        assert!(delete_project_records_in(&conn, "missing").is_err());
+    }
+
+    #[test]
+    fn test_bulk_set_archived_rolls_back_on_failure() {
+        let mut conn = setup();
+        conn.execute(
+            "INSERT INTO projects (id, name, project_path, created_at, updated_at)
+             VALUES ('p2', 'Beta', '/tmp/beta', '2024-01-01', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+        let archived_count = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM projects WHERE archived = 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        let archive = |conn: &rusqlite::Connection, id: &str| {
+            set_archived_with_conn(conn, id, true, "2024-02-01")
+        };
+
+        let ids = vec!["p1".to_string(), "missing".to_string(), "p2".to_string()];
+        let result = run_bulk_with_conn(&mut conn, &ids, archive).unwrap();
+        assert!(!result.committed);
+        assert_eq!(
+            result.results.iter().map(|r| r.ok).collect::<Vec<_>>(),
+            vec![false, false, false]
+        );
+        assert_eq!(result.results[0].error.as_deref(), Some("已回滚"));
+        assert_ne!(result.results[1].error.as_deref(), Some("已回滚"));
+        assert_eq!(result.results[2].error.as_deref(), Some("已回滚"));
+        assert_eq!(archived_count(&conn), 0);
+
+        let ids = vec!["p1".to_string(), "p2".to_string()];
+        let result = run_bulk_with_conn(&mut conn, &ids, archive).unwrap();
+        assert!(result.committed);
+        assert_eq!(archived_count(&conn), 2);
+    }
+
+    #[test]
+    fn test_project_tags_are_case_insensitive_and_unique() {
+        let conn = setup();
+        add_project_tag_with_conn(&conn, "p1", "Backend").unwrap();
+        let tags = add_project_tag_with_conn(&conn, "p1", " backend ").unwrap();
+
+        assert_eq!(tags, vec!["Backend".to_string()]);
+    }
+
+    #[test]
+    fn test_ensure_deletable_project_dir_rejects_workspace_root() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path().to_string_lossy().to_string();
+        let project = workspace.path().join("alpha");
+        std::fs::create_dir(&project).unwrap();
+
+        assert!(ensure_deletable_project_dir(&root, &root).is_err());
+        assert!(ensure_deletable_project_dir(&format!("{}/", root), &root).is_err());
+        assert!(ensure_deletable_project_dir(
+            workspace.path().parent().unwrap().to_str().unwrap(),
+            &root
+        )
+        .is_err());
+        assert!(ensure_deletable_project_dir(project.to_str().unwrap(), &root).is_ok());
+    }
+
+    #[test]
+    fn test_copy_project_directories_assigns_new_ids() {
+        let conn = setup();
+        conn.execute(
+            "INSERT INTO project_directories (id, project_id, dir_type_id, relative_path, created_at, updated_at)
+             VALUES ('d1', 'p1', 'docs', 'docs', '2024-01-01', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+
+        let copied = copy_project_directories(&conn, "p1", "p2", "2024-02-01").unwrap();
+        assert_eq!(copied, vec!["docs".to_string()]);
+
+        let (id, relative): (String, String) = conn
+            .query_row(
+                "SELECT id, relative_path FROM project_directories WHERE project_id = 'p2'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_ne!(id, "d1");
+        assert_eq!(relative, "docs");
+    }
+
+    #[test]
+    fn test_project_template_row_roundtrip() {
+        let conn = setup();
+        let dirs = vec![ProjectTemplateDir {
+            dir_type_id: "docs".to_string(),
+            relative_path: "docs".to_string(),
+        }];
+        conn.execute(
+            "INSERT INTO project_templates (id, name, display_json, directories_json, created_at, updated_at)
+             VALUES ('t1', 'web', '{\"themeColor\":\"#3366ff\"}', ?1, '2024-01-01', '2024-01-01')",
+            params![serde_json::to_string(&dirs).unwrap()],
+        )
+        .unwrap();
+
+        let template = conn
+            .query_row(
+                "SELECT id, name, display_json, directories_json, created_at, updated_at FROM project_templates WHERE name = 'web'",
+                [],
+                map_project_template_row,
+            )
+            .unwrap();
+        assert_eq!(template.directories, dirs);
+        assert_eq!(
+            template.display.and_then(|d| d.theme_color).as_deref(),
+            Some("#3366ff")
+        );
+
+        insert_project_directories(&conn, "p2", &template.directories, "2024-02-01").unwrap();
+        assert_eq!(project_template_dirs_with_conn(&conn, "p2").unwrap(), dirs);
+    }
+
+    #[test]
+    fn test_rebase_repository_paths() {
+        let conn = setup();
+        conn.execute(
+            "INSERT INTO git_repositories (id, project_id, name, path, created_at, updated_at)
+             VALUES ('r1', 'p1', 'api', '/tmp/alpha/code/api', '2024-01-01', '2024-01-01'),
+                    ('r2', 'p1', 'ext', '/elsewhere/ext', '2024-01-01', '2024-01-01')",
+            [],
+        )
+        .unwrap();
+
+        rebase_repository_paths(&conn, "p1", "/tmp/alpha", "/tmp/beta").unwrap();
+
+        let path = |id: &str| -> String {
+            conn.query_row(
+                "SELECT path FROM git_repositories WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            Path::new(&path("r1")),
+            Path::new("/tmp/beta").join("code/api")
+        );
+        assert_eq!(path("r2"), "/elsewhere/ext");
+    }
+
+    #[test]
+    fn test_validate_project_name_requires_single_component() {
+        assert!(validate_project_name("Alpha").is_ok());
+        assert!(validate_project_name("  ").is_err());
+        assert!(validate_project_name("..").is_err());
+        assert!(validate_project_name("../outside").is_err());
+        assert!(validate_project_name("a/b").is_err());
+        assert!(validate_project_name("/abs").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rebase_path_matches_canonical_prefix() {
+        let temp = tempfile::tempdir().unwrap();
+        let real = temp.path().join("real");
+        std::fs::create_dir_all(real.join("code/api")).unwrap();
+        let link = temp.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let recorded = link.join("code/api").to_string_lossy().to_string();
+        let rebased = rebase_path(&recorded, &real.to_string_lossy(), "/tmp/beta").unwrap();
+        assert_eq!(rebased, Path::new("/tmp/beta").join("code/api"));
+        assert!(rebase_path("/elsewhere/ext", &real.to_string_lossy(), "/tmp/beta").is_none());
+    }
+
+    #[test]
+    fn test_project_tag_requires_existing_project() {
+        let conn = setup();
+        assert!(add_project_tag_with_conn(&conn, "missing", "x").is_err());
+        assert!(add_project_tag_with_conn(&conn, "p1", "  ").is_err());
+    }
 }
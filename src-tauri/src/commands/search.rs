@@ -0,0 +1,245 @@
+//! 工作区内的跨项目搜索
+//! 目前基于 LIKE 查询；每类数据源单独实现为一个查询函数，之后可替换为 FTS 索引。
+
+use crate::commands::workspace::Workspace;
+use crate::error::AppError;
+use crate::types::*;
+use crate::with_db;
+use rusqlite::{params, Connection};
+
+/// 单个数据源返回的最大结果数
+const SOURCE_LIMIT: i64 = 50;
+/// 描述片段前后保留的字符数
+const SNIPPET_CONTEXT: usize = 30;
+
+/// 搜索当前工作区的项目名称、描述和项目目录
+#[tauri::command]
+pub fn workspace_search(workspace: Workspace, query: String) -> Result<Vec<SearchHit>, AppError> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    with_db!(workspace, conn, { search_with_conn(conn, &query) })
+}
+
+/// 在指定连接上执行搜索（按分值降序、同分按项目名排序）
+pub fn search_with_conn(conn: &Connection, query: &str) -> Result<Vec<SearchHit>, AppError> {
+    let pattern = format!("%{}%", escape_like(query));
+
+    let mut hits = Vec::new();
+    hits.extend(search_project_names(conn, query, &pattern)?);
+    hits.extend(search_project_descriptions(conn, query, &pattern)?);
+    hits.extend(search_project_directories(conn, &pattern)?);
+
+    hits.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.project_name.cmp(&b.project_name))
+    });
+    Ok(hits)
+}
+
+/// 转义 LIKE 通配符，配合 ESCAPE '\' 使用
+fn escape_like(query: &str) -> String {
+    query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn search_project_names(
+    conn: &Connection,
+    query: &str,
+    pattern: &str,
+) -> Result<Vec<SearchHit>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name FROM projects
+             WHERE visible = 1 AND name LIKE ?1 ESCAPE '\\'
+             LIMIT ?2",
+        )
+        .map_err(|e| AppError::Db(format!("搜索项目失败: {}", e)))?;
+
+    let query_lower = query.to_lowercase();
+    let rows = stmt
+        .query_map(params![pattern, SOURCE_LIMIT], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| AppError::Db(format!("搜索项目失败: {}", e)))?;
+
+    Ok(rows
+        .filter_map(|r| r.ok())
+        .map(|(id, name)| {
+            let name_lower = name.to_lowercase();
+            let score = if name_lower == query_lower {
+                100
+            } else if name_lower.starts_with(&query_lower) {
+                80
+            } else {
+                60
+            };
+            SearchHit {
+                project_id: id,
+                project_name: name.clone(),
+                kind: SearchHitKind::Project,
+                snippet: name,
+                score,
+            }
+        })
+        .collect())
+}
+
+fn search_project_descriptions(
+    conn: &Connection,
+    query: &str,
+    pattern: &str,
+) -> Result<Vec<SearchHit>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, description FROM projects
+             WHERE visible = 1 AND description LIKE ?1 ESCAPE '\\'
+             LIMIT ?2",
+        )
+        .map_err(|e| AppError::Db(format!("搜索项目描述失败: {}", e)))?;
+
+    let rows = stmt
+        .query_map(params![pattern, SOURCE_LIMIT], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| AppError::Db(format!("搜索项目描述失败: {}", e)))?;
+
+    Ok(rows
+        .filter_map(|r| r.ok())
+        .map(|(id, name, description)| SearchHit {
+            project_id: id,
+            project_name: name,
+            kind: SearchHitKind::Description,
+            snippet: make_snippet(&description, query),
+            score: 30,
+        })
+        .collect())
+}
+
+fn search_project_directories(
+    conn: &Connection,
+    pattern: &str,
+) -> Result<Vec<SearchHit>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.name, pd.relative_path FROM project_directories pd
+             JOIN projects p ON p.id = pd.project_id
+             WHERE p.visible = 1 AND pd.relative_path LIKE ?1 ESCAPE '\\'
+             LIMIT ?2",
+        )
+        .map_err(|e| AppError::Db(format!("搜索项目目录失败: {}", e)))?;
+
+    let rows = stmt
+        .query_map(params![pattern, SOURCE_LIMIT], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| AppError::Db(format!("搜索项目目录失败: {}", e)))?;
+
+    Ok(rows
+        .filter_map(|r| r.ok())
+        .map(|(id, name, relative_path)| SearchHit {
+            project_id: id,
+            project_name: name,
+            kind: SearchHitKind::Directory,
+            snippet: relative_path,
+            score: 40,
+        })
+        .collect())
+}
+
+/// 截取命中位置附近的文本片段（按字符处理，避免切断多字节字符）
+fn make_snippet(text: &str, query: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    let needle: Vec<char> = query.chars().flat_map(|c| c.to_lowercase()).collect();
+
+    // 小写化可能改变字符数，此时退化为从开头截取
+    let position = if lower.len() == chars.len() && !needle.is_empty() {
+        lower
+            .windows(needle.len())
+            .position(|window| window == needle.as_slice())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    let start = position.saturating_sub(SNIPPET_CONTEXT);
+    let end = (position + needle.len() + SNIPPET_CONTEXT).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.extend(&chars[start..end]);
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SCHEMA;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        let now = "2024-01-01T00:00:00Z";
+        conn.execute(
+            "INSERT INTO projects (id, name, description, project_path, visible, created_at, updated_at)
+             VALUES ('p1', 'Alpha', 'internal billing service', '/tmp/alpha', 1, ?1, ?1),
+                    ('p2', 'Alpha Tools', NULL, '/tmp/alpha-tools', 1, ?1, ?1),
+                    ('p3', 'Hidden alpha', NULL, '/tmp/hidden', 0, ?1, ?1)",
+            params![now],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO project_directories (id, project_id, dir_type_id, relative_path, created_at, updated_at)
+             VALUES ('d1', 'p2', 'docs', 'billing-docs', ?1, ?1)",
+            params![now],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_search_ranks_exact_name_first_and_skips_hidden() {
+        let conn = setup();
+        let hits = search_with_conn(&conn, "alpha").unwrap();
+
+        let ids: Vec<&str> = hits.iter().map(|h| h.project_id.as_str()).collect();
+        assert_eq!(ids, vec!["p1", "p2"]);
+        assert_eq!(hits[0].kind, SearchHitKind::Project);
+    }
+
+    #[test]
+    fn test_search_matches_descriptions_and_directories() {
+        let conn = setup();
+        let hits = search_with_conn(&conn, "billing").unwrap();
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].kind, SearchHitKind::Directory);
+        assert_eq!(hits[1].kind, SearchHitKind::Description);
+        assert_eq!(hits[1].snippet, "internal billing service");
+    }
+
+    #[test]
+    fn test_search_escapes_like_wildcards() {
+        let conn = setup();
+        assert!(search_with_conn(&conn, "%").unwrap().is_empty());
+    }
+}
@@ -1,4 +1,6 @@
 use crate::commands::directory::directory_create;
+use crate::commands::workspace::Workspace;
+use crate::error::AppError;
 use crate::with_db;
 use crate::types::*;
 use chrono::Utc;
@@ -7,10 +9,11 @@ use rusqlite::params;
 /// 列出模板
 #[tauri::command]
 pub fn template_list(
+    workspace: Workspace,
     scope: Option<String>,
     project_id: Option<String>,
-) -> Result<Vec<DirectoryTemplate>, String> {
-    with_db!(conn, {
+) -> Result<Vec<DirectoryTemplate>, AppError> {
+    with_db!(workspace, conn, {
     let query = match (&scope, &project_id) {
         (Some(s), None) => format!(
             "SELECT id, name, description, scope, project_id, items_json, created_by, created_at, updated_at
@@ -32,7 +35,7 @@ pub fn template_list(
             .to_string(),
     };
 
-    let mut stmt = conn.prepare(&query).map_err(|e| format!("查询失败: {}", e))?;
+    let mut stmt = conn.prepare(&query).map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
 
     let templates = stmt
         .query_map([], |row| {
@@ -59,9 +62,9 @@ pub fn template_list(
                 updated_at: row.get(8)?,
             })
         })
-        .map_err(|e| format!("查询失败: {}", e))?
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("读取数据失败: {}", e))?;
+        .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
 
     Ok(templates)
     })
@@ -69,8 +72,8 @@ pub fn template_list(
 
 /// 获取模板
 #[tauri::command]
-pub fn template_get(id: String) -> Result<DirectoryTemplate, String> {
-    with_db!(conn, {
+pub fn template_get(workspace: Workspace, id: String) -> Result<DirectoryTemplate, AppError> {
+    with_db!(workspace, conn, {
     conn.query_row(
         "SELECT id, name, description, scope, project_id, items_json, created_by, created_at, updated_at
          FROM directory_templates WHERE id = ?1",
@@ -100,17 +103,20 @@ pub fn template_get(id: String) -> Result<DirectoryTemplate, String> {
             })
         },
     )
-    .map_err(|e| format!("模板不存在: {}", e))
+    .map_err(|e| AppError::lookup(&e, format!("模板不存在: {}", e)))
     })
 }
 
 /// 创建模板
 #[tauri::command]
-pub fn template_create(input: serde_json::Value) -> Result<DirectoryTemplate, String> {
+pub fn template_create(
+    workspace: Workspace,
+    input: serde_json::Value,
+) -> Result<DirectoryTemplate, AppError> {
     let name = input
         .get("name")
         .and_then(|v| v.as_str())
-        .ok_or("缺少模板名称")?
+        .ok_or_else(|| AppError::Validation("缺少模板名称".to_string()))?
         .to_string();
 
     let description = input
@@ -158,16 +164,16 @@ pub fn template_create(input: serde_json::Value) -> Result<DirectoryTemplate, St
 
     let id = uuid::Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
-    let items_json =
-        serde_json::to_string(&items).map_err(|e| format!("序列化失败: {}", e))?;
+    let items_json = serde_json::to_string(&items)
+        .map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
 
-    with_db!(conn, {
+    with_db!(workspace, conn, {
     conn.execute(
         "INSERT INTO directory_templates (id, name, description, scope, project_id, items_json, created_by, created_at, updated_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
         params![id, name, description, scope_str, project_id, items_json, created_by, now, now],
     )
-    .map_err(|e| format!("创建模板失败: {}", e))?;
+    .map_err(|e| AppError::Db(format!("创建模板失败: {}", e)))?;
 
     Ok(DirectoryTemplate {
         id,
@@ -186,12 +192,13 @@ pub fn template_create(input: serde_json::Value) -> Result<DirectoryTemplate, St
 /// 更新模板
 #[tauri::command]
 pub fn template_update(
+    workspace: Workspace,
     id: String,
     patch: serde_json::Value,
-) -> Result<DirectoryTemplate, String> {
-    with_db!(conn, {
+) -> Result<DirectoryTemplate, AppError> {
+    with_db!(workspace, conn, {
     // 获取当前模板
-    let template = template_get(id.clone())?;
+    let template = template_get(workspace.clone(), id.clone())?;
 
     let name = patch
         .get("name")
@@ -224,13 +231,13 @@ pub fn template_update(
 
     let now = Utc::now().to_rfc3339();
     let items_json =
-        serde_json::to_string(&items).map_err(|e| format!("序列化失败: {}", e))?;
+        serde_json::to_string(&items).map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
 
     conn.execute(
         "UPDATE directory_templates SET name = ?1, description = ?2, items_json = ?3, updated_at = ?4 WHERE id = ?5",
         params![name, description, items_json, now, id],
     )
-    .map_err(|e| format!("更新模板失败: {}", e))?;
+    .map_err(|e| AppError::Db(format!("更新模板失败: {}", e)))?;
 
     Ok(DirectoryTemplate {
         id,
@@ -248,13 +255,13 @@ pub fn template_update(
 
 /// 删除模板
 #[tauri::command]
-pub fn template_delete(id: String) -> Result<(), String> {
-    with_db!(conn, {
+pub fn template_delete(workspace: Workspace, id: String) -> Result<(), AppError> {
+    with_db!(workspace, conn, {
     conn.execute(
         "DELETE FROM directory_templates WHERE id = ?1",
         params![id],
     )
-    .map_err(|e| format!("删除模板失败: {}", e))?;
+    .map_err(|e| AppError::Db(format!("删除模板失败: {}", e)))?;
 
     Ok(())
     })
@@ -263,11 +270,12 @@ pub fn template_delete(id: String) -> Result<(), String> {
 /// 应用模板到项目
 #[tauri::command]
 pub fn template_apply(
+    workspace: Workspace,
     template_id: String,
     project_id: String,
     customizations: Option<serde_json::Value>,
-) -> Result<Vec<Directory>, String> {
-    let template = template_get(template_id)?;
+) -> Result<Vec<Directory>, AppError> {
+    let template = template_get(workspace.clone(), template_id)?;
 
     let mut created_dirs = Vec::new();
 
@@ -314,6 +322,7 @@ pub fn template_apply(
 
         // 创建目录
         let dir = directory_create(
+            workspace.clone(),
             project_id.clone(),
             serde_json::json!({
                 "name": item.name,
@@ -332,16 +341,17 @@ pub fn template_apply(
 /// 从现有目录创建模板
 #[tauri::command]
 pub fn template_from_directories(
+    workspace: Workspace,
     name: String,
     description: Option<String>,
     scope: String,
     project_id: String,
     directory_ids: Vec<String>,
-) -> Result<DirectoryTemplate, String> {
+) -> Result<DirectoryTemplate, AppError> {
     let mut items = Vec::new();
 
     for dir_id in directory_ids {
-        let dir = crate::commands::directory::directory_get(dir_id)?;
+        let dir = crate::commands::directory::directory_get(workspace.clone(), dir_id)?;
 
         items.push(DirectoryTemplateItem {
             name: dir.name.clone(),
@@ -359,24 +369,28 @@ pub fn template_from_directories(
         "items": items
     });
 
-    template_create(input)
+    template_create(workspace, input)
 }
 
 /// 导出模板
 #[tauri::command]
-pub fn template_export(template_id: String) -> Result<String, String> {
-    let template = template_get(template_id)?;
-    serde_json::to_string_pretty(&template).map_err(|e| format!("序列化失败: {}", e))
+pub fn template_export(workspace: Workspace, template_id: String) -> Result<String, AppError> {
+    let template = template_get(workspace.clone(), template_id)?;
+    serde_json::to_string_pretty(&template)
+        .map_err(|e| AppError::Validation(format!("序列化失败: {}", e)))
 }
 
 /// 导入模板
 #[tauri::command]
-pub fn template_import(file_path: String) -> Result<DirectoryTemplate, String> {
+pub fn template_import(
+    workspace: Workspace,
+    file_path: String,
+) -> Result<DirectoryTemplate, AppError> {
     let content = std::fs::read_to_string(&file_path)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
+        .map_err(|e| AppError::io(&e, format!("读取文件失败: {}", e)))?;
 
-    let template: DirectoryTemplate =
-        serde_json::from_str(&content).map_err(|e| format!("解析模板失败: {}", e))?;
+    let template: DirectoryTemplate = serde_json::from_str(&content)
+        .map_err(|e| AppError::Validation(format!("解析模板失败: {}", e)))?;
 
     // 创建新模板（使用新的 ID）
     let input = serde_json::json!({
@@ -386,5 +400,5 @@ pub fn template_import(file_path: String) -> Result<DirectoryTemplate, String> {
         "items": template.items
     });
 
-    template_create(input)
+    template_create(workspace, input)
 }
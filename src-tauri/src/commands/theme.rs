@@ -0,0 +1,139 @@
+//! 自定义主题
+//! 工作区设置只保存 custom_theme_id，主题定义存放在 custom_themes 表中，由前端通过 theme_list 解析
+
+use crate::commands::workspace::Workspace;
+use crate::error::AppError;
+use crate::types::*;
+use crate::with_db;
+use chrono::Utc;
+use rusqlite::{params, Connection, Row};
+use std::collections::BTreeMap;
+
+/// 从 custom_themes 行映射为 CustomTheme
+/// cols: id, name, tokens_json, created_at, updated_at
+fn map_custom_theme_row(row: &Row) -> rusqlite::Result<CustomTheme> {
+    let tokens_json: String = row.get(2)?;
+    Ok(CustomTheme {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        tokens: serde_json::from_str(&tokens_json).unwrap_or_default(),
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+/// 解析颜色令牌，必须是 "令牌名 -> 颜色值" 的 JSON 对象
+fn parse_tokens(tokens_json: &str) -> Result<BTreeMap<String, String>, AppError> {
+    serde_json::from_str(tokens_json)
+        .map_err(|e| AppError::Validation(format!("主题配色必须是字符串键值的 JSON 对象: {}", e)))
+}
+
+/// 保存自定义主题：id 已存在时覆盖，未提供 id 时新建
+#[tauri::command]
+pub fn theme_save(
+    workspace: Workspace,
+    id: Option<String>,
+    name: String,
+    tokens_json: String,
+) -> Result<CustomTheme, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("主题名称不能为空".into()));
+    }
+    let tokens = parse_tokens(&tokens_json)?;
+    let id = id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    with_db!(workspace, conn, { save_theme_with_conn(conn, &id, &name, &tokens) })
+}
+
+fn save_theme_with_conn(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    tokens: &BTreeMap<String, String>,
+) -> Result<CustomTheme, AppError> {
+    let now = Utc::now().to_rfc3339();
+    let tokens_json = serde_json::to_string(tokens)
+        .map_err(|e| AppError::Internal(format!("序列化主题配色失败: {}", e)))?;
+
+    conn.execute(
+        "INSERT INTO custom_themes (id, name, tokens_json, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?4)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name,
+           tokens_json = excluded.tokens_json, updated_at = excluded.updated_at",
+        params![id, name, tokens_json, now],
+    )
+    .map_err(|e| AppError::Db(format!("保存主题失败: {}", e)))?;
+
+    conn.query_row(
+        "SELECT id, name, tokens_json, created_at, updated_at FROM custom_themes WHERE id = ?1",
+        params![id],
+        map_custom_theme_row,
+    )
+    .map_err(|e| AppError::Db(format!("读取主题失败: {}", e)))
+}
+
+/// 列出自定义主题
+#[tauri::command]
+pub fn theme_list(workspace: Workspace) -> Result<Vec<CustomTheme>, AppError> {
+    with_db!(workspace, conn, { list_themes_with_conn(conn) })
+}
+
+fn list_themes_with_conn(conn: &Connection) -> Result<Vec<CustomTheme>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, tokens_json, created_at, updated_at FROM custom_themes ORDER BY name",
+        )
+        .map_err(|e| AppError::Db(format!("查询主题失败: {}", e)))?;
+    let themes = stmt
+        .query_map([], map_custom_theme_row)
+        .map_err(|e| AppError::Db(format!("查询主题失败: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Db(format!("读取主题失败: {}", e)))?;
+    Ok(themes)
+}
+
+/// 删除自定义主题；工作区设置中的 custom_theme_id 保持不变，由前端回退到默认主题
+#[tauri::command]
+pub fn theme_delete(workspace: Workspace, id: String) -> Result<serde_json::Value, AppError> {
+    with_db!(workspace, conn, {
+        conn.execute("DELETE FROM custom_themes WHERE id = ?1", params![id])
+            .map_err(|e| AppError::Db(format!("删除主题失败: {}", e)))?;
+        Ok::<_, AppError>(())
+    })?;
+
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SCHEMA;
+
+    #[test]
+    fn test_save_theme_upserts_by_id() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+
+        let tokens = parse_tokens(r##"{"primary":"#3b82f6","background":"#ffffff"}"##).unwrap();
+        let created = save_theme_with_conn(&conn, "th1", "Ocean", &tokens).unwrap();
+        assert_eq!(created.tokens.get("primary").map(String::as_str), Some("#3b82f6"));
+
+        let tokens = parse_tokens(r##"{"primary":"#0f766e"}"##).unwrap();
+        let updated = save_theme_with_conn(&conn, "th1", "Teal", &tokens).unwrap();
+        assert_eq!(updated.name, "Teal");
+        assert_eq!(updated.created_at, created.created_at);
+        assert_eq!(updated.tokens.len(), 1);
+
+        let themes = list_themes_with_conn(&conn).unwrap();
+        assert_eq!(themes.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_tokens_rejects_non_object() {
+        assert!(parse_tokens("[1, 2]").is_err());
+        assert!(parse_tokens(r#"{"primary": 1}"#).is_err());
+    }
+}
@@ -1,11 +1,14 @@
+use crate::error::AppError;
 use crate::with_db;
 use crate::db::get_db;
 use crate::types::*;
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use tauri::Window;
 
 fn get_app_config_dir() -> Option<std::path::PathBuf> {
     dirs::config_dir().map(|p| p.join("pm-app"))
@@ -50,126 +53,305 @@ fn save_recent_workspaces(workspaces: &[WorkspaceInfo]) {
     }
 }
 
-fn add_to_recent_workspaces(path: String, last_opened_at: String, alias: Option<String>) {
+/// 规范化工作区路径，使同一目录的不同写法（尾部分隔符、大小写、符号链接）对应同一条记录
+pub fn normalize_workspace_path(path: &str) -> String {
+    match fs::canonicalize(path) {
+        Ok(canonical) => {
+            let canonical = canonical.to_string_lossy().to_string();
+            // Windows 下 canonicalize 返回 \\?\ 前缀的扩展路径，去掉以便展示和比较
+            canonical
+                .strip_prefix(r"\\?\")
+                .map(String::from)
+                .unwrap_or(canonical)
+        }
+        Err(_) => {
+            let trimmed = path.trim_end_matches(['/', '\\']);
+            if trimmed.is_empty() {
+                path.to_string()
+            } else {
+                trimmed.to_string()
+            }
+        }
+    }
+}
+
+fn add_to_recent_workspaces(
+    path: String,
+    db_path: String,
+    last_opened_at: String,
+    alias: Option<String>,
+) {
     let mut workspaces = load_recent_workspaces();
+    let path = normalize_workspace_path(&path);
 
-    // 如果存在现有工作区，保留其别名
-    let existing_alias = workspaces
+    // 如果存在现有工作区（含未规范化的旧记录），保留其别名和固定状态
+    let existing: Vec<&WorkspaceInfo> = workspaces
         .iter()
-        .find(|w| w.path == path)
-        .and_then(|w| w.alias.clone());
+        .filter(|w| normalize_workspace_path(&w.path) == path)
+        .collect();
+    let existing_alias = existing.iter().find_map(|w| w.alias.clone());
+    let pinned = existing.iter().any(|w| w.pinned);
 
-    workspaces.retain(|w| w.path != path);
+    workspaces.retain(|w| normalize_workspace_path(&w.path) != path);
 
     // 使用传入的别名，如果没有则保留现有的
     let final_alias = alias.or(existing_alias);
 
     let new_workspace = WorkspaceInfo {
         path: path.clone(),
-        db_path: Path::new(&path)
-            .join(".app/app.db")
-            .to_string_lossy()
-            .to_string(),
+        db_path,
         last_opened_at,
         settings: None,
         alias: final_alias,
+        pinned,
+        exists: true,
     };
 
     workspaces.insert(0, new_workspace);
+    normalize_recent_workspaces(&mut workspaces, load_global_settings().recent_limit);
+
+    save_recent_workspaces(&workspaces);
+}
 
-    if workspaces.len() > 10 {
-        workspaces.truncate(10);
+/// 固定项排在最前，其余按最近打开时间倒序；超出上限时只移除未固定项
+fn normalize_recent_workspaces(workspaces: &mut Vec<WorkspaceInfo>, limit: usize) {
+    workspaces.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then_with(|| b.last_opened_at.cmp(&a.last_opened_at))
+    });
+
+    let pinned_count = workspaces.iter().filter(|w| w.pinned).count();
+    let keep = pinned_count.max(limit);
+    workspaces.truncate(keep);
+}
+
+/// 工作区状态管理：各窗口打开的工作区（窗口 label -> 工作区路径）
+static WINDOW_WORKSPACES: once_cell::sync::Lazy<std::sync::Mutex<HashMap<String, String>>> =
+    once_cell::sync::Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// 命令所属的工作区，由发起调用的窗口解析
+/// 访问工作区数据的命令以它作为参数，多个窗口同时打开不同工作区时互不影响
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    path: String,
+}
+
+impl Workspace {
+    /// 窗口当前打开的工作区
+    pub fn of_window(label: &str) -> Option<Self> {
+        let path = WINDOW_WORKSPACES.lock().unwrap().get(label).cloned()?;
+        Some(Workspace { path })
     }
 
-    save_recent_workspaces(&workspaces);
+    pub fn path(&self) -> &str {
+        &self.path
+    }
 }
 
-/// 工作区状态管理
-static WORKSPACE_PATH: once_cell::sync::Lazy<std::sync::Mutex<Option<String>>> =
-    once_cell::sync::Lazy::new(|| std::sync::Mutex::new(None));
+impl<'de, R: tauri::Runtime> tauri::ipc::CommandArg<'de, R> for Workspace {
+    /// 发起调用的窗口未打开工作区时以 WorkspaceNotOpen 拒绝调用
+    fn from_command(
+        command: tauri::ipc::CommandItem<'de, R>,
+    ) -> Result<Self, tauri::ipc::InvokeError> {
+        let window = command.message.webview().window();
+        Workspace::of_window(window.label())
+            .ok_or_else(|| AppError::WorkspaceNotOpen("未打开工作区".to_string()).into())
+    }
+}
 
-/// 初始化或打开工作区
+/// 把窗口切换到指定工作区，返回窗口之前打开的另一个工作区
+fn bind_window(label: &str, path: &str) -> Option<String> {
+    WINDOW_WORKSPACES
+        .lock()
+        .unwrap()
+        .insert(label.to_string(), path.to_string())
+        .filter(|previous| previous != path)
+}
+
+/// 是否有窗口打开了该工作区
+fn is_workspace_open(path: &str) -> bool {
+    WINDOW_WORKSPACES
+        .lock()
+        .unwrap()
+        .values()
+        .any(|open| open == path)
+}
+
+/// 工作区已没有窗口使用时停止它的后台监听并释放数据库连接
+fn release_workspace_if_unused(path: &str) {
+    if is_workspace_open(path) {
+        return;
+    }
+    crate::commands::git::stop_status_watchers_for_workspace(path);
+    crate::commands::filesystem::stop_fs_watchers_for_workspace(path);
+    crate::db::close_db(path);
+}
+
+/// 窗口关闭工作区（或窗口本身被关闭）时解除绑定
+pub fn release_window(label: &str) {
+    let path = WINDOW_WORKSPACES.lock().unwrap().remove(label);
+    if let Some(path) = path {
+        release_workspace_if_unused(&path);
+    }
+}
+
+/// 确定工作区的数据目录名：显式传入的优先，其次沿用最近列表中记录的目录，最后使用默认的 .app
+fn resolve_data_dir(path: &str, requested: Option<String>) -> Result<String, AppError> {
+    if let Some(name) = requested {
+        let name = name.trim();
+        let mut components = Path::new(name).components();
+        let single_normal = matches!(
+            (components.next(), components.next()),
+            (Some(std::path::Component::Normal(_)), None)
+        );
+        if !single_normal {
+            return Err(AppError::Validation(format!("数据目录名称无效: {}", name)));
+        }
+        return Ok(name.to_string());
+    }
+
+    let remembered = load_recent_workspaces()
+        .into_iter()
+        .find(|w| normalize_workspace_path(&w.path) == path)
+        .and_then(|w| {
+            Path::new(&w.db_path)
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .map(|name| name.to_string_lossy().to_string())
+        });
+    Ok(remembered.unwrap_or_else(|| crate::db::DEFAULT_DATA_DIR.to_string()))
+}
+
+/// 在当前窗口初始化或打开工作区（`data_dir` 可指定存放数据库的目录名，默认 .app）
+/// 同一工作区可以在多个窗口中打开，共用一个数据库连接
 #[tauri::command]
-pub fn workspace_init_or_open(path: String) -> Result<WorkspaceInfo, String> {
+pub fn workspace_init_or_open(
+    window: Window,
+    path: String,
+    data_dir: Option<String>,
+) -> Result<WorkspaceInfo, AppError> {
     // 验证路径存在且可写
-    let workspace_path = Path::new(&path);
-    if !workspace_path.exists() {
-        return Err("工作区路径不存在".to_string());
+    if !Path::new(&path).exists() {
+        return Err(AppError::NotFound("工作区路径不存在".into()));
     }
 
+    let path = normalize_workspace_path(&path);
+    let workspace_path = Path::new(&path);
+
     if !workspace_path.is_dir() {
-        return Err("工作区路径必须是目录".to_string());
+        return Err(AppError::Validation("工作区路径必须是目录".into()));
     }
 
     // 测试写入权限
     let test_file = workspace_path.join(".app_test_write");
     if std::fs::write(&test_file, "test").is_err() {
-        return Err("工作区目录不可写".to_string());
+        return Err(AppError::Io("工作区目录不可写".into()));
     }
     let _ = std::fs::remove_file(&test_file);
 
     // 初始化数据库
-    crate::db::init_db(&path).map_err(|e| format!("数据库初始化失败: {}", e))?;
+    let data_dir = resolve_data_dir(&path, data_dir)?;
+    crate::db::init_db(&path, &data_dir)
+        .map_err(|e| AppError::Db(format!("数据库初始化失败: {}", e)))?;
+    let db_path = crate::db::db_file_path(&path, &data_dir)
+        .to_string_lossy()
+        .to_string();
+
+    // 切换当前窗口的工作区，窗口之前打开的工作区没有其他窗口使用时一并释放
+    if let Some(previous) = bind_window(window.label(), &path) {
+        release_workspace_if_unused(&previous);
+    }
+    let workspace = Workspace { path: path.clone() };
 
     // 更新最近工作区
     let now = Utc::now().to_rfc3339();
 
     // 保存到全局配置
-    add_to_recent_workspaces(path.clone(), now.clone(), None);
+    add_to_recent_workspaces(path.clone(), db_path.clone(), now.clone(), None);
 
-    // 首先：获取数据库连接并执行数据库操作
-    let settings = with_db!(conn, {
+    let settings = with_db!(workspace, conn, {
         conn.execute(
             "INSERT OR REPLACE INTO workspace_meta (key, value, updated_at) VALUES ('last_opened', ?1, ?2)",
             params![&path, &now],
-        ).map_err(|e| format!("更新最近工作区失败: {}", e))?;
+        ).map_err(|e| AppError::Db(format!("更新最近工作区失败: {}", e)))?;
+
+        // 首次打开时继承全局默认设置
+        seed_workspace_settings_with_conn(conn, &load_global_settings())?;
 
         // 获取设置
         get_workspace_settings_internal(conn)
     });
 
-    // 其次：在数据库锁释放后，获取工作区路径锁
-    {
-        let mut wp = WORKSPACE_PATH.lock().unwrap();
-        *wp = Some(path.clone());
-    } // WORKSPACE_PATH 锁在这里被释放
-
     Ok(WorkspaceInfo {
         path: path.clone(),
-        db_path: Path::new(&path)
-            .join(".app/app.db")
-            .to_string_lossy()
-            .to_string(),
+        db_path,
         last_opened_at: now,
         settings,
         alias: None,
+        pinned: false,
+        exists: true,
     })
 }
 
+/// 关闭当前窗口的工作区：没有其他窗口使用该工作区时停止它的后台监听并释放数据库连接
+#[tauri::command]
+pub fn workspace_close(window: Window) -> Result<(), AppError> {
+    release_window(window.label());
+    Ok(())
+}
+
 /// 列出最近工作区
 #[tauri::command]
-pub fn workspace_list_recent() -> Result<Vec<WorkspaceInfo>, String> {
-    let workspaces = load_recent_workspaces();
+pub fn workspace_list_recent() -> Result<Vec<WorkspaceInfo>, AppError> {
+    let mut workspaces = load_recent_workspaces();
+    // 标记已被移动或删除的工作区，便于前端置灰或提示移除
+    for workspace in workspaces.iter_mut() {
+        workspace.exists = Path::new(&workspace.path).is_dir();
+    }
     Ok(workspaces)
 }
 
-/// 获取工作区设置
+/// 获取当前窗口的工作区设置（未打开工作区时返回默认设置）
 #[tauri::command]
-pub fn workspace_settings_get() -> Result<WorkspaceSettings, String> {
+pub fn workspace_settings_get(window: Window) -> Result<WorkspaceSettings, AppError> {
+    Ok(match Workspace::of_window(window.label()) {
+        Some(workspace) => workspace_settings(&workspace),
+        None => load_global_settings().workspace_defaults(),
+    })
+}
+
+/// 读取工作区设置
+pub fn workspace_settings(workspace: &Workspace) -> WorkspaceSettings {
     // 如果数据库未初始化，返回默认设置
-    let db_guard = match get_db() {
+    let db_guard = match get_db(workspace.path()) {
         Ok(guard) => guard,
-        Err(_) => return Ok(WorkspaceSettings::default()),
+        Err(_) => return load_global_settings().workspace_defaults(),
     };
 
     match db_guard.as_ref() {
-        Some(conn) => {
-            Ok(get_workspace_settings_internal(conn).unwrap_or_else(WorkspaceSettings::default))
-        }
-        None => Ok(WorkspaceSettings::default()),
+        Some(conn) => get_workspace_settings_internal(conn)
+            .unwrap_or_else(|| load_global_settings().workspace_defaults()),
+        None => load_global_settings().workspace_defaults(),
     }
 }
 
+/// 工作区尚无设置记录时写入由全局默认值生成的设置，已有记录时不做改动
+fn seed_workspace_settings_with_conn(
+    conn: &rusqlite::Connection,
+    global: &GlobalSettings,
+) -> Result<bool, AppError> {
+    let json = serde_json::to_string(&global.workspace_defaults())
+        .map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
+    let inserted = conn
+        .execute(
+            "INSERT OR IGNORE INTO workspace_meta (key, value, updated_at) VALUES ('settings', ?1, ?2)",
+            params![json, Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| AppError::Db(format!("写入默认设置失败: {}", e)))?;
+    Ok(inserted > 0)
+}
+
 fn get_workspace_settings_internal(conn: &rusqlite::Connection) -> Option<WorkspaceSettings> {
     let result: rusqlite::Result<String> = conn.query_row(
         "SELECT value FROM workspace_meta WHERE key = 'settings'",
@@ -178,16 +360,19 @@ fn get_workspace_settings_internal(conn: &rusqlite::Connection) -> Option<Worksp
     );
 
     match result {
-        Ok(json) => serde_json::from_str(&json).ok(),
-        Err(_) => Some(WorkspaceSettings::default()),
+        Ok(json) => serde_json::from_str::<WorkspaceSettings>(&json).ok(),
+        Err(_) => Some(load_global_settings().workspace_defaults()),
     }
 }
 
 /// 更新工作区设置
 #[tauri::command]
-pub fn workspace_settings_update(patch: serde_json::Value) -> Result<WorkspaceSettings, String> {
+pub fn workspace_settings_update(
+    workspace: Workspace,
+    patch: serde_json::Value,
+) -> Result<WorkspaceSettings, AppError> {
     // 获取当前设置
-    with_db!(conn, {
+    with_db!(workspace, conn, {
     let mut settings = get_workspace_settings_internal(conn).unwrap_or_default();
 
     // 合并更新
@@ -213,32 +398,47 @@ pub fn workspace_settings_update(patch: serde_json::Value) -> Result<WorkspaceSe
                 settings.auto_fetch_git_projects = Some(val);
             }
         }
+        if let Some(ssh_key_path) = obj.get("sshKeyPath").or(obj.get("ssh_key_path")) {
+            settings.ssh_key_path = ssh_key_path
+                .as_str()
+                .filter(|s| !s.trim().is_empty())
+                .map(String::from);
+        }
+        if let Some(author_name) = obj.get("authorName").or(obj.get("author_name")) {
+            settings.author_name = author_name
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from);
+        }
+        if let Some(author_email) = obj.get("authorEmail").or(obj.get("author_email")) {
+            settings.author_email = author_email
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from);
+        }
     }
 
     // 保存设置
-    let json = serde_json::to_string(&settings).map_err(|e| format!("序列化失败: {}", e))?;
+    let json = serde_json::to_string(&settings).map_err(|e| AppError::Internal(format!("序列化失败: {}", e)))?;
     let now = Utc::now().to_rfc3339();
 
     conn.execute(
         "INSERT OR REPLACE INTO workspace_meta (key, value, updated_at) VALUES ('settings', ?1, ?2)",
         params![json, now],
-    ).map_err(|e| format!("保存设置失败: {}", e))?;
+    ).map_err(|e| AppError::Db(format!("保存设置失败: {}", e)))?;
 
     Ok(settings)
     })
 }
 
-/// 获取当前工作区路径
-pub fn get_workspace_path() -> Option<String> {
-    WORKSPACE_PATH.lock().unwrap().clone()
-}
-
 /// 更新工作区别名
 #[tauri::command]
 pub fn workspace_update_alias(
     path: String,
     alias: Option<String>,
-) -> Result<WorkspaceInfo, String> {
+) -> Result<WorkspaceInfo, AppError> {
     let mut workspaces = load_recent_workspaces();
 
     // 查找并更新指定工作区的别名
@@ -246,7 +446,7 @@ pub fn workspace_update_alias(
         let workspace = workspaces
             .iter_mut()
             .find(|w| w.path == path)
-            .ok_or_else(|| "工作区不存在".to_string())?;
+            .ok_or_else(|| AppError::NotFound("工作区不存在".to_string()))?;
         workspace.alias = alias;
         workspace.clone()
     };
@@ -255,9 +455,28 @@ pub fn workspace_update_alias(
     Ok(updated_workspace)
 }
 
+/// 固定或取消固定最近工作区
+#[tauri::command]
+pub fn workspace_set_pinned(path: String, pinned: bool) -> Result<WorkspaceInfo, AppError> {
+    let mut workspaces = load_recent_workspaces();
+
+    let updated_workspace = {
+        let workspace = workspaces
+            .iter_mut()
+            .find(|w| w.path == path)
+            .ok_or_else(|| AppError::NotFound("工作区不存在".to_string()))?;
+        workspace.pinned = pinned;
+        workspace.clone()
+    };
+
+    normalize_recent_workspaces(&mut workspaces, load_global_settings().recent_limit);
+    save_recent_workspaces(&workspaces);
+    Ok(updated_workspace)
+}
+
 /// 从最近工作区列表中移除
 #[tauri::command]
-pub fn workspace_remove_from_recent(path: String) -> Result<(), String> {
+pub fn workspace_remove_from_recent(path: String) -> Result<(), AppError> {
     let mut workspaces = load_recent_workspaces();
 
     // 移除指定路径的工作区
@@ -267,30 +486,150 @@ pub fn workspace_remove_from_recent(path: String) -> Result<(), String> {
     Ok(())
 }
 
-/// 获取当前工作区信息
+/// 工作区目录被移动后更新元数据：校验新目录中的数据库，把最近列表中的记录（别名、固定状态不变）
+/// 以及数据库中的 last_opened、工作区内的项目目录和仓库路径指向新路径。不移动任何文件
 #[tauri::command]
-pub fn workspace_get_current() -> Result<Option<WorkspaceInfo>, String> {
-    let current_path = get_workspace_path();
+pub fn workspace_relocate(old_path: String, new_path: String) -> Result<WorkspaceInfo, AppError> {
+    let old_path = normalize_workspace_path(&old_path);
+    if is_workspace_open(&old_path) {
+        return Err(AppError::Validation("不能迁移当前打开的工作区，请先关闭".into()));
+    }
+    if !Path::new(&new_path).is_dir() {
+        return Err(AppError::NotFound("新的工作区路径不存在".into()));
+    }
+    let new_path = normalize_workspace_path(&new_path);
+
+    // 沿用原记录的数据目录名（默认 .app）
+    let data_dir = resolve_data_dir(&old_path, None)?;
+    let db_path = crate::db::db_file_path(&new_path, &data_dir);
+    if !db_path.is_file() {
+        return Err(AppError::NotFound(format!(
+            "新路径中不存在工作区数据库: {}",
+            db_path.display()
+        )));
+    }
+    validate_workspace_db(&db_path)?;
+
+    let db_path = db_path.to_string_lossy().to_string();
+    let mut workspaces = load_recent_workspaces();
+    let relocated = relocate_recent_entry(&mut workspaces, &old_path, &new_path, &db_path)
+        .ok_or_else(|| AppError::NotFound("最近工作区中不存在该路径".into()))?;
+    normalize_recent_workspaces(&mut workspaces, load_global_settings().recent_limit);
+
+    let mut conn = rusqlite::Connection::open(&db_path)
+        .map_err(|e| AppError::Db(format!("工作区数据库无法打开: {}", e)))?;
+    relocate_paths_with_conn(&mut conn, &old_path, &new_path)?;
+
+    save_recent_workspaces(&workspaces);
+    Ok(relocated)
+}
+
+/// 在同一事务中把数据库里位于旧工作区路径下的项目目录、仓库路径以及 last_opened 改为新路径
+fn relocate_paths_with_conn(
+    conn: &mut rusqlite::Connection,
+    old_path: &str,
+    new_path: &str,
+) -> Result<(), AppError> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| AppError::Db(format!("开启事务失败: {}", e)))?;
+    tx.execute(
+        "INSERT OR REPLACE INTO workspace_meta (key, value, updated_at) VALUES ('last_opened', ?1, ?2)",
+        params![new_path, Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| AppError::Db(format!("更新最近工作区失败: {}", e)))?;
+    rebase_path_column(&tx, "projects", "project_path", old_path, new_path)?;
+    rebase_path_column(&tx, "git_repositories", "path", old_path, new_path)?;
+    tx.commit()
+        .map_err(|e| AppError::Db(format!("提交事务失败: {}", e)))
+}
+
+/// 将表中某个路径列里位于 old_base 下的值改到 new_base 下
+fn rebase_path_column(
+    conn: &rusqlite::Connection,
+    table: &str,
+    column: &str,
+    old_base: &str,
+    new_base: &str,
+) -> Result<(), AppError> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, {column} FROM {table} WHERE {column} IS NOT NULL"
+        ))
+        .map_err(|e| AppError::Db(format!("查询路径失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| AppError::Db(format!("查询路径失败: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Db(format!("读取路径失败: {}", e)))?;
+    drop(stmt);
+
+    for (id, path) in rows {
+        if let Some(rebased) = crate::commands::project::rebase_path(&path, old_base, new_base) {
+            conn.execute(
+                &format!("UPDATE {table} SET {column} = ?1 WHERE id = ?2"),
+                params![rebased.to_string_lossy().to_string(), id],
+            )
+            .map_err(|e| AppError::Db(format!("更新路径失败: {}", e)))?;
+        }
+    }
+    Ok(())
+}
+
+/// 将最近列表中旧路径的记录改为新路径；新路径已有记录时合并（别名优先取旧记录，任一固定即固定）
+fn relocate_recent_entry(
+    workspaces: &mut Vec<WorkspaceInfo>,
+    old_path: &str,
+    new_path: &str,
+    db_path: &str,
+) -> Option<WorkspaceInfo> {
+    let index = workspaces
+        .iter()
+        .position(|w| normalize_workspace_path(&w.path) == old_path)?;
+    let mut entry = workspaces.remove(index);
+
+    if let Some(index) = workspaces
+        .iter()
+        .position(|w| normalize_workspace_path(&w.path) == new_path)
+    {
+        let existing = workspaces.remove(index);
+        entry.alias = entry.alias.or(existing.alias);
+        entry.pinned |= existing.pinned;
+        entry.last_opened_at = entry.last_opened_at.max(existing.last_opened_at);
+    }
+
+    entry.path = new_path.to_string();
+    entry.db_path = db_path.to_string();
+    entry.exists = true;
+    workspaces.push(entry.clone());
+    Some(entry)
+}
 
-    match current_path {
-        Some(path) => {
+/// 获取当前窗口的工作区信息
+#[tauri::command]
+pub fn workspace_get_current(window: Window) -> Result<Option<WorkspaceInfo>, AppError> {
+    match Workspace::of_window(window.label()) {
+        Some(workspace) => {
+            let path = workspace.path().to_string();
             // 从最近工作区列表中获取完整信息（包括别名）
             let workspaces = load_recent_workspaces();
             let ws = workspaces.into_iter().find(|w| w.path == path);
 
             match ws {
-                Some(workspace) => {
+                Some(recent) => {
                     // 获取设置
-                    let settings = with_db!(conn, {
+                    let settings = with_db!(workspace, conn, {
                         get_workspace_settings_internal(conn)
                     });
 
                     Ok(Some(WorkspaceInfo {
-                        path: workspace.path,
-                        db_path: workspace.db_path,
-                        last_opened_at: workspace.last_opened_at,
+                        path: recent.path,
+                        db_path: recent.db_path,
+                        last_opened_at: recent.last_opened_at,
                         settings,
-                        alias: workspace.alias,
+                        alias: recent.alias,
+                        pinned: recent.pinned,
+                        exists: Path::new(&path).is_dir(),
                     }))
                 }
                 None => Ok(None),
@@ -311,6 +650,9 @@ pub struct GlobalSettings {
     pub font_size: String,
     pub default_ide: Option<IdeConfig>,
     pub auto_fetch_git_projects: Option<bool>,
+    /// 最近工作区列表的数量上限（最近列表为全局共享，不随工作区变化）
+    #[serde(default = "default_recent_limit")]
+    pub recent_limit: usize,
 }
 
 impl Default for GlobalSettings {
@@ -321,6 +663,19 @@ impl Default for GlobalSettings {
             font_size: "medium".to_string(),
             default_ide: None,
             auto_fetch_git_projects: None,
+            recent_limit: default_recent_limit(),
+        }
+    }
+}
+
+impl GlobalSettings {
+    /// 新工作区继承的设置：主题、默认 IDE 和自动拉取取自全局默认值
+    pub fn workspace_defaults(&self) -> WorkspaceSettings {
+        WorkspaceSettings {
+            theme_mode: self.theme_mode.clone(),
+            default_ide: self.default_ide.clone(),
+            auto_fetch_git_projects: self.auto_fetch_git_projects,
+            ..WorkspaceSettings::default()
         }
     }
 }
@@ -341,33 +696,33 @@ pub fn load_global_settings() -> GlobalSettings {
     }
 }
 
-fn save_global_settings(settings: &GlobalSettings) -> Result<(), String> {
+fn save_global_settings(settings: &GlobalSettings) -> Result<(), AppError> {
     let file_path = match get_global_settings_file() {
         Some(p) => p,
-        None => return Err("无法获取配置目录".to_string()),
+        None => return Err(AppError::Internal("无法获取配置目录".to_string())),
     };
 
     if let Some(parent) = file_path.parent() {
         let _ = fs::create_dir_all(parent);
     }
 
-    let content =
-        serde_json::to_string_pretty(settings).map_err(|e| format!("序列化设置失败: {}", e))?;
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| AppError::Internal(format!("序列化设置失败: {}", e)))?;
 
-    fs::write(&file_path, content).map_err(|e| format!("保存设置失败: {}", e))?;
+    fs::write(&file_path, content).map_err(|e| AppError::io(&e, format!("保存设置失败: {}", e)))?;
 
     Ok(())
 }
 
 /// 获取全局设置
 #[tauri::command]
-pub fn global_settings_get() -> Result<GlobalSettings, String> {
+pub fn global_settings_get() -> Result<GlobalSettings, AppError> {
     Ok(load_global_settings())
 }
 
 /// 更新全局设置
 #[tauri::command]
-pub fn global_settings_update(patch: serde_json::Value) -> Result<GlobalSettings, String> {
+pub fn global_settings_update(patch: serde_json::Value) -> Result<GlobalSettings, AppError> {
     let mut settings = load_global_settings();
 
     if let Some(obj) = patch.as_object() {
@@ -399,8 +754,610 @@ pub fn global_settings_update(patch: serde_json::Value) -> Result<GlobalSettings
                 settings.auto_fetch_git_projects = Some(val);
             }
         }
+        if let Some(limit) = obj.get("recentLimit").or(obj.get("recent_limit")) {
+            if let Some(val) = limit.as_u64().filter(|v| *v > 0) {
+                settings.recent_limit = val as usize;
+
+                let mut workspaces = load_recent_workspaces();
+                normalize_recent_workspaces(&mut workspaces, settings.recent_limit);
+                save_recent_workspaces(&workspaces);
+            }
+        }
     }
 
     save_global_settings(&settings)?;
     Ok(settings)
 }
+
+// ==================== Maintenance ====================
+
+/// 工作区统计：数量实时查询，仓库总大小需要遍历目录，缓存在 workspace_meta 中，
+/// 首次调用或 refresh 为 true 时重新计算（遍历期间不持有数据库锁）
+#[tauri::command]
+pub fn workspace_stats(
+    workspace: Workspace,
+    refresh: Option<bool>,
+) -> Result<WorkspaceStats, AppError> {
+    let (mut stats, repo_paths) = with_db!(workspace, conn, {
+        let stats = workspace_stats_with_conn(conn)?;
+        let repo_paths = if refresh.unwrap_or(false) || stats.size_computed_at.is_none() {
+            Some(active_repo_paths_with_conn(conn)?)
+        } else {
+            None
+        };
+        Ok::<_, AppError>((stats, repo_paths))
+    })?;
+
+    if let Some(repo_paths) = repo_paths {
+        let total: u64 = repo_paths.iter().map(|path| directory_size(Path::new(path))).sum();
+        let computed_at = Utc::now().to_rfc3339();
+        let cache = serde_json::json!({ "bytes": total, "computedAt": computed_at }).to_string();
+        with_db!(workspace, conn, {
+            conn.execute(
+                "INSERT OR REPLACE INTO workspace_meta (key, value, updated_at) VALUES ('repo_size_cache', ?1, ?2)",
+                params![cache, computed_at],
+            )
+            .map_err(|e| AppError::Db(format!("保存统计缓存失败: {}", e)))
+        })?;
+        stats.total_repo_size_bytes = total;
+        stats.size_computed_at = Some(computed_at);
+    }
+
+    Ok(stats)
+}
+
+/// 查询数量并读取缓存的仓库大小
+fn workspace_stats_with_conn(conn: &rusqlite::Connection) -> Result<WorkspaceStats, AppError> {
+    let count = |sql: &str| -> Result<i64, AppError> {
+        conn.query_row(sql, [], |row| row.get(0))
+            .map_err(|e| AppError::Db(format!("查询失败: {}", e)))
+    };
+    let project_count = count("SELECT COUNT(*) FROM projects WHERE visible = 1")?;
+    let repo_count = count(
+        "SELECT COUNT(*) FROM git_repositories r JOIN projects p ON p.id = r.project_id WHERE p.visible = 1",
+    )?;
+    let dir_type_count = count("SELECT COUNT(*) FROM directory_types")?;
+
+    let cache: Option<serde_json::Value> = conn
+        .query_row(
+            "SELECT value FROM workspace_meta WHERE key = 'repo_size_cache'",
+            [],
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok());
+
+    Ok(WorkspaceStats {
+        project_count,
+        repo_count,
+        dir_type_count,
+        total_repo_size_bytes: cache
+            .as_ref()
+            .and_then(|c| c["bytes"].as_u64())
+            .unwrap_or(0),
+        size_computed_at: cache
+            .as_ref()
+            .and_then(|c| c["computedAt"].as_str().map(String::from)),
+    })
+}
+
+fn active_repo_paths_with_conn(conn: &rusqlite::Connection) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.path FROM git_repositories r JOIN projects p ON p.id = r.project_id WHERE p.visible = 1",
+        )
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?;
+    let paths = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| AppError::Db(format!("查询失败: {}", e)))?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| AppError::Db(format!("读取数据失败: {}", e)))?;
+    Ok(paths)
+}
+
+/// 目录下所有文件的总大小（不跟随符号链接，无法读取的条目忽略）
+fn directory_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// 压缩当前工作区数据库（VACUUM），返回压缩前后的文件大小（字节，含 WAL 文件）
+/// VACUUM 需要独占数据库：执行期间一直持有全局数据库锁，其他命令会等待而不会死锁；
+/// 建议在没有文件监听和状态监听运行时调用，避免它们的数据库访问被长时间阻塞。
+#[tauri::command]
+pub fn workspace_vacuum(workspace: Workspace) -> Result<serde_json::Value, AppError> {
+    let (before, after) = with_db!(workspace, conn, { vacuum_connection(conn) })?;
+
+    Ok(serde_json::json!({
+        "ok": true,
+        "sizeBefore": before,
+        "sizeAfter": after
+    }))
+}
+
+fn vacuum_connection(conn: &rusqlite::Connection) -> Result<(u64, u64), AppError> {
+    let db_path = conn
+        .path()
+        .map(std::path::PathBuf::from)
+        .ok_or_else(|| AppError::Internal("无法确定数据库文件路径".to_string()))?;
+
+    let before = database_file_size(&db_path);
+    conn.execute_batch("VACUUM;")
+        .map_err(|e| AppError::Db(format!("压缩数据库失败: {}", e)))?;
+    // 将 WAL 写回主库并截断，文件大小才能反映压缩结果
+    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+        .map_err(|e| AppError::Db(format!("写回 WAL 失败: {}", e)))?;
+    let after = database_file_size(&db_path);
+
+    Ok((before, after))
+}
+
+/// 数据库文件及其 WAL 文件的总大小
+fn database_file_size(db_path: &Path) -> u64 {
+    let mut wal_path = db_path.as_os_str().to_owned();
+    wal_path.push("-wal");
+    [db_path.to_path_buf(), std::path::PathBuf::from(wal_path)]
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+// ==================== Backup ====================
+
+/// 备份包中数据库文件的名称
+const BACKUP_DB_ENTRY: &str = "app.db";
+/// 备份包中工作区设置的名称
+const BACKUP_SETTINGS_ENTRY: &str = "settings.json";
+/// 备份包中清单文件的名称
+const BACKUP_MANIFEST_ENTRY: &str = "manifest.json";
+
+/// 导出当前工作区元数据（数据库快照 + 设置）为 zip 备份包
+/// dest_path 为目录时在其中生成带时间戳的 .pmbackup 文件
+#[tauri::command]
+pub fn workspace_export(
+    workspace: Workspace,
+    dest_path: String,
+) -> Result<serde_json::Value, AppError> {
+    let dest = Path::new(&dest_path);
+    let archive_path = if dest.is_dir() {
+        dest.join(format!(
+            "workspace-{}.pmbackup",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ))
+    } else {
+        dest.to_path_buf()
+    };
+
+    // VACUUM INTO 在数据库打开期间也能生成一致的快照，目标文件必须不存在
+    let snapshot_path =
+        std::env::temp_dir().join(format!("pm-app-export-{}.db", uuid::Uuid::new_v4()));
+
+    let (settings, schema_version) = with_db!(workspace, conn, {
+        snapshot_database_with_conn(conn, &snapshot_path)?;
+        let version = crate::db::migrations::schema_version(conn)
+            .map_err(|e| AppError::Db(format!("读取数据库版本失败: {}", e)))?;
+        Ok::<_, AppError>((get_workspace_settings_internal(conn).unwrap_or_default(), version))
+    })?;
+
+    let result = write_backup_archive(
+        &archive_path,
+        &snapshot_path,
+        &settings,
+        &serde_json::json!({
+            "format": 1,
+            "schema_version": schema_version,
+            "workspace_path": workspace.path(),
+            "exported_at": Utc::now().to_rfc3339(),
+        }),
+    );
+    let _ = fs::remove_file(&snapshot_path);
+    result?;
+
+    let size = fs::metadata(&archive_path)
+        .map(|m| m.len())
+        .map_err(|e| AppError::io(&e, format!("读取备份文件失败: {}", e)))?;
+
+    Ok(serde_json::json!({
+        "ok": true,
+        "path": archive_path.to_string_lossy(),
+        "size": size
+    }))
+}
+
+/// 生成数据库快照并清除其中的 Git 凭据，备份包可能被拷贝到其他机器，不应携带令牌
+fn snapshot_database_with_conn(
+    conn: &rusqlite::Connection,
+    snapshot_path: &Path,
+) -> Result<(), AppError> {
+    conn.execute(
+        "VACUUM INTO ?1",
+        params![snapshot_path.to_string_lossy().to_string()],
+    )
+    .map_err(|e| AppError::Db(format!("生成数据库快照失败: {}", e)))?;
+
+    let strip = || -> Result<(), rusqlite::Error> {
+        let snapshot = rusqlite::Connection::open(snapshot_path)?;
+        // 删除后再次 VACUUM，避免令牌残留在空闲页中
+        snapshot.execute_batch("DELETE FROM git_credentials; VACUUM;")
+    };
+    strip().map_err(|e| {
+        let _ = fs::remove_file(snapshot_path);
+        AppError::Db(format!("清除快照中的凭据失败: {}", e))
+    })
+}
+
+/// 写入备份包：清单、设置和数据库快照
+fn write_backup_archive(
+    archive_path: &Path,
+    snapshot_path: &Path,
+    settings: &WorkspaceSettings,
+    manifest: &serde_json::Value,
+) -> Result<(), AppError> {
+    use std::io::Write;
+
+    if let Some(parent) = archive_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(&e, format!("创建目标目录失败: {}", e)))?;
+    }
+
+    let file = fs::File::create(archive_path)
+        .map_err(|e| AppError::io(&e, format!("创建备份文件失败: {}", e)))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let manifest_json = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| AppError::Internal(format!("序列化清单失败: {}", e)))?;
+    zip.start_file(BACKUP_MANIFEST_ENTRY, options)
+        .map_err(|e| AppError::Io(format!("写入清单失败: {}", e)))?;
+    zip.write_all(&manifest_json)
+        .map_err(|e| AppError::io(&e, format!("写入清单失败: {}", e)))?;
+
+    let settings_json = serde_json::to_vec_pretty(settings)
+        .map_err(|e| AppError::Internal(format!("序列化设置失败: {}", e)))?;
+    zip.start_file(BACKUP_SETTINGS_ENTRY, options)
+        .map_err(|e| AppError::Io(format!("写入设置失败: {}", e)))?;
+    zip.write_all(&settings_json)
+        .map_err(|e| AppError::io(&e, format!("写入设置失败: {}", e)))?;
+
+    let mut snapshot = fs::File::open(snapshot_path)
+        .map_err(|e| AppError::io(&e, format!("读取数据库快照失败: {}", e)))?;
+    zip.start_file(BACKUP_DB_ENTRY, options)
+        .map_err(|e| AppError::Io(format!("写入数据库失败: {}", e)))?;
+    std::io::copy(&mut snapshot, &mut zip)
+        .map_err(|e| AppError::io(&e, format!("写入数据库失败: {}", e)))?;
+
+    zip.finish()
+        .map_err(|e| AppError::Io(format!("完成备份文件失败: {}", e)))?;
+    Ok(())
+}
+
+/// 从备份包恢复工作区到 target_path，并在当前窗口打开
+/// 目标已有数据库时需传入 overwrite = true 才会覆盖
+#[tauri::command]
+pub fn workspace_import(
+    window: Window,
+    archive_path: String,
+    target_path: String,
+    overwrite: Option<bool>,
+) -> Result<WorkspaceInfo, AppError> {
+    let target = Path::new(&target_path);
+    fs::create_dir_all(target)
+        .map_err(|e| AppError::io(&e, format!("创建工作区目录失败: {}", e)))?;
+    let target_path = normalize_workspace_path(&target_path);
+    let target = Path::new(&target_path);
+    let app_dir = target.join(crate::db::DEFAULT_DATA_DIR);
+    let db_path = app_dir.join(crate::db::DB_FILE_NAME);
+
+    if db_path.exists() && !overwrite.unwrap_or(false) {
+        return Err(AppError::Validation("目标目录已包含工作区数据库".into()));
+    }
+    fs::create_dir_all(&app_dir)
+        .map_err(|e| AppError::io(&e, format!("创建工作区目录失败: {}", e)))?;
+
+    let file = fs::File::open(&archive_path)
+        .map_err(|e| AppError::io(&e, format!("打开备份文件失败: {}", e)))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::Validation(format!("备份文件格式无效: {}", e)))?;
+
+    // 先解压到临时文件并校验，通过后再替换正式数据库
+    let staging_path = app_dir.join("app.db.importing");
+    {
+        let mut entry = archive
+            .by_name(BACKUP_DB_ENTRY)
+            .map_err(|_| AppError::Validation("备份文件中缺少数据库".to_string()))?;
+        let mut staging = fs::File::create(&staging_path)
+            .map_err(|e| AppError::io(&e, format!("解压数据库失败: {}", e)))?;
+        std::io::copy(&mut entry, &mut staging)
+            .map_err(|e| AppError::io(&e, format!("解压数据库失败: {}", e)))?;
+    }
+
+    if let Err(e) = validate_workspace_db(&staging_path) {
+        let _ = fs::remove_file(&staging_path);
+        return Err(e);
+    }
+
+    crate::commands::git::stop_status_watchers_for_workspace(&target_path);
+    crate::db::close_db(&target_path);
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(app_dir.join(format!("app.db{}", suffix)));
+    }
+    fs::rename(&staging_path, &db_path)
+        .map_err(|e| AppError::io(&e, format!("替换数据库失败: {}", e)))?;
+
+    // 打开工作区会执行迁移并加入最近工作区列表
+    workspace_init_or_open(window, target_path, Some(crate::db::DEFAULT_DATA_DIR.to_string()))
+}
+
+/// 校验工作区数据库（备份或迁移后的目录中）：必须是本应用的数据库，且结构版本不高于当前支持的版本
+fn validate_workspace_db(db_path: &Path) -> Result<(), AppError> {
+    let conn = rusqlite::Connection::open(db_path)
+        .map_err(|e| AppError::Db(format!("工作区数据库无法打开: {}", e)))?;
+
+    let has_projects: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'projects'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .map_err(|e| AppError::Db(format!("工作区数据库无效: {}", e)))?;
+    if !has_projects {
+        return Err(AppError::Validation("不是有效的工作区数据库".to_string()));
+    }
+
+    let version = crate::db::migrations::schema_version(&conn)
+        .map_err(|e| AppError::Db(format!("读取数据库版本失败: {}", e)))?;
+    if version > crate::db::migrations::latest_version() {
+        return Err(AppError::Validation(format!(
+            "数据库来自更新版本的应用（结构版本 {}），请先升级应用",
+            version
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vacuum_connection_shrinks_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = rusqlite::Connection::open(dir.path().join("app.db")).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE blobs (data BLOB);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+             INSERT INTO blobs SELECT zeroblob(4096) FROM n;
+             DELETE FROM blobs;",
+        )
+        .unwrap();
+
+        let (before, after) = vacuum_connection(&conn).unwrap();
+        assert!(before > 0);
+        assert!(after < before);
+    }
+
+    #[test]
+    fn test_export_archive_excludes_git_credentials() {
+        use std::io::Read;
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::db::schema::SCHEMA).unwrap();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, project_path, created_at, updated_at)
+               VALUES ('p1', 'a', '/a', 'now', 'now');
+             INSERT INTO git_credentials (host, username, token, created_at, updated_at)
+               VALUES ('git.example.com', 'credential-user', 'secret-token-value', 'now', 'now');",
+        )
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let snapshot_path = dir.path().join("snapshot.db");
+        let archive_path = dir.path().join("backup.pmbackup");
+        snapshot_database_with_conn(&conn, &snapshot_path).unwrap();
+        write_backup_archive(
+            &archive_path,
+            &snapshot_path,
+            &WorkspaceSettings::default(),
+            &serde_json::json!({ "format": 1 }),
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&archive_path).unwrap()).unwrap();
+        let mut db_bytes = Vec::new();
+        archive
+            .by_name(BACKUP_DB_ENTRY)
+            .unwrap()
+            .read_to_end(&mut db_bytes)
+            .unwrap();
+        for needle in [&b"credential-user"[..], &b"secret-token-value"[..]] {
+            assert!(!db_bytes.windows(needle.len()).any(|w| w == needle));
+        }
+
+        let restored_path = dir.path().join("restored.db");
+        fs::write(&restored_path, &db_bytes).unwrap();
+        let restored = rusqlite::Connection::open(&restored_path).unwrap();
+        let count = |sql: &str| restored.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM git_credentials"), 0);
+        assert_eq!(count("SELECT COUNT(*) FROM projects"), 1);
+    }
+
+    #[test]
+    fn test_workspace_stats_counts_visible_projects() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::db::schema::SCHEMA).unwrap();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, project_path, visible, created_at, updated_at)
+               VALUES ('p1', 'a', '/a', 1, 'now', 'now'), ('p2', 'b', '/b', 0, 'now', 'now');
+             INSERT INTO git_repositories (id, project_id, name, path, created_at, updated_at)
+               VALUES ('r1', 'p1', 'r1', '/a/r1', 'now', 'now'), ('r2', 'p2', 'r2', '/b/r2', 'now', 'now');",
+        )
+        .unwrap();
+
+        let stats = workspace_stats_with_conn(&conn).unwrap();
+        assert_eq!((stats.project_count, stats.repo_count), (1, 1));
+        assert!(stats.size_computed_at.is_none());
+        assert_eq!(active_repo_paths_with_conn(&conn).unwrap(), ["/a/r1"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), [0u8; 10]).unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/b.txt"), [0u8; 5]).unwrap();
+        assert_eq!(directory_size(dir.path()), 15);
+    }
+
+    #[test]
+    fn test_resolve_data_dir_rejects_nested_names() {
+        assert_eq!(
+            resolve_data_dir("/tmp/ws", Some(" .pm-app ".to_string())).unwrap(),
+            ".pm-app"
+        );
+        assert!(resolve_data_dir("/tmp/ws", Some("../x".to_string())).is_err());
+        assert!(resolve_data_dir("/tmp/ws", Some("a/b".to_string())).is_err());
+        assert!(resolve_data_dir("/tmp/ws", Some("".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_seed_workspace_settings_inherits_global_defaults_once() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::db::schema::SCHEMA).unwrap();
+        let global = GlobalSettings {
+            theme_mode: ThemeMode::Dark,
+            default_ide: Some(IdeConfig {
+                kind: SupportedIdeKind::Zed,
+                name: "Zed".into(),
+                command: "zed".into(),
+                args: None,
+                available: None,
+            }),
+            ..GlobalSettings::default()
+        };
+
+        assert!(seed_workspace_settings_with_conn(&conn, &global).unwrap());
+        let settings = get_workspace_settings_internal(&conn).unwrap();
+        assert_eq!(settings.theme_mode, ThemeMode::Dark);
+        assert_eq!(settings.default_ide.unwrap().command, "zed");
+
+        // 已有设置不会被全局默认值覆盖
+        let light = GlobalSettings::default();
+        assert!(!seed_workspace_settings_with_conn(&conn, &light).unwrap());
+        let settings = get_workspace_settings_internal(&conn).unwrap();
+        assert_eq!(settings.theme_mode, ThemeMode::Dark);
+    }
+
+    fn workspace(path: &str, last_opened_at: &str, pinned: bool) -> WorkspaceInfo {
+        WorkspaceInfo {
+            path: path.to_string(),
+            db_path: format!("{}/.app/app.db", path),
+            last_opened_at: last_opened_at.to_string(),
+            settings: None,
+            alias: None,
+            pinned,
+            exists: true,
+        }
+    }
+
+    #[test]
+    fn test_normalize_workspace_path_collapses_trailing_separator() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let plain = temp_dir.path().to_string_lossy().to_string();
+        let with_slash = format!("{}{}", plain, std::path::MAIN_SEPARATOR);
+
+        assert_eq!(
+            normalize_workspace_path(&plain),
+            normalize_workspace_path(&with_slash)
+        );
+        assert_eq!(normalize_workspace_path("/missing/dir/"), "/missing/dir");
+    }
+
+    #[test]
+    fn test_normalize_keeps_pinned_on_top() {
+        let mut list = vec![
+            workspace("/a", "2024-01-03T00:00:00Z", false),
+            workspace("/b", "2024-01-01T00:00:00Z", true),
+            workspace("/c", "2024-01-02T00:00:00Z", false),
+        ];
+        normalize_recent_workspaces(&mut list, 10);
+
+        let paths: Vec<&str> = list.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(paths, vec!["/b", "/a", "/c"]);
+    }
+
+    #[test]
+    fn test_relocate_recent_entry_keeps_alias_and_pin() {
+        let mut moved = workspace("/old", "2024-01-02T00:00:00Z", true);
+        moved.alias = Some("Work".to_string());
+        let mut list = vec![
+            workspace("/a", "2024-01-03T00:00:00Z", false),
+            moved,
+            workspace("/new", "2024-01-01T00:00:00Z", false),
+        ];
+
+        let entry = relocate_recent_entry(&mut list, "/old", "/new", "/new/.app/app.db").unwrap();
+        assert_eq!(entry.path, "/new");
+        assert_eq!(entry.db_path, "/new/.app/app.db");
+        assert_eq!(entry.alias.as_deref(), Some("Work"));
+        assert!(entry.pinned);
+        assert_eq!(entry.last_opened_at, "2024-01-02T00:00:00Z");
+
+        let paths: Vec<&str> = list.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(paths, vec!["/a", "/new"]);
+        assert!(relocate_recent_entry(&mut list, "/missing", "/x", "/x/.app/app.db").is_none());
+    }
+
+    #[test]
+    fn test_relocate_paths_rewrites_projects_and_repositories() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(crate::db::schema::SCHEMA).unwrap();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, project_path, created_at, updated_at)
+               VALUES ('p1', 'a', '/old/ws/alpha', 'now', 'now'), ('p2', 'b', '/elsewhere/beta', 'now', 'now');
+             INSERT INTO git_repositories (id, project_id, name, path, created_at, updated_at)
+               VALUES ('r1', 'p1', 'api', '/old/ws/alpha/code/api', 'now', 'now'),
+                      ('r2', 'p2', 'ext', '/elsewhere/beta/code/ext', 'now', 'now');",
+        )
+        .unwrap();
+
+        relocate_paths_with_conn(&mut conn, "/old/ws", "/new/ws").unwrap();
+
+        let value = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, String>(0)).unwrap();
+        assert_eq!(
+            Path::new(&value("SELECT project_path FROM projects WHERE id = 'p1'")),
+            Path::new("/new/ws").join("alpha")
+        );
+        assert_eq!(value("SELECT project_path FROM projects WHERE id = 'p2'"), "/elsewhere/beta");
+        assert_eq!(
+            Path::new(&value("SELECT path FROM git_repositories WHERE id = 'r1'")),
+            Path::new("/new/ws").join("alpha/code/api")
+        );
+        assert_eq!(
+            value("SELECT path FROM git_repositories WHERE id = 'r2'"),
+            "/elsewhere/beta/code/ext"
+        );
+        assert_eq!(
+            value("SELECT value FROM workspace_meta WHERE key = 'last_opened'"),
+            "/new/ws"
+        );
+    }
+
+    #[test]
+    fn test_normalize_never_truncates_pinned() {
+        let mut list = vec![
+            workspace("/a", "2024-01-03T00:00:00Z", false),
+            workspace("/b", "2024-01-01T00:00:00Z", true),
+            workspace("/c", "2024-01-02T00:00:00Z", true),
+        ];
+        normalize_recent_workspaces(&mut list, 1);
+
+        let paths: Vec<&str> = list.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(paths, vec!["/c", "/b"]);
+    }
+}
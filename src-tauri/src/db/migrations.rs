@@ -0,0 +1,148 @@
+//! 版本化数据库迁移
+//! 已应用的版本记录在 `PRAGMA user_version` 中，每个步骤在独立事务内执行。
+//! SCHEMA 始终描述最新结构：新增步骤时需同步修改 SCHEMA，新建的数据库直接标记为最新版本。
+
+use rusqlite::{Connection, Result};
+
+/// 有序的迁移步骤：(版本号, SQL)，版本号必须严格递增
+pub const MIGRATIONS: &[(i64, &str)] = &[
+    (
+        1,
+        "CREATE INDEX IF NOT EXISTS idx_git_repositories_project_path ON git_repositories(project_id, path);",
+    ),
+    (
+        2,
+        "ALTER TABLE projects ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;",
+    ),
+    (
+        3,
+        "CREATE TABLE IF NOT EXISTS project_tags (
+          project_id TEXT NOT NULL,
+          tag TEXT NOT NULL COLLATE NOCASE,
+          created_at TEXT NOT NULL,
+          PRIMARY KEY (project_id, tag)
+        );",
+    ),
+    (
+        4,
+        "ALTER TABLE projects ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;",
+    ),
+    (
+        5,
+        "CREATE TABLE IF NOT EXISTS project_templates (
+          id TEXT PRIMARY KEY,
+          name TEXT NOT NULL UNIQUE,
+          display_json TEXT,
+          directories_json TEXT NOT NULL,
+          created_at TEXT NOT NULL,
+          updated_at TEXT NOT NULL
+        );",
+    ),
+    (
+        6,
+        "ALTER TABLE git_repositories ADD COLUMN last_commit_sha TEXT;
+         ALTER TABLE git_repositories ADD COLUMN last_commit_summary TEXT;
+         ALTER TABLE git_repositories ADD COLUMN last_commit_at TEXT;",
+    ),
+    (
+        7,
+        "CREATE TABLE IF NOT EXISTS custom_themes (
+          id TEXT PRIMARY KEY,
+          name TEXT NOT NULL,
+          tokens_json TEXT NOT NULL,
+          created_at TEXT NOT NULL,
+          updated_at TEXT NOT NULL
+        );",
+    ),
+];
+
+/// 最新的结构版本
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|(version, _)| *version).unwrap_or(0)
+}
+
+/// 读取数据库当前的结构版本
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// 将新建的数据库直接标记为最新版本（SCHEMA 已包含全部迁移）
+pub fn mark_latest(conn: &Connection) -> Result<()> {
+    conn.execute_batch(&format!("PRAGMA user_version = {}", latest_version()))
+}
+
+/// 依次应用尚未执行的迁移，返回本次应用的步骤数
+pub fn apply_pending(conn: &Connection) -> Result<usize> {
+    let current = schema_version(conn)?;
+    let mut applied = 0;
+
+    for (version, sql) in MIGRATIONS.iter().filter(|(version, _)| *version > current) {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(sql)?;
+        tx.execute_batch(&format!("PRAGMA user_version = {}", version))?;
+        tx.commit()?;
+        applied += 1;
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SCHEMA;
+
+    #[test]
+    fn test_versions_strictly_increase() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    /// 版本化之前创建的数据库结构（仅包含迁移涉及的表）
+    const LEGACY_SCHEMA: &str = "
+        CREATE TABLE projects (
+          id TEXT PRIMARY KEY,
+          name TEXT NOT NULL,
+          description TEXT,
+          project_path TEXT NOT NULL,
+          display_json TEXT,
+          ide_override_json TEXT,
+          visible INTEGER NOT NULL DEFAULT 1,
+          created_at TEXT NOT NULL,
+          updated_at TEXT NOT NULL
+        );
+        CREATE TABLE git_repositories (
+          id TEXT PRIMARY KEY,
+          project_id TEXT NOT NULL,
+          name TEXT NOT NULL,
+          path TEXT NOT NULL
+        );
+    ";
+
+    #[test]
+    fn test_apply_pending_upgrades_legacy_db_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(LEGACY_SCHEMA).unwrap();
+
+        assert_eq!(apply_pending(&conn).unwrap(), MIGRATIONS.len());
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        assert_eq!(apply_pending(&conn).unwrap(), 0);
+
+        let has_archived: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('projects') WHERE name = 'archived'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(has_archived, 1);
+    }
+
+    #[test]
+    fn test_mark_latest_skips_all_steps() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        mark_latest(&conn).unwrap();
+
+        assert_eq!(apply_pending(&conn).unwrap(), 0);
+    }
+}
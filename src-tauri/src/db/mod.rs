@@ -1,28 +1,54 @@
 use once_cell::sync::Lazy;
 use rusqlite::{params, Connection, Result};
-use std::path::Path;
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
 
+pub mod migrations;
 pub mod schema;
 
 pub use schema::*;
 
-/// 全局数据库连接
-pub static DB: Lazy<Mutex<Option<Connection>>> = Lazy::new(|| Mutex::new(None));
+/// 全局数据库连接（按工作区路径索引，多个窗口可以同时打开不同的工作区）
+pub static DB: Lazy<Mutex<HashMap<String, Connection>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// 初始化数据库
-pub fn init_db(workspace_path: &str) -> Result<()> {
-    let app_dir = Path::new(workspace_path).join(".app");
-    std::fs::create_dir_all(&app_dir).ok();
+/// 工作区数据目录的默认名称
+pub const DEFAULT_DATA_DIR: &str = ".app";
+
+/// 数据库文件名
+pub const DB_FILE_NAME: &str = "app.db";
+
+/// 工作区数据库文件路径：`<工作区>/<数据目录>/app.db`
+pub fn db_file_path(workspace_path: &str, data_dir: &str) -> PathBuf {
+    Path::new(workspace_path).join(data_dir).join(DB_FILE_NAME)
+}
+
+/// 初始化数据库（`data_dir` 为工作区下存放数据库的目录名）
+pub fn init_db(workspace_path: &str, data_dir: &str) -> Result<()> {
+    let db_path = db_file_path(workspace_path, data_dir);
+    if let Some(app_dir) = db_path.parent() {
+        std::fs::create_dir_all(app_dir).ok();
+    }
 
-    let db_path = app_dir.join("app.db");
     let conn = Connection::open(&db_path)?;
+    configure_connection(&conn)?;
+
+    let is_new = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'projects'",
+        [],
+        |row| row.get::<_, i32>(0),
+    )? == 0;
 
     // 创建表
     conn.execute_batch(SCHEMA)?;
 
-    // 执行迁移
+    // 执行迁移（版本化之前的列检查迁移保持幂等执行）
     run_migrations(&conn)?;
+    if is_new {
+        migrations::mark_latest(&conn)?;
+    } else {
+        migrations::apply_pending(&conn)?;
+    }
 
     // 插入默认目录类型
     insert_default_directory_types(&conn)?;
@@ -30,13 +56,21 @@ pub fn init_db(workspace_path: &str) -> Result<()> {
     // 插入内置模块
     insert_builtin_modules(&conn)?;
 
-    // 存储连接
+    // 存储连接（同一工作区重复打开时替换旧连接，不影响其他工作区）
     let mut db = DB.lock().unwrap();
-    *db = Some(conn);
+    db.insert(workspace_path.to_string(), conn);
 
     Ok(())
 }
 
+/// 连接级配置：WAL 允许读写并发，busy_timeout 避免后台任务与界面读写时立即报 "database is locked"
+fn configure_connection(conn: &Connection) -> Result<()> {
+    conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get::<_, String>(0))?;
+    conn.execute_batch("PRAGMA synchronous = NORMAL;")?;
+    conn.busy_timeout(std::time::Duration::from_millis(5000))?;
+    Ok(())
+}
+
 /// 执行数据库迁移
 fn run_migrations(conn: &Connection) -> Result<()> {
     // 迁移 1: 添加 custom_name 列到 git_repositories 表
@@ -370,7 +404,110 @@ fn insert_builtin_modules(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
-/// 获取数据库连接
-pub fn get_db() -> Result<std::sync::MutexGuard<'static, Option<Connection>>> {
-    Ok(DB.lock().unwrap())
+/// 指定工作区数据库连接的锁守卫
+pub struct DbGuard {
+    guard: MutexGuard<'static, HashMap<String, Connection>>,
+    workspace_path: String,
+}
+
+impl DbGuard {
+    /// 获取连接（工作区未打开时为 None）
+    pub fn as_ref(&self) -> Option<&Connection> {
+        self.guard.get(&self.workspace_path)
+    }
+
+    /// 获取可变连接，用于事务
+    pub fn as_mut(&mut self) -> Option<&mut Connection> {
+        self.guard.get_mut(&self.workspace_path)
+    }
+}
+
+/// 获取指定工作区的数据库连接
+pub fn get_db(workspace_path: &str) -> Result<DbGuard> {
+    Ok(DbGuard {
+        guard: DB.lock().unwrap(),
+        workspace_path: workspace_path.to_string(),
+    })
+}
+
+/// 指定工作区的数据目录（数据库文件所在目录）
+pub fn data_dir(workspace_path: &str) -> Option<PathBuf> {
+    let db_guard = get_db(workspace_path).ok()?;
+    let conn = db_guard.as_ref()?;
+    conn.path()
+        .and_then(|path| Path::new(path).parent().map(Path::to_path_buf))
+}
+
+/// 关闭指定工作区的数据库连接（先将 WAL 写回主库，关闭后数据库文件不再被占用）
+pub fn close_db(workspace_path: &str) {
+    let conn = DB.lock().unwrap().remove(workspace_path);
+    if let Some(conn) = conn {
+        let _ = conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);");
+        let _ = conn.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_configure_connection_pragmas() {
+        let temp_dir = TempDir::new().unwrap();
+        let conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+        configure_connection(&conn).unwrap();
+
+        let journal_mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        let synchronous: i64 = conn
+            .query_row("PRAGMA synchronous", [], |row| row.get(0))
+            .unwrap();
+        let busy_timeout: i64 = conn
+            .query_row("PRAGMA busy_timeout", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(journal_mode.to_lowercase(), "wal");
+        // NORMAL = 1
+        assert_eq!(synchronous, 1);
+        assert_eq!(busy_timeout, 5000);
+    }
+
+    #[test]
+    fn test_workspaces_keep_separate_connections() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        let first_path = first.path().to_string_lossy().to_string();
+        let second_path = second.path().to_string_lossy().to_string();
+        init_db(&first_path, DEFAULT_DATA_DIR).unwrap();
+        init_db(&second_path, DEFAULT_DATA_DIR).unwrap();
+
+        get_db(&first_path)
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .execute(
+                "INSERT INTO projects (id, name, project_path, created_at, updated_at)
+                 VALUES ('p1', 'a', '/a', 'now', 'now')",
+                [],
+            )
+            .unwrap();
+
+        let count = |path: &str| -> i64 {
+            get_db(path)
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .query_row("SELECT COUNT(*) FROM projects", [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(count(&first_path), 1);
+        assert_eq!(count(&second_path), 0);
+
+        close_db(&first_path);
+        assert!(get_db(&first_path).unwrap().as_ref().is_none());
+        assert_eq!(count(&second_path), 0);
+        close_db(&second_path);
+    }
 }
@@ -14,6 +14,8 @@ CREATE TABLE IF NOT EXISTS projects (
   display_json TEXT,
   ide_override_json TEXT,
   visible INTEGER NOT NULL DEFAULT 1,
+  archived INTEGER NOT NULL DEFAULT 0,
+  sort_order INTEGER NOT NULL DEFAULT 0,
   created_at TEXT NOT NULL,
   updated_at TEXT NOT NULL
 );
@@ -32,6 +34,9 @@ CREATE TABLE IF NOT EXISTS git_repositories (
   last_sync_at TEXT,
   last_status_checked_at TEXT,
   last_status_json TEXT,
+  last_commit_sha TEXT,
+  last_commit_summary TEXT,
+  last_commit_at TEXT,
   sort_order INTEGER NOT NULL DEFAULT 0,
   created_at TEXT NOT NULL,
   updated_at TEXT NOT NULL
@@ -57,9 +62,42 @@ CREATE TABLE IF NOT EXISTS project_directories (
   UNIQUE(project_id, dir_type_id)
 );
 
+CREATE TABLE IF NOT EXISTS project_tags (
+  project_id TEXT NOT NULL,
+  tag TEXT NOT NULL COLLATE NOCASE,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (project_id, tag)
+);
+
+CREATE TABLE IF NOT EXISTS project_templates (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  display_json TEXT,
+  directories_json TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS custom_themes (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  tokens_json TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS git_credentials (
+  host TEXT PRIMARY KEY,
+  username TEXT NOT NULL,
+  token TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
 CREATE INDEX IF NOT EXISTS idx_projects_updated_at ON projects(updated_at DESC);
 CREATE INDEX IF NOT EXISTS idx_projects_name ON projects(name);
 CREATE INDEX IF NOT EXISTS idx_git_repositories_project_id ON git_repositories(project_id);
+CREATE INDEX IF NOT EXISTS idx_git_repositories_project_path ON git_repositories(project_id, path);
 CREATE INDEX IF NOT EXISTS idx_directory_types_sort_order ON directory_types(sort_order);
 CREATE INDEX IF NOT EXISTS idx_project_directories_project_id ON project_directories(project_id);
 
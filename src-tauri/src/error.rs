@@ -0,0 +1,133 @@
+//! 命令错误类型
+//! 序列化为 `{ code, message }`，前端可按 code 区分错误种类，message 保留原有中文提示
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// 命令返回的结构化错误
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AppError {
+    /// 未打开工作区或数据库尚未初始化
+    #[error("{0}")]
+    WorkspaceNotOpen(String),
+    /// 记录、文件或目录不存在
+    #[error("{0}")]
+    NotFound(String),
+    /// 文件系统读写失败
+    #[error("{0}")]
+    Io(String),
+    /// git 操作失败
+    #[error("{0}")]
+    Git(String),
+    /// 数据库读写失败
+    #[error("{0}")]
+    Db(String),
+    /// 参数不合法或操作不被允许
+    #[error("{0}")]
+    Validation(String),
+    /// 后台任务或内部状态异常（与用户输入无关）
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::WorkspaceNotOpen(_) => "workspace_not_open",
+            AppError::NotFound(_) => "not_found",
+            AppError::Io(_) => "io",
+            AppError::Git(_) => "git",
+            AppError::Db(_) => "db",
+            AppError::Validation(_) => "validation",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    /// 查询单条记录失败：查无记录时为 NotFound，其余为 Db
+    pub fn lookup(e: &rusqlite::Error, message: String) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(message),
+            _ => AppError::Db(message),
+        }
+    }
+
+    /// 文件系统操作失败：目标不存在时为 NotFound，其余为 Io
+    pub fn io(e: &std::io::Error, message: String) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(message),
+            _ => AppError::Io(message),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::WorkspaceNotOpen(m)
+            | AppError::NotFound(m)
+            | AppError::Io(m)
+            | AppError::Git(m)
+            | AppError::Db(m)
+            | AppError::Validation(m)
+            | AppError::Internal(m) => m,
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound("记录不存在".to_string()),
+            e => AppError::Db(format!("数据库操作失败: {}", e)),
+        }
+    }
+}
+
+impl From<git2::Error> for AppError {
+    fn from(e: git2::Error) -> Self {
+        AppError::Git(e.message().to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        let message = e.to_string();
+        AppError::io(&e, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_code_and_message() {
+        let value = serde_json::to_value(AppError::NotFound("项目不存在".into())).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "code": "not_found", "message": "项目不存在" })
+        );
+    }
+
+    #[test]
+    fn test_lookup_and_io_pick_variant_by_source_error() {
+        let missing = AppError::lookup(&rusqlite::Error::QueryReturnedNoRows, "仓库不存在".into());
+        assert_eq!(missing.code(), "not_found");
+        let busy = AppError::lookup(&rusqlite::Error::InvalidQuery, "查询失败".into());
+        assert_eq!(busy.code(), "db");
+
+        let not_found = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(
+            AppError::io(&not_found, "读取失败".into()).code(),
+            "not_found"
+        );
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert_eq!(AppError::io(&denied, "创建目录失败".into()).code(), "io");
+    }
+}
@@ -1,5 +1,6 @@
 mod commands;
 mod db;
+mod error;
 mod types;
 
 use commands::*;
@@ -19,6 +20,13 @@ pub fn run() {
     // Register global watcher state for directory watching
     builder = builder.manage(git::WatcherState::new());
 
+    // Release the workspace a window had open once that window is gone
+    builder = builder.on_window_event(|window, event| {
+        if let tauri::WindowEvent::Destroyed = event {
+            workspace::release_window(window.label());
+        }
+    });
+
     builder.invoke_handler(tauri::generate_handler![
             // Workspace commands
             workspace_init_or_open,
@@ -27,54 +35,111 @@ pub fn run() {
             workspace_settings_update,
             workspace_update_alias,
             workspace_remove_from_recent,
+            workspace_relocate,
+            workspace_set_pinned,
             workspace_get_current,
+            workspace_close,
+            workspace_search,
+            workspace_export,
+            workspace_import,
+            workspace_vacuum,
+            workspace_stats,
             // Global settings commands
             global_settings_get,
             global_settings_update,
+            // Theme commands
+            theme_save,
+            theme_list,
+            theme_delete,
             // Project commands
             projects_list,
             project_create,
+            project_import,
             project_get,
+            project_duplicate,
+            project_template_save,
+            project_templates_list,
+            project_create_from_template,
             project_update,
             project_delete,
+            projects_reorder,
             project_show,
+            project_set_archived,
+            projects_bulk_delete,
+            projects_bulk_set_archived,
+            project_tags_list,
+            project_add_tag,
+            project_remove_tag,
             // Git commands
             git_repo_list,
             git_repo_create,
             git_repo_clone,
+            git_clone_cancel,
             git_repo_update,
+            git_repo_rename,
+            git_repo_set_description,
             git_repo_reorder,
             git_extract_repo_name,
             git_repo_pull,
+            git_repo_fetch,
+            git_repo_push,
+            git_credentials_set,
+            git_credentials_provide,
+            git_credentials_cancel,
+            git_log,
+            git_branch_list,
+            git_checkout_branch,
             git_repo_status_get,
+            git_repo_refresh_all,
             git_repo_status_check,
+            git_repo_changes,
+            git_remote_list,
+            git_remote_set_url,
+            git_tag_list,
+            git_diff,
+            git_repo_stash_save,
+            git_repo_stash_pop,
             git_status_watch_start,
             git_status_watch_stop,
             git_repo_scan,
+            git_repo_import,
             git_repo_delete,
             watch_directory,
             unwatch_directory,
             // Filesystem commands
             project_fs_tree,
             fs_read_text,
+            fs_write_text,
+            fs_search,
+            fs_watch_start,
+            fs_watch_stop,
             fs_read_binary,
+            fs_read_bytes,
             fs_create_dir,
             fs_create_file,
             fs_delete,
             fs_rename,
+            fs_move,
+            fs_copy,
             fs_open_external,
             fs_copy_file,
             // Directory type commands
             dir_types_list,
             dir_type_create_custom,
             dir_type_update,
+            dir_type_delete,
+            dir_types_reorder,
             project_dirs_list,
             project_dir_create_or_update,
+            project_dir_delete,
             project_dirs_sync_auto,
             preview_detect,
+            preview_thumbnail,
             // IDE commands
             ide_list_supported,
             ide_open_repo,
+            ide_open_project,
+            ide_open_file,
             ide_preview,
             open_in_terminal,
             // Module commands
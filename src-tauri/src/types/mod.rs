@@ -35,6 +35,7 @@ pub struct IdeConfig {
     pub kind: SupportedIdeKind,
     pub name: String,
     pub command: String,
+    /// 启动参数，可包含 `{path}` 占位符（无占位符时路径追加在最后）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub args: Option<Vec<String>>,
     /// 是否可用（CLI 命令在 PATH 中可用）
@@ -50,6 +51,17 @@ pub struct WorkspaceSettings {
     pub custom_theme_id: Option<String>,
     pub default_ide: Option<IdeConfig>,
     pub auto_fetch_git_projects: Option<bool>,
+    /// SSH 私钥路径（ssh-agent 不可用时用于 SSH 远程认证，如 ~/.ssh/id_ed25519）
+    pub ssh_key_path: Option<String>,
+    /// 提交作者名称（未设置时使用仓库的 git 配置 user.name）
+    pub author_name: Option<String>,
+    /// 提交作者邮箱（未设置时使用仓库的 git 配置 user.email）
+    pub author_email: Option<String>,
+}
+
+/// 最近工作区数量上限的默认值
+pub fn default_recent_limit() -> usize {
+    10
 }
 
 impl Default for WorkspaceSettings {
@@ -59,6 +71,9 @@ impl Default for WorkspaceSettings {
             custom_theme_id: None,
             default_ide: None,
             auto_fetch_git_projects: None,
+            ssh_key_path: None,
+            author_name: None,
+            author_email: None,
         }
     }
 }
@@ -72,6 +87,37 @@ pub struct WorkspaceInfo {
     pub last_opened_at: String,
     pub settings: Option<WorkspaceSettings>,
     pub alias: Option<String>,
+    /// 是否固定在最近列表顶部（固定项不会被数量上限挤出）
+    #[serde(default)]
+    pub pinned: bool,
+    /// 工作区目录是否仍然存在（列出最近工作区时计算）
+    #[serde(default)]
+    pub exists: bool,
+}
+
+/// 工作区统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStats {
+    pub project_count: i64,
+    pub repo_count: i64,
+    pub dir_type_count: i64,
+    /// 所有仓库目录的总大小（字节），来自缓存，refresh 时重新计算
+    pub total_repo_size_bytes: u64,
+    /// 仓库大小的计算时间，从未计算过时为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_computed_at: Option<String>,
+}
+
+/// 自定义主题，tokens 为颜色令牌（如 `primary` -> `#3b82f6`），由前端解释
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomTheme {
+    pub id: String,
+    pub name: String,
+    pub tokens: std::collections::BTreeMap<String, String>,
+    pub created_at: String,
+    pub updated_at: String,
 }
 
 /// 项目显示配置
@@ -108,6 +154,16 @@ pub struct GitRepository {
     pub ide_override: Option<IdeConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_order: Option<i32>,
+    /// 用户自定义显示名称（优先于 name 显示）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_name: Option<String>,
+    /// HEAD 提交信息，状态检查和拉取时缓存，空仓库为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_sha: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_at: Option<String>,
 }
 
 /// 网络状态
@@ -124,8 +180,14 @@ pub enum NetworkState {
 #[serde(rename_all = "camelCase")]
 pub struct GitRepoStatus {
     pub repo_id: String,
+    /// 当前分支，HEAD 分离时为 None
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branch: Option<String>,
+    /// HEAD 是否处于分离状态（此时 head_sha 为当前提交）
+    #[serde(default)]
+    pub detached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_sha: Option<String>,
     pub dirty: bool,
     pub ahead: i32,
     pub behind: i32,
@@ -149,7 +211,35 @@ pub struct Project {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ide_override: Option<IdeConfig>,
     pub visible: bool,
+    pub created_at: String,
     pub updated_at: String,
+    /// 是否已归档（归档项目默认不在列表中显示）
+    #[serde(default)]
+    pub archived: bool,
+    /// 手动排序序号（按 manual 方式列出时使用）
+    #[serde(default)]
+    pub sort_order: i32,
+    /// 项目标签（比较时不区分大小写）
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// 批量操作中单个项目的结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkItemResult {
+    pub id: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 批量项目操作结果：任一项目失败时整体回滚，committed 为 false
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkResult {
+    pub committed: bool,
+    pub results: Vec<BulkItemResult>,
 }
 
 /// 文件节点
@@ -161,6 +251,15 @@ pub struct FileNode {
     pub kind: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub children: Option<Vec<FileNode>>,
+    /// 文件大小（字节），目录为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// 最后修改时间（RFC 3339）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<String>,
+    /// 目录的子项总数（分页时 children 只包含其中一页）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_children: Option<usize>,
 }
 
 /// Git 克隆输入
@@ -177,6 +276,12 @@ pub struct GitCloneInput {
     pub branch: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// 浅克隆深度（仅获取最近 N 次提交）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
+    /// 客户端生成的操作 ID，用于 git_clone_cancel 取消本次克隆
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
 }
 
 /// Git 克隆阶段
@@ -190,6 +295,7 @@ pub enum GitCloneStage {
     CheckingOut,
     Completed,
     Failed,
+    Cancelled,
 }
 
 /// Git 克隆进度
@@ -203,6 +309,16 @@ pub struct GitCloneProgress {
     pub error: Option<String>,
 }
 
+/// Git 克隆传输进度（对象接收情况）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCloneTransferProgress {
+    pub repo_target: String,
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub bytes: usize,
+}
+
 /// Git 拉取结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -216,6 +332,128 @@ pub struct GitPullResult {
     pub error: Option<String>,
 }
 
+/// Git 推送结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitPushResult {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pushed_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Git 提交记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommit {
+    pub sha: String,
+    pub short_sha: String,
+    pub summary: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: String,
+}
+
+/// Git 分支列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitBranchList {
+    pub local: Vec<String>,
+    pub remote: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+}
+
+/// Git 标签
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitTag {
+    pub name: String,
+    pub target_sha: String,
+    /// 附注标签的说明（轻量标签为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Git 远程
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitRemote {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// 文件变更类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeStatus {
+    New,
+    Modified,
+    Deleted,
+    Renamed,
+    Untracked,
+}
+
+/// 工作区中的单个文件变更
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub path: String,
+    pub status: FileChangeStatus,
+    pub staged: bool,
+}
+
+/// 搜索命中类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchHitKind {
+    /// 项目名称
+    Project,
+    /// 项目描述
+    Description,
+    /// 项目目录
+    Directory,
+}
+
+/// 工作区搜索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub project_id: String,
+    pub project_name: String,
+    pub kind: SearchHitKind,
+    /// 命中的文本片段
+    pub snippet: String,
+    /// 排序分值（越大越相关）
+    pub score: i32,
+}
+
+/// 文件内容搜索的单条匹配
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsSearchMatch {
+    /// 相对项目根目录的路径（以 `/` 分隔）
+    pub path: String,
+    /// 行号（从 1 开始）
+    pub line_number: usize,
+    pub line_preview: String,
+}
+
+/// 文件内容搜索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsSearchResult {
+    pub matches: Vec<FsSearchMatch>,
+    /// 是否因达到匹配数或文件数上限而提前结束
+    pub truncated: bool,
+}
+
 /// 目录类型种类
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -239,6 +477,9 @@ pub struct DirectoryType {
     pub sort_order: i32,
     pub created_at: String,
     pub updated_at: String,
+    /// 使用该类型的项目数
+    #[serde(default)]
+    pub usage_count: i64,
 }
 
 /// 项目目录
@@ -253,6 +494,27 @@ pub struct ProjectDirectory {
     pub updated_at: String,
 }
 
+/// 项目模板中的目录映射
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTemplateDir {
+    pub dir_type_id: String,
+    pub relative_path: String,
+}
+
+/// 项目模板（目录布局 + 显示设置）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTemplate {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<ProjectDisplay>,
+    pub directories: Vec<ProjectTemplateDir>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
 /// 预览类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -263,6 +525,8 @@ pub enum PreviewKind {
     Pdf,
     Word,
     Excel,
+    Video,
+    Audio,
 }
 
 /// 预览检测结果
@@ -270,6 +534,9 @@ pub enum PreviewKind {
 #[serde(rename_all = "camelCase")]
 pub struct PreviewDetectResult {
     pub kind: PreviewKind,
+    /// 文本类文件的语言（用于语法高亮），无法判断时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
 }
 
 #[cfg(test)]
@@ -374,6 +641,9 @@ mod tests {
             name: "file.rs".to_string(),
             kind: "file".to_string(),
             children: None,
+            size: Some(42),
+            modified_at: None,
+            total_children: None,
         };
 
         assert_eq!(node.kind, "file");
@@ -384,10 +654,16 @@ mod tests {
             name: "test".to_string(),
             kind: "dir".to_string(),
             children: Some(vec![]),
+            size: None,
+            modified_at: None,
+            total_children: Some(0),
         };
 
         assert_eq!(dir_node.kind, "dir");
         assert!(dir_node.children.is_some());
+
+        let json = serde_json::to_string(&dir_node).unwrap();
+        assert!(!json.contains("size"));
     }
 
     #[test]
@@ -398,6 +674,8 @@ mod tests {
             target_directory: None,
             branch: Some("main".to_string()),
             name: Some("My Repo".to_string()),
+            depth: None,
+            operation_id: None,
         };
 
         let json = serde_json::to_string(&input).unwrap();
@@ -415,10 +693,12 @@ mod tests {
             sort_order: 1,
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
+            usage_count: 2,
         };
 
         let json = serde_json::to_string(&dt).unwrap();
         assert!(json.contains("文档"));
+        assert!(json.contains("\"usageCount\":2"));
     }
 
     #[test]
@@ -2,9 +2,14 @@ import { invoke } from '@tauri-apps/api/core'
 import type {
   WorkspaceInfo,
   WorkspaceSettings,
+  WorkspaceStats,
+  CustomTheme,
+  SearchHit,
   Project,
   ProjectCreateInput,
   ProjectUpdateInput,
+  ProjectTemplate,
+  BulkResult,
   GitRepository,
   GitCloneInput,
   GitPullResult,
@@ -18,6 +23,7 @@ import type {
   PreviewKind,
   IdeConfig,
   FsResult,
+  FsSearchResult,
   // New module system types
   Module,
   ModuleCreateInput,
@@ -35,8 +41,9 @@ import type {
 
 // Workspace API
 export const workspaceApi = {
-  async initOrOpen(path: string): Promise<WorkspaceInfo> {
-    return invoke('workspace_init_or_open', { path })
+  /** dataDir overrides the database directory name (defaults to `.app`) */
+  async initOrOpen(path: string, dataDir?: string): Promise<WorkspaceInfo> {
+    return invoke('workspace_init_or_open', { path, dataDir })
   },
 
   async listRecent(): Promise<WorkspaceInfo[]> {
@@ -59,15 +66,74 @@ export const workspaceApi = {
     return invoke('workspace_update_alias', { path, alias })
   },
 
+  async setPinned(path: string, pinned: boolean): Promise<WorkspaceInfo> {
+    return invoke('workspace_set_pinned', { path, pinned })
+  },
+
   async removeFromRecent(path: string): Promise<void> {
     return invoke('workspace_remove_from_recent', { path })
   },
+
+  /** Re-points the recent entry after the folder was moved; does not move files */
+  async relocate(oldPath: string, newPath: string): Promise<WorkspaceInfo> {
+    return invoke('workspace_relocate', { oldPath, newPath })
+  },
+
+  async close(): Promise<void> {
+    return invoke('workspace_close')
+  },
+
+  async search(query: string): Promise<SearchHit[]> {
+    return invoke('workspace_search', { query })
+  },
+
+  async exportBackup(destPath: string): Promise<{ ok: boolean; path: string; size: number }> {
+    return invoke('workspace_export', { destPath })
+  },
+
+  async importBackup(
+    archivePath: string,
+    targetPath: string,
+    overwrite: boolean = false
+  ): Promise<WorkspaceInfo> {
+    return invoke('workspace_import', { archivePath, targetPath, overwrite })
+  },
+
+  /** Pass refresh to recompute the cached repository size */
+  async stats(refresh: boolean = false): Promise<WorkspaceStats> {
+    return invoke('workspace_stats', { refresh })
+  },
+
+  /** Compacts the workspace database; sizes are in bytes */
+  async vacuum(): Promise<{ ok: boolean; sizeBefore: number; sizeAfter: number }> {
+    return invoke('workspace_vacuum')
+  },
+}
+
+// Theme API
+export const themeApi = {
+  /** tokensJson is a JSON object of color tokens; omit id to create a new theme */
+  async save(name: string, tokensJson: string, id?: string): Promise<CustomTheme> {
+    return invoke('theme_save', { id, name, tokensJson })
+  },
+
+  async list(): Promise<CustomTheme[]> {
+    return invoke('theme_list')
+  },
+
+  async delete(id: string): Promise<{ ok: boolean }> {
+    return invoke('theme_delete', { id })
+  },
 }
 
 // Project API
 export const projectApi = {
-  async list(): Promise<Project[]> {
-    return invoke('projects_list')
+  async list(
+    includeArchived: boolean = false,
+    tag?: string,
+    sort: 'recent' | 'manual' = 'recent'
+  ): Promise<Project[]> {
+    return invoke('projects_list', { includeArchived, tag, sort })
   },
 
   async create(input: ProjectCreateInput): Promise<Project> {
@@ -82,13 +148,62 @@ export const projectApi = {
     return invoke('project_update', { id, patch })
   },
 
-  async delete(id: string): Promise<{ ok: boolean }> {
-    return invoke('project_delete', { id })
+  async import(path: string, name?: string): Promise<Project> {
+    return invoke('project_import', { path, name })
+  },
+
+  async duplicate(id: string, newName: string): Promise<Project> {
+    return invoke('project_duplicate', { id, newName })
+  },
+
+  async saveTemplate(projectId: string, templateName: string): Promise<ProjectTemplate> {
+    return invoke('project_template_save', { projectId, templateName })
+  },
+
+  async listTemplates(): Promise<ProjectTemplate[]> {
+    return invoke('project_templates_list')
+  },
+
+  async createFromTemplate(name: string, templateName: string): Promise<Project> {
+    return invoke('project_create_from_template', { name, templateName })
+  },
+
+  async reorder(orderedIds: string[]): Promise<Project[]> {
+    return invoke('projects_reorder', { orderedIds })
+  },
+
+  async delete(id: string, deleteFiles?: boolean): Promise<{ ok: boolean }> {
+    return invoke('project_delete', { id, deleteFiles })
   },
 
   async show(id: string): Promise<Project> {
     return invoke('project_show', { id })
   },
+
+  async setArchived(id: string, archived: boolean): Promise<Project> {
+    return invoke('project_set_archived', { id, archived })
+  },
+
+  /** Runs in one transaction; nothing is applied unless every id succeeds */
+  async bulkDelete(ids: string[]): Promise<BulkResult> {
+    return invoke('projects_bulk_delete', { ids })
+  },
+
+  async bulkSetArchived(ids: string[], archived: boolean): Promise<BulkResult> {
+    return invoke('projects_bulk_set_archived', { ids, archived })
+  },
+
+  async listTags(projectId: string): Promise<string[]> {
+    return invoke('project_tags_list', { projectId })
+  },
+
+  async addTag(projectId: string, tag: string): Promise<string[]> {
+    return invoke('project_add_tag', { projectId, tag })
+  },
+
+  async removeTag(projectId: string, tag: string): Promise<string[]> {
+    return invoke('project_remove_tag', { projectId, tag })
+  },
 }
 
 // Git types
@@ -104,18 +219,39 @@ export const gitApi = {
     return invoke('git_repo_list', { projectId, folder: folder || null })
   },
 
-  async repoCreate(projectId: string, name: string): Promise<GitRepository> {
-    return invoke('git_repo_create', { projectId, name })
+  async repoCreate(
+    projectId: string,
+    name: string,
+    initialCommit: boolean = false,
+    gitignoreTemplate?: string
+  ): Promise<GitRepository> {
+    return invoke('git_repo_create', { projectId, name, initialCommit, gitignoreTemplate })
   },
 
   async repoClone(projectId: string, input: GitCloneInput): Promise<GitRepository> {
     return invoke('git_repo_clone', { projectId, input })
   },
 
+  async cloneCancel(operationId: string): Promise<{ ok: boolean; cancelled: boolean }> {
+    return invoke('git_clone_cancel', { operationId })
+  },
+
   async repoUpdate(repoId: string, patch: GitRepoUpdateInput): Promise<GitRepository> {
     return invoke('git_repo_update', { repoId, patch })
   },
 
+  async repoImport(projectId: string, relativePath: string): Promise<GitRepository> {
+    return invoke('git_repo_import', { projectId, relativePath })
+  },
+
+  async repoRename(repoId: string, customName: string | null): Promise<GitRepository> {
+    return invoke('git_repo_rename', { repoId, customName })
+  },
+
+  async repoSetDescription(repoId: string, description: string | null): Promise<GitRepository> {
+    return invoke('git_repo_set_description', { repoId, description })
+  },
+
   async repoReorder(projectId: string, orderedIds: string[]): Promise<GitRepository[]> {
     return invoke('git_repo_reorder', { projectId, orderedIds })
   },
@@ -128,6 +264,20 @@ export const gitApi = {
     return invoke('git_repo_pull', { repoId })
   },
 
+  /** Answers a `git://credentials-needed` event; the credentials are used once and not stored */
+  async credentialsProvide(
+    operationId: string,
+    username: string,
+    secret: string
+  ): Promise<{ ok: boolean }> {
+    return invoke('git_credentials_provide', { operationId, username, secret })
+  },
+
+  /** Declines a `git://credentials-needed` event; the waiting operation fails as cancelled */
+  async credentialsCancel(operationId: string): Promise<{ ok: boolean }> {
+    return invoke('git_credentials_cancel', { operationId })
+  },
+
   async scan(projectId: string): Promise<{ ok: boolean; scanned: string[] }> {
     return invoke('git_repo_scan', { projectId })
   },
@@ -140,11 +290,16 @@ export const gitApi = {
     return invoke('git_repo_status_check', { repoId })
   },
 
-  async repoDelete(repoId: string, deleteLocal: boolean = false): Promise<{ ok: boolean }> {
-    return invoke('git_repo_delete', { repoId, deleteLocal })
+  /** Local status of every repo in the project; per-repo failures are reported in lastError */
+  async repoRefreshAll(projectId: string): Promise<GitRepoStatus[]> {
+    return invoke('git_repo_refresh_all', { projectId })
+  },
+
+  async repoDelete(repoId: string, deleteFiles: boolean = false): Promise<{ ok: boolean }> {
+    return invoke('git_repo_delete', { repoId, deleteFiles })
   },
 
-  async statusWatchStart(repoId?: string): Promise<{ ok: boolean }> {
+  async statusWatchStart(repoId: string): Promise<{ ok: boolean }> {
     return invoke('git_status_watch_start', { repoId })
   },
 
@@ -163,18 +318,69 @@ export const gitApi = {
 
 // Filesystem API
 export const fsApi = {
-  async tree(projectId: string, relativeRoot: string): Promise<FileNode> {
-    return invoke('project_fs_tree', { projectId, relativeRoot })
+  async tree(
+    projectId: string,
+    relativeRoot: string,
+    respectGitignore: boolean = true,
+    exclude?: string[],
+    offset?: number,
+    limit?: number,
+    /** Roots the tree at this directory type's mapped path; relativeRoot is then relative to it */
+    dirTypeId?: string
+  ): Promise<FileNode> {
+    return invoke('project_fs_tree', {
+      projectId,
+      relativeRoot,
+      respectGitignore,
+      exclude,
+      offset,
+      limit,
+      dirTypeId,
+    })
   },
 
-  async readText(path: string): Promise<{ content: string }> {
+  async readText(path: string): Promise<{
+    content: string
+    binary: boolean
+    truncated?: boolean
+    lossy?: boolean
+    size: number
+  }> {
     return invoke('fs_read_text', { path })
   },
 
+  async writeText(path: string, content: string): Promise<{ ok: boolean; bytes: number }> {
+    return invoke('fs_write_text', { path, content })
+  },
+
+  async search(
+    projectId: string,
+    query: string,
+    caseSensitive: boolean = false
+  ): Promise<FsSearchResult> {
+    return invoke('fs_search', { projectId, query, caseSensitive })
+  },
+
+  async watchStart(projectId: string): Promise<{ ok: boolean; started: boolean }> {
+    return invoke('fs_watch_start', { projectId })
+  },
+
+  async watchStop(projectId: string): Promise<{ ok: boolean; stopped: number }> {
+    return invoke('fs_watch_stop', { projectId })
+  },
+
   async readBinary(path: string): Promise<{ data: string }> {
     return invoke('fs_read_binary', { path })
   },
 
+  /** Base64 of at most maxBytes from the start of the file (capped at 5MB) */
+  async readBytes(
+    path: string,
+    maxBytes: number
+  ): Promise<{ data: string; truncated: boolean; size: number }> {
+    return invoke('fs_read_bytes', { path, maxBytes })
+  },
+
   async createDir(projectId: string, relativePath: string): Promise<FsResult> {
     return invoke('fs_create_dir', { projectId, relativePath })
   },
@@ -183,8 +389,29 @@ export const fsApi = {
     return invoke('fs_create_file', { projectId, relativePath })
   },
 
-  async delete(path: string): Promise<FsResult> {
-    return invoke('fs_delete', { path })
+  async delete(
+    path: string,
+    toTrash: boolean = true
+  ): Promise<FsResult & { mode: 'trash' | 'permanent' }> {
+    return invoke('fs_delete', { path, toTrash })
+  },
+
+  async move(
+    projectId: string,
+    fromRelative: string,
+    toRelative: string,
+    overwrite: boolean = false
+  ): Promise<FsResult & { path: string }> {
+    return invoke('fs_move', { projectId, fromRelative, toRelative, overwrite })
+  },
+
+  async copy(
+    projectId: string,
+    fromRelative: string,
+    toRelative: string,
+    overwrite: boolean = false
+  ): Promise<FsResult & { relativePath: string }> {
+    return invoke('fs_copy', { projectId, fromRelative, toRelative, overwrite })
   },
 
   async rename(oldPath: string, newName: string): Promise<FsResult> {
@@ -214,6 +441,14 @@ export const dirTypeApi = {
     return invoke('dir_type_update', { id, patch })
   },
 
+  async delete(id: string, force?: boolean): Promise<{ ok: boolean }> {
+    return invoke('dir_type_delete', { id, force })
+  },
+
+  async reorder(orderedIds: string[]): Promise<DirectoryType[]> {
+    return invoke('dir_types_reorder', { orderedIds })
+  },
+
   async listProjectDirs(projectId: string): Promise<ProjectDirectory[]> {
     return invoke('project_dirs_list', { projectId })
   },
@@ -225,6 +460,14 @@ export const dirTypeApi = {
     return invoke('project_dir_create_or_update', { projectId, input })
   },
 
+  async deleteProjectDir(
+    projectId: string,
+    dirTypeId: string,
+    deleteFiles: boolean = false
+  ): Promise<{ ok: boolean }> {
+    return invoke('project_dir_delete', { projectId, dirTypeId, deleteFiles })
+  },
+
   async syncAuto(projectId: string): Promise<{ ok: boolean; synced: string[] }> {
     return invoke('project_dirs_sync_auto', { projectId })
   },
@@ -232,9 +475,14 @@ export const dirTypeApi = {
 
 // Preview API
 export const previewApi = {
-  async detect(path: string): Promise<{ kind: PreviewKind }> {
+  async detect(path: string): Promise<{ kind: PreviewKind; language?: string }> {
     return invoke('preview_detect', { path })
   },
+
+  /** Returns a PNG data URL scaled to fit within maxDim */
+  async thumbnail(path: string, maxDim: number): Promise<string> {
+    return invoke('preview_thumbnail', { path, maxDim })
+  },
 }
 
 // IDE API
@@ -243,10 +491,30 @@ export const ideApi = {
     return invoke('ide_list_supported')
   },
 
-  async openRepo(repoId: string, ide?: IdeConfig): Promise<{ ok: boolean; message?: string }> {
+  async openRepo(
+    repoId: string,
+    ide?: IdeConfig
+  ): Promise<{ ok: boolean; message?: string; defaultIdeMissing?: boolean }> {
     return invoke('ide_open_repo', { repoId, ide })
   },
 
+  /** Opens the project folder; falls back from the project's ideOverride to the default IDE */
+  async openProject(
+    projectId: string,
+    ide?: IdeConfig
+  ): Promise<{ ok: boolean; message?: string; defaultIdeMissing?: boolean }> {
+    return invoke('ide_open_project', { projectId, ide })
+  },
+
+  async openFile(
+    repoId: string,
+    relativePath: string,
+    line?: number,
+    ide?: IdeConfig
+  ): Promise<{ ok: boolean; message?: string }> {
+    return invoke('ide_open_file', { repoId, relativePath, line, ide })
+  },
+
   async openInTerminal(repoId: string): Promise<{ ok: boolean; message?: string }> {
     return invoke('open_in_terminal', { repoId })
   },
@@ -6,10 +6,19 @@ export type SupportedIdeKind = 'vscode' | 'idea' | 'webstorm' | 'trae' | 'zed' |
 // Re-export locale types
 export type { LocaleCode } from './locale'
 
+/** Error returned by every backend command */
+export type AppErrorCode = 'workspace_not_open' | 'not_found' | 'io' | 'git' | 'db' | 'validation' | 'internal'
+
+export type AppError = {
+  code: AppErrorCode
+  message: string
+}
+
 export type IdeConfig = {
   kind: SupportedIdeKind
   name: string
   command: string
+  /** Launch arguments; `{path}` is replaced with the target path, otherwise the path is appended */
   args?: string[]
   /** Whether the IDE is available on the system (has a working CLI command) */
   available?: boolean
@@ -20,6 +29,10 @@ export type WorkspaceSettings = {
   customThemeId?: string
   defaultIde?: IdeConfig
   autoFetchGitProjects?: boolean
+  sshKeyPath?: string
+  /** Commit author; falls back to the repository's git config when unset */
+  authorName?: string
+  authorEmail?: string
 }
 
 export type WorkspaceInfo = {
@@ -28,6 +41,34 @@ export type WorkspaceInfo = {
   lastOpenedAt: string
   settings?: WorkspaceSettings
   alias?: string
+  pinned?: boolean
+  exists?: boolean
+}
+
+export type WorkspaceStats = {
+  projectCount: number
+  repoCount: number
+  dirTypeCount: number
+  /** Cached total size of all repository directories */
+  totalRepoSizeBytes: number
+  sizeComputedAt?: string
+}
+
+export type CustomTheme = {
+  id: string
+  name: string
+  /** Color tokens, e.g. { primary: '#3b82f6' } */
+  tokens: Record<string, string>
+  createdAt: string
+  updatedAt: string
+}
+
+export type SearchHit = {
+  projectId: string
+  projectName: string
+  kind: 'project' | 'description' | 'directory'
+  snippet: string
+  score: number
 }
 
 export type ProjectDisplay = {
@@ -49,13 +90,22 @@ export type GitRepository = {
   lastStatusCheckedAt?: string
   ideOverride?: IdeConfig
   sortOrder?: number
+  customName?: string
+  /** HEAD commit, cached on status checks and pulls */
+  lastCommitSha?: string
+  lastCommitSummary?: string
+  lastCommitAt?: string
 }
 
 export type NetworkState = 'online' | 'offline' | 'unknown'
 
 export type GitRepoStatus = {
   repoId: string
+  /** Unset when HEAD is detached */
   branch?: string
+  detached: boolean
+  /** Current commit when HEAD is detached */
+  headSha?: string
   dirty: boolean
   ahead: number
   behind: number
@@ -64,6 +114,18 @@ export type GitRepoStatus = {
   lastError?: string
 }
 
+export type BulkItemResult = {
+  id: string
+  ok: boolean
+  error?: string
+}
+
+/** committed is false when any item failed and the whole batch was rolled back */
+export type BulkResult = {
+  committed: boolean
+  results: BulkItemResult[]
+}
+
 export type Project = {
   id: string
   name: string
@@ -72,7 +134,11 @@ export type Project = {
   display?: ProjectDisplay
   ideOverride?: IdeConfig
   visible: boolean
+  createdAt: string
   updatedAt: string
+  archived?: boolean
+  sortOrder?: number
+  tags?: string[]
 }
 
 export type FileNode = {
@@ -80,6 +146,9 @@ export type FileNode = {
   name: string
   kind: 'file' | 'dir'
   children?: FileNode[]
+  size?: number
+  modifiedAt?: string
+  totalChildren?: number
 }
 
 export type GitCloneInput = {
@@ -89,6 +158,17 @@ export type GitCloneInput = {
   targetDirectory?: string
   branch?: string
   name?: string
+  /** Client-generated id that lets gitApi.cloneCancel abort this clone */
+  operationId?: string
+}
+
+/** Payload of the `git://credentials-needed` event emitted during clone/pull/fetch/push */
+export type GitCredentialsNeeded = {
+  operationId: string
+  /** null while cloning, before the repository is registered */
+  repoId: string | null
+  url: string
+  username?: string | null
 }
 
 export type GitPullResult = {
@@ -106,6 +186,7 @@ export type GitCloneStage =
   | 'checking_out'
   | 'completed'
   | 'failed'
+  | 'cancelled'
 
 export type GitCloneProgress = {
   stage: GitCloneStage
@@ -126,6 +207,8 @@ export type DirectoryType = {
   sortOrder: number
   createdAt: string
   updatedAt: string
+  /** Number of projects using this directory type */
+  usageCount: number
 }
 
 export type ProjectDirectory = {
@@ -137,7 +220,29 @@ export type ProjectDirectory = {
   updatedAt: string
 }
 
-export type PreviewKind = 'image' | 'markdown' | 'text' | 'pdf' | 'word' | 'excel'
+export type ProjectTemplateDir = {
+  dirTypeId: string
+  relativePath: string
+}
+
+export type ProjectTemplate = {
+  id: string
+  name: string
+  display?: ProjectDisplay
+  directories: ProjectTemplateDir[]
+  createdAt: string
+  updatedAt: string
+}
+
+export type PreviewKind =
+  | 'image'
+  | 'markdown'
+  | 'text'
+  | 'pdf'
+  | 'word'
+  | 'excel'
+  | 'video'
+  | 'audio'
 
 // API input types
 export type ProjectCreateInput = {
@@ -164,6 +269,17 @@ export type ProjectDirCreateInput = {
 }
 
 // File system types
+export type FsSearchMatch = {
+  path: string
+  lineNumber: number
+  linePreview: string
+}
+
+export type FsSearchResult = {
+  matches: FsSearchMatch[]
+  truncated: boolean
+}
+
 export type FsResult = {
   ok: boolean
   message?: string
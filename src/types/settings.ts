@@ -22,6 +22,8 @@ export type GlobalSettings = {
   defaultIde?: IdeConfig
   markdownTheme?: MarkdownThemeConfig
   autoFetchGitProjects?: boolean
+  /** Maximum number of entries kept in the recent workspaces list */
+  recentLimit?: number
 }
 
 /**